@@ -14,7 +14,7 @@ use bevy_egui::{
     egui::{Slider, Window},
 };
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
-use bevy_shader_mtoon::{MtoonBundle, MtoonMaterial, MtoonPlugin, MtoonSun, VrmOutlineMode};
+use bevy_shader_mtoon::{MtoonBundle, MtoonMaterial, MtoonPlugin, VrmOutlineMode};
 
 fn main() {
     App::new()
@@ -54,7 +54,6 @@ fn setup(
             ..default()
         },
         Transform::from_rotation(Quat::from_rotation_x(-PI / 3.0)),
-        MtoonSun,
     ));
 
     let mtoon_textured = MtoonBundle {
@@ -71,7 +70,7 @@ fn setup(
         mtoon: MeshMaterial3d(mtoon_materials.add(MtoonMaterial {
             base_color: BISQUE.into(),
             shade_factor: SALMON.into(),
-            outline_width: 0.2,
+            outline_width: 0.005,
             outline_mode: VrmOutlineMode::World,
             ..default()
         })),
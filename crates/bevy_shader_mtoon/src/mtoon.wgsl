@@ -1,8 +1,18 @@
 #import bevy_pbr::{
     pbr_fragment::pbr_input_from_vertex_output,
-    mesh_view_bindings::view,
-    ambient::ambient_light,
+    mesh_view_bindings::{view, globals, lights, clusterable_objects},
+    mesh_view_types,
+    mesh_types::MESH_FLAGS_SHADOW_RECEIVER_BIT,
+    clustered_forward as clustering,
+    lighting,
+    lighting::{LAYER_BASE, getDistanceAttenuation},
+    shadows,
 }
+#import bevy_render::maths::PI
+
+#ifdef ENVIRONMENT_MAP
+#import bevy_pbr::environment_map
+#endif
 
 #ifdef PREPASS_PIPELINE
 #import bevy_pbr::{
@@ -12,7 +22,7 @@
 #else
 #import bevy_pbr::{
     forward_io::{VertexOutput, FragmentOutput},
-    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_functions::main_pass_post_lighting_processing,
 }
 #endif
 
@@ -22,8 +32,6 @@ struct MtoonMaterialUniform {
     emissive_factor: vec4<f32>,
     flags: u32,
     gi_equalization_factor: f32,
-    light_color: vec3<f32>,
-    light_dir: vec3<f32>,
     matcap_factor: vec3<f32>,
     normal_map_scale: f32,
     parametric_rim_color: vec3<f32>,
@@ -31,8 +39,12 @@ struct MtoonMaterialUniform {
     parametric_rim_lift_factor: f32,
     rim_lighting_mix_factor: f32,
     shade_color: vec3<f32>,
+    shade_receive_multiply_factor: f32,
     shading_shift_factor: f32,
     shading_toony_factor: f32,
+    uv_animation_rotation_speed_factor: f32,
+    uv_animation_scroll_x_speed_factor: f32,
+    uv_animation_scroll_y_speed_factor: f32,
 };
 
 @group(2) @binding(0)
@@ -52,6 +64,10 @@ var<uniform> material: MtoonMaterialUniform;
 @group(2) @binding(12) var shade_color_sampler: sampler;
 @group(2) @binding(13) var shade_shift_texture: texture_2d<f32>;
 @group(2) @binding(14) var shade_shift_sampler: sampler;
+@group(2) @binding(15) var outline_width_multiply_texture: texture_2d<f32>;
+@group(2) @binding(16) var outline_width_multiply_sampler: sampler;
+@group(2) @binding(17) var uv_animation_mask_texture: texture_2d<f32>;
+@group(2) @binding(18) var uv_animation_mask_sampler: sampler;
 
 const MTOON_FLAGS_ALPHA_MODE_MASK: u32 = 1u;
 const MTOON_FLAGS_ALPHA_MODE_OPAQUE: u32 = 2u;
@@ -63,6 +79,7 @@ const MTOON_FLAGS_NORMAL_MAP_TEXTURE: u32 = 64u;
 const MTOON_FLAGS_RIM_MULTIPLY_TEXTURE: u32 = 128u;
 const MTOON_FLAGS_SHADE_COLOR_TEXTURE: u32 = 256u;
 const MTOON_FLAGS_SHADING_SHIFT_TEXTURE: u32 = 512u;
+const MTOON_FLAGS_UV_ANIMATION_MASK_TEXTURE: u32 = 1024u;
 
 const EPSILON: f32 = 0.00001;
 
@@ -77,10 +94,21 @@ fn fragment (
     pbr_input.material.metallic = 0.0;
     pbr_input.material.perceptual_roughness = 0.9;
 
+    // UV animation, rotating around the center and then scrolling.
+    var uv_animation_mask = 1.0;
+    if (material.flags & MTOON_FLAGS_UV_ANIMATION_MASK_TEXTURE) != 0u {
+        uv_animation_mask = textureSampleBias(uv_animation_mask_texture, uv_animation_mask_sampler, in.uv, view.mip_bias).b;
+    }
+    let uv_rotation = globals.time * material.uv_animation_rotation_speed_factor * uv_animation_mask;
+    let uv_scroll = globals.time * vec2<f32>(material.uv_animation_scroll_x_speed_factor, material.uv_animation_scroll_y_speed_factor) * uv_animation_mask;
+    let uv_rotation_cos = cos(uv_rotation);
+    let uv_rotation_sin = sin(uv_rotation);
+    let uv = mat2x2<f32>(uv_rotation_cos, uv_rotation_sin, -uv_rotation_sin, uv_rotation_cos) * (in.uv - 0.5) + 0.5 + uv_scroll;
+
     // Base color.
     var base_color = material.base_color;
     if (material.flags & MTOON_FLAGS_BASE_COLOR_TEXTURE) != 0u {
-        base_color *= textureSampleBias(base_color_texture, base_color_sampler, in.uv, view.mip_bias);
+        base_color *= textureSampleBias(base_color_texture, base_color_sampler, uv, view.mip_bias);
     }
 
     // Alpha discard.
@@ -120,7 +148,7 @@ fn fragment (
 #ifdef VERTEX_TANGENTS
 #ifdef VERTEX_UVS
       // Nt is the tangent-space normal.
-      var Nt = textureSampleBias(normal_map_texture, normal_map_sampler, in.uv, view.mip_bias).rgb;
+      var Nt = textureSampleBias(normal_map_texture, normal_map_sampler, uv, view.mip_bias).rgb;
       Nt = Nt * 2.0 - 1.0;
 
       if double_sided && !is_front {
@@ -143,41 +171,113 @@ fn fragment (
     // Emissive.
     var emissive = material.emissive_factor;
     if (material.flags & MTOON_FLAGS_EMISSIVE_TEXTURE) != 0u {
-        emissive = vec4<f32>(emissive.rgb * textureSampleBias(emissive_texture, emissive_sampler, in.uv, view.mip_bias).rgb, 1.0);
+        emissive = vec4<f32>(emissive.rgb * textureSampleBias(emissive_texture, emissive_sampler, uv, view.mip_bias).rgb, 1.0);
     }
-    pbr_input.material.emissive = emissive;
 
     // Shading.
-    var shading = dot(pbr_input.N, material.light_dir);
-    shading = shading + material.shading_shift_factor;
+    var shading_shift = material.shading_shift_factor;
     if (material.flags & MTOON_FLAGS_SHADING_SHIFT_TEXTURE) != 0u {
         // Is grabbing the alpha correct here?
-        shading = shading + textureSampleBias(shade_shift_texture, shade_shift_sampler, in.uv, view.mip_bias).a;
+        shading_shift += textureSampleBias(shade_shift_texture, shade_shift_sampler, uv, view.mip_bias).a;
     }
-    shading = 1.0 - linear_step(material.shading_toony_factor - 1.0, 1.0 - material.shading_toony_factor, shading);
     var shade_color = material.shade_color;
     if (material.flags & MTOON_FLAGS_SHADE_COLOR_TEXTURE) != 0u {
-        shade_color *= textureSampleBias(shade_color_texture, shade_color_sampler, in.uv, view.mip_bias).rgb;
+        shade_color *= textureSampleBias(shade_color_texture, shade_color_sampler, uv, view.mip_bias).rgb;
     }
-    var mtoon_rgb = mix(base_color.rgb, shade_color, shading);
-    mtoon_rgb *= material.light_color;
 
-    // Global illumination.
-    pbr_input.material.base_color = vec4<f32>(mtoon_rgb, base_color.a);
-    let pbr_lighting_color = apply_pbr_lighting(pbr_input);
-    let n_dot_v = max(dot(pbr_input.N, pbr_input.V), 0.0001);
+    // Direct lighting, toon shading each light that reaches the fragment.
+    let world_position = pbr_input.world_position;
+    let world_normal = pbr_input.world_normal;
+    let receives_shadows = (pbr_input.flags & MESH_FLAGS_SHADOW_RECEIVER_BIT) != 0u;
+
+    let view_z = dot(vec4<f32>(
+        view.view_from_world[0].z,
+        view.view_from_world[1].z,
+        view.view_from_world[2].z,
+        view.view_from_world[3].z
+    ), world_position);
+    let cluster_index = clustering::fragment_cluster_index(pbr_input.frag_coord.xy, view_z, pbr_input.is_orthographic);
+    var clusterable_object_index_ranges = clustering::unpack_clusterable_object_index_ranges(cluster_index);
+
+    var direct = vec3<f32>(0.0);
+    var direct_light = vec3<f32>(0.0);
+
+    for (var i: u32 = clusterable_object_index_ranges.first_point_light_index_offset;
+            i < clusterable_object_index_ranges.first_spot_light_index_offset;
+            i = i + 1u) {
+        let light_id = clustering::get_clusterable_object_id(i);
+        let light = &clusterable_objects.data[light_id];
+
+        var shadow = 1.0;
+        if receives_shadows && ((*light).flags & mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u {
+            shadow = shadows::fetch_point_shadow(light_id, world_position, world_normal);
+        }
+
+        let light_to_frag = (*light).position_radius.xyz - world_position.xyz;
+        let attenuation = getDistanceAttenuation(dot(light_to_frag, light_to_frag), (*light).color_inverse_square_range.w);
+        let light_color = (*light).color_inverse_square_range.rgb * attenuation;
+
+        direct += toon_light(pbr_input.N, normalize(light_to_frag), shading_shift, shadow, base_color.rgb, shade_color) * light_color;
+        direct_light += light_color;
+    }
+
+    for (var i: u32 = clusterable_object_index_ranges.first_spot_light_index_offset;
+            i < clusterable_object_index_ranges.first_reflection_probe_index_offset;
+            i = i + 1u) {
+        let light_id = clustering::get_clusterable_object_id(i);
+        let light = &clusterable_objects.data[light_id];
+
+        var shadow = 1.0;
+        if receives_shadows && ((*light).flags & mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u {
+            shadow = shadows::fetch_spot_shadow(light_id, world_position, world_normal, (*light).shadow_map_near_z);
+        }
+
+        let light_to_frag = (*light).position_radius.xyz - world_position.xyz;
+        let attenuation = getDistanceAttenuation(dot(light_to_frag, light_to_frag), (*light).color_inverse_square_range.w)
+            * spot_attenuation(light_id, light_to_frag);
+        let light_color = (*light).color_inverse_square_range.rgb * attenuation;
+
+        direct += toon_light(pbr_input.N, normalize(light_to_frag), shading_shift, shadow, base_color.rgb, shade_color) * light_color;
+        direct_light += light_color;
+    }
+
+    // Bevy only uploads the directional lights whose render layers intersect the view's,
+    // so every light in the array applies. Point and spot lights are filtered the same way
+    // when they are assigned to clusters.
+    let n_directional_lights = lights.n_directional_lights;
+    for (var i: u32 = 0u; i < n_directional_lights; i = i + 1u) {
+        let light = &lights.directional_lights[i];
 
-    let diffuse_color = mtoon_rgb.rgb;
-    let F0 = base_color.rgb;
-    let perceptual_roughness = pbr_input.material.perceptual_roughness;
+        var shadow = 1.0;
+        if receives_shadows && ((*light).flags & mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u {
+            shadow = shadows::fetch_directional_shadow(i, world_position, world_normal, view_z);
+        }
+
+        let light_color = (*light).color.rgb;
+
+        direct += toon_light(pbr_input.N, (*light).direction_to_light, shading_shift, shadow, base_color.rgb, shade_color) * light_color;
+        direct_light += light_color;
+    }
+
+    // Global illumination.
+    // Bevy's ambient light is the same in every direction. Environment maps are evened out
+    // towards the average of the light from above and below by the equalization factor.
     let diffuse_occlusion = pbr_input.diffuse_occlusion;
-    var uniform_gi = ambient_light(pbr_input.world_position, pbr_input.N, pbr_input.V, n_dot_v, diffuse_color, F0, perceptual_roughness, diffuse_occlusion);
+    var gi_light = lights.ambient_color.rgb;
+#ifdef ENVIRONMENT_MAP
+    let environment_light = environment_irradiance(world_position.xyz, pbr_input.N, pbr_input.V, &clusterable_object_index_ranges);
+    let uniform_environment_light = 0.5 * (
+        environment_irradiance(world_position.xyz, vec3<f32>(0.0, 1.0, 0.0), pbr_input.V, &clusterable_object_index_ranges) +
+        environment_irradiance(world_position.xyz, vec3<f32>(0.0, -1.0, 0.0), pbr_input.V, &clusterable_object_index_ranges)
+    );
+    gi_light += mix(environment_light, uniform_environment_light, material.gi_equalization_factor);
+#endif
+    gi_light *= diffuse_occlusion;
 
-    uniform_gi *= base_color.rgb;
-    uniform_gi *= view.exposure;
+    var mtoon_rgb = (direct + gi_light * base_color.rgb) * view.exposure;
+    mtoon_rgb += emissive.rgb;
 
-    let gi = mix(pbr_lighting_color.rgb, uniform_gi, material.gi_equalization_factor);
-    mtoon_rgb += gi;
+    let lighting_color = (direct_light / PI + gi_light) * view.exposure;
 
     // Rim lighting.
     var rim = vec3(0.0);
@@ -192,15 +292,75 @@ fn fragment (
     parametric_rim = pow(parametric_rim, max(material.parametric_rim_fresnel_power, EPSILON));
     rim += parametric_rim * material.parametric_rim_color;
     if (material.flags & MTOON_FLAGS_RIM_MULTIPLY_TEXTURE) != 0u {
-        let rim_multiply = textureSampleBias(rim_multiply_texture, rim_multiply_sampler, in.uv, view.mip_bias);
+        let rim_multiply = textureSampleBias(rim_multiply_texture, rim_multiply_sampler, uv, view.mip_bias);
         rim *= rim_multiply.rgb;
     }
-    rim *= mix(vec3(1.0), pbr_lighting_color.rgb, material.rim_lighting_mix_factor);
+    rim *= mix(vec3(1.0), lighting_color, material.rim_lighting_mix_factor);
     mtoon_rgb += rim;
 
     return vec4<f32>(mtoon_rgb, base_color.a);
 }
 
+// Shades a single light, mixing from the shade color to the lit color as the surface turns
+// towards it. Received shadows darken the surface by the receive factor.
+fn toon_light(
+    N: vec3<f32>,
+    L: vec3<f32>,
+    shading_shift: f32,
+    shadow: f32,
+    lit_color: vec3<f32>,
+    shade_color: vec3<f32>,
+) -> vec3<f32> {
+    var shading = dot(N, L) + shading_shift;
+    shading = linear_step(material.shading_toony_factor - 1.0, 1.0 - material.shading_toony_factor, shading);
+    shading *= mix(1.0, shadow, material.shade_receive_multiply_factor);
+
+    // Lambertian normalization, matching the brightness of PBR materials.
+    return mix(shade_color, lit_color, shading) / PI;
+}
+
+// Adapted from Bevy's spot_light.
+fn spot_attenuation(light_id: u32, light_to_frag: vec3<f32>) -> f32 {
+    let light = &clusterable_objects.data[light_id];
+
+    // Reconstruct the spot direction from x/z and the y-direction flag.
+    var spot_dir = vec3<f32>((*light).light_custom_data.x, 0.0, (*light).light_custom_data.y);
+    spot_dir.y = sqrt(max(0.0, 1.0 - spot_dir.x * spot_dir.x - spot_dir.z * spot_dir.z));
+    if ((*light).flags & mesh_view_types::POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE) != 0u {
+        spot_dir.y = -spot_dir.y;
+    }
+
+    let cd = dot(-spot_dir, normalize(light_to_frag));
+    let attenuation = saturate(cd * (*light).light_custom_data.z + (*light).light_custom_data.w);
+    return attenuation * attenuation;
+}
+
+#ifdef ENVIRONMENT_MAP
+// Diffuse light from environment maps arriving at a surface facing N.
+fn environment_irradiance(
+    P: vec3<f32>,
+    N: vec3<f32>,
+    V: vec3<f32>,
+    clusterable_object_index_ranges: ptr<function, clustering::ClusterableObjectIndexRanges>,
+) -> vec3<f32> {
+    // A white, fully rough dielectric reflects exactly the irradiance as diffuse light.
+    let NdotV = max(dot(N, V), 0.0001);
+    var input: lighting::LightingInput;
+    input.layers[LAYER_BASE].NdotV = NdotV;
+    input.layers[LAYER_BASE].N = N;
+    input.layers[LAYER_BASE].R = reflect(-V, N);
+    input.layers[LAYER_BASE].perceptual_roughness = 1.0;
+    input.layers[LAYER_BASE].roughness = 1.0;
+    input.P = P;
+    input.V = V;
+    input.diffuse_color = vec3<f32>(1.0);
+    input.F0_ = vec3<f32>(0.0);
+    input.F_ab = lighting::F_AB(1.0, NdotV);
+
+    return environment_map::environment_map_light(&input, clusterable_object_index_ranges, false).diffuse;
+}
+#endif
+
 fn linear_step(a: f32, b: f32, t: f32) -> f32 {
     return saturate((t - a) / (b - a));
 }
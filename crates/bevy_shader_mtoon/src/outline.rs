@@ -0,0 +1,419 @@
+use bevy::{
+    pbr::OpaqueRendererMethod,
+    platform::collections::HashMap,
+    prelude::*,
+    render::{
+        mesh::{morph::MeshMorphWeights, skinning::SkinnedMesh},
+        render_asset::RenderAssets,
+        render_resource::{AsBindGroup, AsBindGroupShaderType, Face, ShaderRef, ShaderType},
+        texture::GpuImage,
+        view::RenderLayers,
+    },
+};
+
+use crate::{MtoonMaterial, OUTLINE_SHADER_HANDLE, VrmOutlineMode};
+
+/// Draws the outline of an [MtoonMaterial] as an inverted hull,
+/// extruding back faces along their normals.
+///
+/// Created from the [MtoonMaterial] of entities with an [OutlineSync].
+#[derive(Asset, AsBindGroup, PartialEq, Debug, Clone, Reflect)]
+#[uniform(0, MtoonOutlineUniform)]
+#[reflect(PartialEq)]
+pub struct MtoonOutlineMaterial {
+    pub alpha_mode: AlphaMode,
+    pub base_color: Color,
+    pub outline_color: Color,
+    pub outline_lighting_mix_factor: f32,
+    pub outline_mode: VrmOutlineMode,
+    pub outline_width: f32,
+
+    #[texture(1)]
+    #[sampler(2)]
+    #[dependency]
+    pub base_color_texture: Option<Handle<Image>>,
+    #[texture(3)]
+    #[sampler(4)]
+    #[dependency]
+    pub outline_width_multiply_texture: Option<Handle<Image>>,
+}
+
+impl From<&MtoonMaterial> for MtoonOutlineMaterial {
+    fn from(material: &MtoonMaterial) -> Self {
+        Self {
+            alpha_mode: material.alpha_mode,
+            base_color: material.base_color,
+            outline_color: material.outline_color,
+            outline_lighting_mix_factor: material.outline_lighting_mix_factor,
+            outline_mode: material.outline_mode.clone(),
+            outline_width: material.outline_width,
+            base_color_texture: material.base_color_texture.clone(),
+            outline_width_multiply_texture: material.outline_width_multiply_texture.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Default, ShaderType)]
+pub struct MtoonOutlineUniform {
+    pub alpha_cutoff: f32,
+    pub base_color: Vec4,
+    pub flags: u32,
+    pub outline_color: Vec3,
+    pub outline_lighting_mix_factor: f32,
+    pub outline_width: f32,
+}
+
+impl AsBindGroupShaderType<MtoonOutlineUniform> for MtoonOutlineMaterial {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> MtoonOutlineUniform {
+        let mut flags = MtoonOutlineFlags::empty();
+
+        if self.base_color_texture.is_some() {
+            flags |= MtoonOutlineFlags::BASE_COLOR_TEXTURE;
+        }
+        if self.outline_width_multiply_texture.is_some() {
+            flags |= MtoonOutlineFlags::OUTLINE_WIDTH_MULTIPLY_TEXTURE;
+        }
+        if self.outline_mode == VrmOutlineMode::Screen {
+            flags |= MtoonOutlineFlags::OUTLINE_WIDTH_SCREEN;
+        }
+
+        let alpha_cutoff = match self.alpha_mode {
+            AlphaMode::Mask(value) => {
+                flags |= MtoonOutlineFlags::ALPHA_MODE_MASK;
+                value
+            }
+            AlphaMode::Opaque => {
+                flags |= MtoonOutlineFlags::ALPHA_MODE_OPAQUE;
+                0.0
+            }
+            _ => 0.0,
+        };
+
+        let outline_color = self.outline_color.to_linear().to_f32_array();
+
+        MtoonOutlineUniform {
+            alpha_cutoff,
+            base_color: self.base_color.to_linear().to_f32_array().into(),
+            flags: flags.bits(),
+            outline_color: Vec3::new(outline_color[0], outline_color[1], outline_color[2]),
+            outline_lighting_mix_factor: self.outline_lighting_mix_factor,
+            outline_width: self.outline_width,
+        }
+    }
+}
+
+impl Material for MtoonOutlineMaterial {
+    fn vertex_shader() -> ShaderRef {
+        OUTLINE_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        OUTLINE_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    fn opaque_render_method(&self) -> OpaqueRendererMethod {
+        OpaqueRendererMethod::Forward
+    }
+
+    fn specialize(
+        _pipeline: &bevy::pbr::MaterialPipeline<Self>,
+        descriptor: &mut bevy::render::render_resource::RenderPipelineDescriptor,
+        _layout: &bevy::render::mesh::MeshVertexBufferLayoutRef,
+        _key: bevy::pbr::MaterialPipelineKey<Self>,
+    ) -> Result<(), bevy::render::render_resource::SpecializedMeshPipelineError> {
+        // Only the extruded back faces are drawn, leaving a rim around the mesh.
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        Ok(())
+    }
+}
+
+bitflags::bitflags! {
+    #[repr(transparent)]
+    pub struct MtoonOutlineFlags: u32 {
+        const ALPHA_MODE_MASK = 1 << 0;
+        const ALPHA_MODE_OPAQUE = 1 << 1;
+        const BASE_COLOR_TEXTURE = 1 << 2;
+        const OUTLINE_WIDTH_MULTIPLY_TEXTURE = 1 << 3;
+        const OUTLINE_WIDTH_SCREEN = 1 << 4;
+    }
+}
+
+/// Syncs an Entity's outline with its [MtoonMaterial].
+/// Will add the outline if one is not present.
+#[derive(Component, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct OutlineSync;
+
+/// Outline of an entity with an [OutlineSync].
+///
+/// Each entity renders a single material, so the outline is drawn by its own
+/// entity sharing the mesh, skin, and morph weights of the surface. It is
+/// spawned as a sibling of the surface, inheriting the same morph weights,
+/// and despawned once the surface no longer has an outline.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MtoonOutline {
+    pub surface: Entity,
+}
+
+/// Outline entity of a surface.
+#[derive(Component)]
+pub(crate) struct OutlineEntity(Entity);
+
+/// Outline materials, keyed by the [MtoonMaterial] they were created from.
+/// Materials without an outline have no entry.
+#[derive(Resource, Default)]
+pub(crate) struct OutlineMaterials(HashMap<AssetId<MtoonMaterial>, Handle<MtoonOutlineMaterial>>);
+
+pub(crate) fn update_outline_materials(
+    mut events: EventReader<AssetEvent<MtoonMaterial>>,
+    materials: Res<Assets<MtoonMaterial>>,
+    mut outline_materials: ResMut<Assets<MtoonOutlineMaterial>>,
+    mut outlines: ResMut<OutlineMaterials>,
+) {
+    for event in events.read() {
+        match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                let Some(material) = materials.get(*id) else {
+                    continue;
+                };
+
+                if material.outline_mode == VrmOutlineMode::None || material.outline_width <= 0.0 {
+                    outlines.0.remove(id);
+                    continue;
+                }
+
+                let outline = MtoonOutlineMaterial::from(material);
+
+                match outlines.0.get(id) {
+                    Some(handle) => {
+                        // Avoid re-uploading outlines that did not change.
+                        if outline_materials.get(handle) != Some(&outline)
+                            && let Some(existing) = outline_materials.get_mut(handle)
+                        {
+                            *existing = outline;
+                        }
+                    }
+                    None => {
+                        let handle = outline_materials.add(outline);
+                        outlines.0.insert(*id, handle);
+                    }
+                }
+            }
+            AssetEvent::Removed { id } | AssetEvent::Unused { id } => {
+                outlines.0.remove(id);
+            }
+            AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn sync_outline(
+    mut commands: Commands,
+    surfaces: Query<
+        (
+            Entity,
+            &MeshMaterial3d<MtoonMaterial>,
+            &Mesh3d,
+            Option<&Transform>,
+            Option<&SkinnedMesh>,
+            Option<&MeshMorphWeights>,
+            Option<&RenderLayers>,
+            Option<&ChildOf>,
+            Option<&OutlineEntity>,
+        ),
+        (With<OutlineSync>, Without<MtoonOutline>),
+    >,
+    mut outline_entities: Query<(
+        Entity,
+        &MtoonOutline,
+        &mut MeshMaterial3d<MtoonOutlineMaterial>,
+        &mut Mesh3d,
+        Option<&mut MeshMorphWeights>,
+        Option<&RenderLayers>,
+    )>,
+    outlines: Res<OutlineMaterials>,
+) {
+    for (entity, material, mesh, transform, skin, morph_weights, layers, parent, current) in
+        surfaces.iter()
+    {
+        let target = outlines.0.get(&material.id());
+
+        match (target, current) {
+            (Some(handle), Some(&OutlineEntity(outline))) => {
+                let Ok((
+                    _,
+                    _,
+                    mut outline_material,
+                    mut outline_mesh,
+                    outline_weights,
+                    outline_layers,
+                )) = outline_entities.get_mut(outline)
+                else {
+                    continue;
+                };
+
+                if outline_material.0 != *handle {
+                    outline_material.0 = handle.clone();
+                }
+
+                if outline_mesh.0 != mesh.0 {
+                    outline_mesh.0 = mesh.0.clone();
+                }
+
+                if let (Some(weights), Some(mut outline_weights)) = (morph_weights, outline_weights)
+                    && weights.weights() != outline_weights.weights()
+                {
+                    outline_weights.clear_weights();
+                    outline_weights.extend_weights(weights.weights());
+                }
+
+                if layers != outline_layers {
+                    match layers {
+                        Some(layers) => commands.entity(outline).insert(layers.clone()),
+                        None => commands.entity(outline).remove::<RenderLayers>(),
+                    };
+                }
+            }
+            (Some(handle), None) => {
+                let outline = commands
+                    .spawn((
+                        MtoonOutline { surface: entity },
+                        MeshMaterial3d(handle.clone()),
+                        mesh.clone(),
+                    ))
+                    .id();
+
+                if let Some(v) = skin {
+                    commands.entity(outline).insert(v.clone());
+                }
+
+                if let Some(v) = morph_weights {
+                    commands.entity(outline).insert(v.clone());
+                }
+
+                if let Some(v) = layers {
+                    commands.entity(outline).insert(v.clone());
+                }
+
+                // As a sibling, the outline inherits the surface's morph weights.
+                match parent {
+                    Some(parent) => {
+                        commands
+                            .entity(outline)
+                            .insert(transform.copied().unwrap_or_default());
+                        commands.entity(parent.parent()).add_child(outline);
+                    }
+                    None => {
+                        commands.entity(entity).add_child(outline);
+                    }
+                }
+
+                commands.entity(entity).insert(OutlineEntity(outline));
+            }
+            (None, Some(&OutlineEntity(outline))) => {
+                commands.entity(outline).despawn();
+                commands.entity(entity).remove::<OutlineEntity>();
+            }
+            (None, None) => {}
+        }
+    }
+
+    // Despawn outlines whose surface was despawned or stopped syncing.
+    for (outline, MtoonOutline { surface }, ..) in outline_entities.iter() {
+        if !surfaces.contains(*surface) {
+            commands.entity(outline).despawn();
+
+            if let Ok(mut surface) = commands.get_entity(*surface) {
+                surface.remove::<OutlineEntity>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn spawns_outline_entity() {
+        let mut world = World::new();
+        world.init_resource::<Assets<MtoonMaterial>>();
+        world.init_resource::<Assets<MtoonOutlineMaterial>>();
+        world.init_resource::<Assets<Mesh>>();
+        world.init_resource::<OutlineMaterials>();
+
+        let material = world
+            .resource_mut::<Assets<MtoonMaterial>>()
+            .add(MtoonMaterial::default());
+        let outline_material = world
+            .resource_mut::<Assets<MtoonOutlineMaterial>>()
+            .add(MtoonOutlineMaterial::from(&MtoonMaterial::default()));
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(Cuboid::default()));
+
+        world
+            .resource_mut::<OutlineMaterials>()
+            .0
+            .insert(material.id(), outline_material.clone());
+
+        let parent = world.spawn(Transform::default()).id();
+        let surface = world
+            .spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                OutlineSync,
+                ChildOf(parent),
+            ))
+            .id();
+
+        world.run_system_once(sync_outline).unwrap();
+
+        let outline = world.get::<OutlineEntity>(surface).unwrap().0;
+
+        // Each entity holds a single material instance.
+        assert!(
+            world
+                .get::<MeshMaterial3d<MtoonMaterial>>(surface)
+                .is_some()
+        );
+        assert!(
+            world
+                .get::<MeshMaterial3d<MtoonOutlineMaterial>>(surface)
+                .is_none()
+        );
+        assert_eq!(
+            world
+                .get::<MeshMaterial3d<MtoonOutlineMaterial>>(outline)
+                .map(|m| m.id()),
+            Some(outline_material.id())
+        );
+        assert!(
+            world
+                .get::<MeshMaterial3d<MtoonMaterial>>(outline)
+                .is_none()
+        );
+        assert_eq!(
+            world.get::<Mesh3d>(outline).map(|m| m.id()),
+            Some(mesh.id())
+        );
+        assert_eq!(
+            world.get::<ChildOf>(outline).map(ChildOf::parent),
+            Some(parent)
+        );
+
+        // Removing the outline despawns its entity.
+        world.resource_mut::<OutlineMaterials>().0.clear();
+        world.run_system_once(sync_outline).unwrap();
+
+        assert!(world.get_entity(outline).is_err());
+        assert!(world.get::<OutlineEntity>(surface).is_none());
+    }
+}
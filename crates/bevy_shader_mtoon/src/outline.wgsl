@@ -0,0 +1,180 @@
+#import bevy_pbr::{
+    mesh_bindings::mesh,
+    mesh_functions,
+    skinning,
+    morph::morph,
+    forward_io::{Vertex, VertexOutput},
+    view_transformations::position_world_to_clip,
+    mesh_view_bindings::view,
+    pbr_fragment::pbr_input_from_vertex_output,
+    pbr_functions::apply_pbr_lighting,
+}
+
+struct MtoonOutlineUniform {
+    alpha_cutoff: f32,
+    base_color: vec4<f32>,
+    flags: u32,
+    outline_color: vec3<f32>,
+    outline_lighting_mix_factor: f32,
+    outline_width: f32,
+};
+
+@group(2) @binding(0)
+var<uniform> material: MtoonOutlineUniform;
+
+@group(2) @binding(1) var base_color_texture: texture_2d<f32>;
+@group(2) @binding(2) var base_color_sampler: sampler;
+@group(2) @binding(3) var outline_width_multiply_texture: texture_2d<f32>;
+@group(2) @binding(4) var outline_width_multiply_sampler: sampler;
+
+const MTOON_OUTLINE_FLAGS_ALPHA_MODE_MASK: u32 = 1u;
+const MTOON_OUTLINE_FLAGS_ALPHA_MODE_OPAQUE: u32 = 2u;
+const MTOON_OUTLINE_FLAGS_BASE_COLOR_TEXTURE: u32 = 4u;
+const MTOON_OUTLINE_FLAGS_OUTLINE_WIDTH_MULTIPLY_TEXTURE: u32 = 8u;
+const MTOON_OUTLINE_FLAGS_OUTLINE_WIDTH_SCREEN: u32 = 16u;
+
+#ifdef MORPH_TARGETS
+fn morph_vertex(vertex_in: Vertex) -> Vertex {
+    var vertex = vertex_in;
+    let first_vertex = mesh[vertex.instance_index].first_vertex_index;
+    let vertex_index = vertex.index - first_vertex;
+
+    let weight_count = bevy_pbr::morph::layer_count();
+    for (var i: u32 = 0u; i < weight_count; i ++) {
+        let weight = bevy_pbr::morph::weight_at(i);
+        if weight == 0.0 {
+            continue;
+        }
+        vertex.position += weight * morph(vertex_index, bevy_pbr::morph::position_offset, i);
+#ifdef VERTEX_NORMALS
+        vertex.normal += weight * morph(vertex_index, bevy_pbr::morph::normal_offset, i);
+#endif
+    }
+    return vertex;
+}
+#endif
+
+// Adapted from the Bevy mesh vertex shader, extruding each vertex along its normal.
+@vertex
+fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+#ifdef MORPH_TARGETS
+    var vertex = morph_vertex(vertex_no_morph);
+#else
+    var vertex = vertex_no_morph;
+#endif
+
+#ifdef SKINNED
+    var world_from_local = skinning::skin_model(
+        vertex.joint_indices,
+        vertex.joint_weights,
+        vertex_no_morph.instance_index
+    );
+#else
+    var world_from_local = mesh_functions::get_world_from_local(vertex_no_morph.instance_index);
+#endif
+
+    var world_normal = vec3<f32>(0.0);
+#ifdef VERTEX_NORMALS
+#ifdef SKINNED
+    world_normal = skinning::skin_normals(world_from_local, vertex.normal);
+#else
+    world_normal = mesh_functions::mesh_normal_local_to_world(
+        vertex.normal,
+        vertex_no_morph.instance_index
+    );
+#endif
+#endif
+    out.world_normal = world_normal;
+
+    var width = material.outline_width;
+#ifdef VERTEX_UVS_A
+    if (material.flags & MTOON_OUTLINE_FLAGS_OUTLINE_WIDTH_MULTIPLY_TEXTURE) != 0u {
+        width *= textureSampleLevel(outline_width_multiply_texture, outline_width_multiply_sampler, vertex.uv, 0.0).g;
+    }
+#endif
+
+    var world_position = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+
+    if (material.flags & MTOON_OUTLINE_FLAGS_OUTLINE_WIDTH_SCREEN) != 0u {
+        // Width is a ratio of the viewport height, which spans two units in clip space.
+        out.position = position_world_to_clip(world_position.xyz);
+
+        let aspect = view.viewport.z / view.viewport.w;
+        let clip_normal = (view.clip_from_world * vec4<f32>(world_normal, 0.0)).xy;
+        let screen_normal = clip_normal * vec2<f32>(aspect, 1.0);
+
+        if dot(screen_normal, screen_normal) > 0.0 {
+            let direction = normalize(screen_normal);
+            out.position.x += direction.x * 2.0 * width * out.position.w / aspect;
+            out.position.y += direction.y * 2.0 * width * out.position.w;
+        }
+    } else {
+        // Width is in meters.
+        world_position = vec4<f32>(world_position.xyz + world_normal * width, world_position.w);
+        out.position = position_world_to_clip(world_position.xyz);
+    }
+
+    out.world_position = world_position;
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+
+#ifdef VERTEX_TANGENTS
+    out.world_tangent = mesh_functions::mesh_tangent_local_to_world(
+        world_from_local,
+        vertex.tangent,
+        vertex_no_morph.instance_index
+    );
+#endif
+
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex_no_morph.instance_index;
+#endif
+
+    return out;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> @location(0) vec4<f32> {
+    var alpha = material.base_color.a;
+#ifdef VERTEX_UVS_A
+    if (material.flags & MTOON_OUTLINE_FLAGS_BASE_COLOR_TEXTURE) != 0u {
+        alpha *= textureSampleBias(base_color_texture, base_color_sampler, in.uv, view.mip_bias).a;
+    }
+#endif
+
+    // Alpha discard, matching the surface.
+    if (material.flags & MTOON_OUTLINE_FLAGS_ALPHA_MODE_OPAQUE) != 0u {
+        alpha = 1.0;
+    } else if (material.flags & MTOON_OUTLINE_FLAGS_ALPHA_MODE_MASK) != 0u {
+        if alpha >= material.alpha_cutoff {
+            alpha = 1.0;
+        } else {
+            discard;
+        }
+    }
+
+    // Lighting, from a white surface facing the same way as the mesh.
+    var pbr_input = pbr_input_from_vertex_output(in, is_front, false);
+    pbr_input.material.base_color = vec4<f32>(1.0);
+    pbr_input.material.metallic = 0.0;
+    pbr_input.material.perceptual_roughness = 0.9;
+    let lighting = apply_pbr_lighting(pbr_input).rgb;
+
+    let color = material.outline_color * mix(vec3<f32>(1.0), lighting, material.outline_lighting_mix_factor);
+
+    return vec4<f32>(color, alpha);
+}
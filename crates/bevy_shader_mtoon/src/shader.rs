@@ -9,12 +9,17 @@ use bevy::{
 
 use crate::SHADER_HANDLE;
 
+/// Sort distance, in view space units, between consecutive render queue offsets.
+const RENDER_QUEUE_DEPTH_BIAS: f32 = 1e-4;
+
+/// Lit by the scene's directional, point, and spot lights, read from Bevy's view bindings.
 #[derive(Asset, AsBindGroup, PartialEq, Debug, Clone, Component, Reflect)]
 #[bind_group_data(MtoonMaterialKey)]
 #[uniform(0, MtoonShaderUniform)]
 #[reflect(PartialEq)]
 pub struct MtoonMaterial {
     pub outline_color: Color,
+    pub outline_lighting_mix_factor: f32,
     pub outline_mode: OutlineMode,
     pub outline_width: f32,
 
@@ -23,18 +28,22 @@ pub struct MtoonMaterial {
     pub double_sided: bool,
     pub emissive_factor: Color,
     pub gi_equalization_factor: f32,
-    pub light_color: Color,
-    pub light_dir: Vec3,
     pub matcap_factor: Vec3,
     pub normal_map_scale: f32,
     pub parametric_rim_color: Color,
     pub parametric_rim_fresnel_power: f32,
     pub parametric_rim_lift_factor: f32,
+    /// Offsets the draw order among transparent materials.
+    pub render_queue_offset_number: i32,
     pub rim_lighting_mix_factor: f32,
     pub shade_factor: Color,
+    /// How strongly received shadows darken the surface.
+    pub shade_receive_multiply_factor: f32,
     pub shading_shift_factor: f32,
     pub shading_toony_factor: f32,
-    pub view_dir: Vec3,
+    pub uv_animation_rotation_speed_factor: f32,
+    pub uv_animation_scroll_x_speed_factor: f32,
+    pub uv_animation_scroll_y_speed_factor: f32,
 
     #[texture(1)]
     #[sampler(2)]
@@ -64,6 +73,14 @@ pub struct MtoonMaterial {
     #[sampler(14)]
     #[dependency]
     pub shade_shift_texture: Option<Handle<Image>>,
+    #[texture(15)]
+    #[sampler(16)]
+    #[dependency]
+    pub outline_width_multiply_texture: Option<Handle<Image>>,
+    #[texture(17)]
+    #[sampler(18)]
+    #[dependency]
+    pub uv_animation_mask_texture: Option<Handle<Image>>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Reflect)]
@@ -78,6 +95,7 @@ impl Default for MtoonMaterial {
     fn default() -> Self {
         Self {
             outline_color: Color::BLACK,
+            outline_lighting_mix_factor: 1.0,
             outline_mode: OutlineMode::None,
             outline_width: 0.0,
 
@@ -86,18 +104,20 @@ impl Default for MtoonMaterial {
             double_sided: false,
             emissive_factor: Color::BLACK,
             gi_equalization_factor: 0.9,
-            light_color: Color::WHITE,
-            light_dir: Vec3::Y,
             matcap_factor: Vec3::ZERO,
             normal_map_scale: 1.0,
             parametric_rim_color: Color::WHITE,
             parametric_rim_fresnel_power: 5.0,
             parametric_rim_lift_factor: 0.0,
+            render_queue_offset_number: 0,
             rim_lighting_mix_factor: 1.0,
             shade_factor: Color::BLACK,
+            shade_receive_multiply_factor: 1.0,
             shading_shift_factor: 0.0,
             shading_toony_factor: 0.9,
-            view_dir: Vec3::ZERO,
+            uv_animation_rotation_speed_factor: 0.0,
+            uv_animation_scroll_x_speed_factor: 0.0,
+            uv_animation_scroll_y_speed_factor: 0.0,
 
             base_color_texture: None,
             emissive_texture: None,
@@ -106,6 +126,8 @@ impl Default for MtoonMaterial {
             rim_multiply_texture: None,
             shade_multiply_texture: None,
             shade_shift_texture: None,
+            outline_width_multiply_texture: None,
+            uv_animation_mask_texture: None,
         }
     }
 }
@@ -117,8 +139,6 @@ pub struct MtoonShaderUniform {
     pub emissive_factor: Vec4,
     pub flags: u32,
     pub gi_equalization_factor: f32,
-    pub light_color: Vec3,
-    pub light_dir: Vec3,
     pub matcap_factor: Vec3,
     pub normal_map_scale: f32,
     pub parametric_rim_color: Vec3,
@@ -126,9 +146,12 @@ pub struct MtoonShaderUniform {
     pub parametric_rim_lift_factor: f32,
     pub rim_lighting_mix_factor: f32,
     pub shade_color: Vec3,
+    pub shade_receive_multiply_factor: f32,
     pub shading_shift_factor: f32,
     pub shading_toony_factor: f32,
-    pub view_dir: Vec3,
+    pub uv_animation_rotation_speed_factor: f32,
+    pub uv_animation_scroll_x_speed_factor: f32,
+    pub uv_animation_scroll_y_speed_factor: f32,
 }
 
 impl AsBindGroupShaderType<MtoonShaderUniform> for MtoonMaterial {
@@ -156,6 +179,9 @@ impl AsBindGroupShaderType<MtoonShaderUniform> for MtoonMaterial {
         if self.shade_shift_texture.is_some() {
             flags |= MtoonMaterialFlags::SHADING_SHIFT_TEXTURE;
         }
+        if self.uv_animation_mask_texture.is_some() {
+            flags |= MtoonMaterialFlags::UV_ANIMATION_MASK_TEXTURE;
+        }
 
         let alpha_cutoff = match self.alpha_mode {
             AlphaMode::Mask(value) => {
@@ -169,9 +195,6 @@ impl AsBindGroupShaderType<MtoonShaderUniform> for MtoonMaterial {
             _ => 0.0,
         };
 
-        let light_color = self.light_color.to_linear().to_f32_array();
-        let light_color = Vec3::new(light_color[0], light_color[1], light_color[2]);
-
         let parametric_rim_color = self.parametric_rim_color.to_linear().to_f32_array();
         let parametric_rim_color = Vec3::new(
             parametric_rim_color[0],
@@ -188,8 +211,6 @@ impl AsBindGroupShaderType<MtoonShaderUniform> for MtoonMaterial {
             emissive_factor: self.emissive_factor.to_linear().to_f32_array().into(),
             flags: flags.bits(),
             gi_equalization_factor: self.gi_equalization_factor,
-            light_color,
-            light_dir: self.light_dir,
             matcap_factor: self.matcap_factor,
             normal_map_scale: self.normal_map_scale,
             parametric_rim_color,
@@ -197,9 +218,12 @@ impl AsBindGroupShaderType<MtoonShaderUniform> for MtoonMaterial {
             parametric_rim_lift_factor: self.parametric_rim_lift_factor,
             rim_lighting_mix_factor: self.rim_lighting_mix_factor,
             shade_color,
+            shade_receive_multiply_factor: self.shade_receive_multiply_factor,
             shading_shift_factor: self.shading_shift_factor,
             shading_toony_factor: self.shading_toony_factor,
-            view_dir: self.view_dir,
+            uv_animation_rotation_speed_factor: self.uv_animation_rotation_speed_factor,
+            uv_animation_scroll_x_speed_factor: self.uv_animation_scroll_x_speed_factor,
+            uv_animation_scroll_y_speed_factor: self.uv_animation_scroll_y_speed_factor,
         }
     }
 }
@@ -222,6 +246,18 @@ impl From<&MtoonMaterial> for MtoonMaterialKey {
 }
 
 impl Material for MtoonMaterial {
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    fn depth_bias(&self) -> f32 {
+        // Bevy only adds the bias to the view depth that transparent meshes are sorted by,
+        // without touching the depth buffer. A small step per offset breaks ties between
+        // overlapping meshes, such as a face and its eyebrows, without reordering meshes
+        // that are actually apart.
+        self.render_queue_offset_number as f32 * RENDER_QUEUE_DEPTH_BIAS
+    }
+
     fn fragment_shader() -> ShaderRef {
         SHADER_HANDLE.into()
     }
@@ -254,5 +290,6 @@ bitflags::bitflags! {
         const RIM_MULTIPLY_TEXTURE = 1 << 7;
         const SHADE_COLOR_TEXTURE = 1 << 8;
         const SHADING_SHIFT_TEXTURE = 1 << 9;
+        const UV_ANIMATION_MASK_TEXTURE = 1 << 10;
     }
 }
@@ -0,0 +1,725 @@
+//! [BVH](https://research.cs.wisc.edu/graphics/Courses/cs-838-1999/Jeff/BVH.html) motion capture
+//! import, producing [AnimationClip]s that target VRM humanoid bones.
+//!
+//! Rotations are retargeted in world space, so the capture's rest pose is expected to
+//! match the avatar's rest pose (typically a T-pose).
+
+use std::{collections::HashMap, str::SplitWhitespace};
+
+use bevy::{
+    animation::{
+        animated_field,
+        animation_curves::{AnimatableCurve, AnimatableKeyframeCurve},
+    },
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use serde_vrm::vrm0::BoneName;
+use thiserror::Error;
+
+use crate::animations::vrm::VRM_ANIMATION_TARGETS;
+
+pub const ROOT_MOTION_LABEL: &str = "RootMotion";
+
+#[derive(Default)]
+pub struct BvhLoader;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BvhLoaderSettings {
+    /// Maps BVH joint names to humanoid bones.
+    /// Names are matched case-insensitively, ignoring any namespace prefix (e.g. `mixamorig:`).
+    /// Unmapped joints are skipped, with their rotation carried into mapped descendants.
+    pub bone_map: HashMap<String, BoneName>,
+    /// Multiplier applied to all translations, e.g. `0.01` for captures in centimeters.
+    pub scale: f32,
+    /// Rest height of the avatar's hips above the ground,
+    /// see [VrmMeasurements::hips_height](crate::measurements::VrmMeasurements::hips_height).
+    /// When set, hips translation is scaled by the ratio of this to the capture's rest hips height
+    /// instead of by `scale`, so the motion fits avatars with different leg lengths.
+    pub hips_height: Option<f32>,
+    pub up_axis: BvhUpAxis,
+    /// Rotates the capture 180 degrees around the up axis.
+    pub flip_forward: bool,
+    pub root_motion: RootMotion,
+}
+
+impl Default for BvhLoaderSettings {
+    fn default() -> Self {
+        Self {
+            bone_map: default_bone_map(),
+            scale: 0.01,
+            hips_height: None,
+            up_axis: BvhUpAxis::default(),
+            flip_forward: false,
+            root_motion: RootMotion::default(),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BvhUpAxis {
+    #[default]
+    Y,
+    Z,
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RootMotion {
+    /// Hips translation is kept in the clip.
+    #[default]
+    Keep,
+    /// Horizontal hips translation is removed from the clip, leaving the animation in place.
+    /// The removed trajectory is stored as a [BvhRootMotion] sub-asset, labeled [ROOT_MOTION_LABEL].
+    Extract,
+}
+
+/// Horizontal hips trajectory extracted from a BVH capture, relative to the first frame.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct BvhRootMotion {
+    pub keyframes: Vec<(f32, Vec3)>,
+}
+
+impl BvhRootMotion {
+    /// Linearly samples the trajectory at `time`, clamped to the clip duration.
+    pub fn sample(&self, time: f32) -> Vec3 {
+        let Some(next) = self.keyframes.iter().position(|(t, _)| *t >= time) else {
+            return self.keyframes.last().map(|(_, v)| *v).unwrap_or_default();
+        };
+
+        if next == 0 {
+            return self.keyframes[0].1;
+        }
+
+        let (t0, a) = self.keyframes[next - 1];
+        let (t1, b) = self.keyframes[next];
+        let s = if t1 > t0 {
+            (time - t0) / (t1 - t0)
+        } else {
+            1.0
+        };
+        a.lerp(b, s)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BvhError {
+    #[error("Failed to load file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error("Unexpected end of file")]
+    UnexpectedEof,
+    #[error("Expected {expected}, found {found}")]
+    UnexpectedToken { expected: String, found: String },
+    #[error("Invalid number: {0}")]
+    InvalidNumber(String),
+    #[error("Unknown channel: {0}")]
+    UnknownChannel(String),
+    #[error("Frame {frame} has {found} values, expected {expected}")]
+    FrameLength {
+        frame: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("No joints were mapped to humanoid bones")]
+    NoMappedJoints,
+}
+
+impl AssetLoader for BvhLoader {
+    type Asset = AnimationClip;
+    type Settings = BvhLoaderSettings;
+    type Error = BvhError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let bvh = Bvh::parse(std::str::from_utf8(&bytes)?)?;
+        let (clip, root_motion) = bvh.to_clip(settings)?;
+
+        if let Some(root_motion) = root_motion {
+            load_context.add_labeled_asset(ROOT_MOTION_LABEL.to_string(), root_motion);
+        }
+
+        Ok(clip)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bvh"]
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Channel {
+    XPosition,
+    YPosition,
+    ZPosition,
+    XRotation,
+    YRotation,
+    ZRotation,
+}
+
+impl Channel {
+    fn parse(s: &str) -> Result<Self, BvhError> {
+        match s.to_ascii_lowercase().as_str() {
+            "xposition" => Ok(Self::XPosition),
+            "yposition" => Ok(Self::YPosition),
+            "zposition" => Ok(Self::ZPosition),
+            "xrotation" => Ok(Self::XRotation),
+            "yrotation" => Ok(Self::YRotation),
+            "zrotation" => Ok(Self::ZRotation),
+            _ => Err(BvhError::UnknownChannel(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Joint {
+    name: String,
+    parent: Option<usize>,
+    offset: Vec3,
+    channels: Vec<Channel>,
+    /// Index of this joint's first channel within a frame.
+    channel_offset: usize,
+}
+
+#[derive(Debug)]
+struct Bvh {
+    /// Joints in hierarchy order, parents always come before their children.
+    joints: Vec<Joint>,
+    frame_time: f32,
+    frames: Vec<Vec<f32>>,
+}
+
+struct Tokens<'a>(SplitWhitespace<'a>);
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Result<&'a str, BvhError> {
+        self.0.next().ok_or(BvhError::UnexpectedEof)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), BvhError> {
+        let found = self.next()?;
+        if found.eq_ignore_ascii_case(expected) {
+            Ok(())
+        } else {
+            Err(BvhError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: found.to_string(),
+            })
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, BvhError> {
+        let token = self.next()?;
+        token
+            .parse()
+            .map_err(|_| BvhError::InvalidNumber(token.to_string()))
+    }
+}
+
+impl Bvh {
+    fn parse(text: &str) -> Result<Self, BvhError> {
+        let mut tokens = Tokens(text.split_whitespace());
+
+        tokens.expect("HIERARCHY")?;
+        tokens.expect("ROOT")?;
+
+        let mut joints = Vec::new();
+        let mut num_channels = 0;
+        Self::parse_joint(&mut tokens, None, &mut joints, &mut num_channels)?;
+
+        tokens.expect("MOTION")?;
+        tokens.expect("Frames:")?;
+        let num_frames = tokens.number()? as usize;
+        tokens.expect("Frame")?;
+        tokens.expect("Time:")?;
+        let frame_time = tokens.number()?;
+
+        // Frames are whitespace separated like the rest of the file,
+        // so count values rather than lines.
+        let mut frames = Vec::with_capacity(num_frames);
+        for frame in 0..num_frames {
+            let mut values = Vec::with_capacity(num_channels);
+            for _ in 0..num_channels {
+                match tokens.0.next() {
+                    Some(token) => values.push(
+                        token
+                            .parse()
+                            .map_err(|_| BvhError::InvalidNumber(token.to_string()))?,
+                    ),
+                    None => {
+                        return Err(BvhError::FrameLength {
+                            frame,
+                            expected: num_channels,
+                            found: values.len(),
+                        });
+                    }
+                }
+            }
+            frames.push(values);
+        }
+
+        Ok(Self {
+            joints,
+            frame_time,
+            frames,
+        })
+    }
+
+    fn parse_joint(
+        tokens: &mut Tokens,
+        parent: Option<usize>,
+        joints: &mut Vec<Joint>,
+        num_channels: &mut usize,
+    ) -> Result<(), BvhError> {
+        let name = tokens.next()?.to_string();
+        tokens.expect("{")?;
+        tokens.expect("OFFSET")?;
+        let offset = Vec3::new(tokens.number()?, tokens.number()?, tokens.number()?);
+
+        let index = joints.len();
+        joints.push(Joint {
+            name,
+            parent,
+            offset,
+            channels: Vec::new(),
+            channel_offset: *num_channels,
+        });
+
+        loop {
+            match tokens.next()? {
+                "}" => return Ok(()),
+                token if token.eq_ignore_ascii_case("CHANNELS") => {
+                    let count = tokens.number()? as usize;
+                    for _ in 0..count {
+                        let channel = Channel::parse(tokens.next()?)?;
+                        joints[index].channels.push(channel);
+                    }
+                    *num_channels += count;
+                }
+                token if token.eq_ignore_ascii_case("JOINT") => {
+                    Self::parse_joint(tokens, Some(index), joints, num_channels)?;
+                }
+                token if token.eq_ignore_ascii_case("End") => {
+                    // End sites only carry an offset, which we have no use for.
+                    tokens.expect("Site")?;
+                    tokens.expect("{")?;
+                    tokens.expect("OFFSET")?;
+                    tokens.number()?;
+                    tokens.number()?;
+                    tokens.number()?;
+                    tokens.expect("}")?;
+                }
+                found => {
+                    return Err(BvhError::UnexpectedToken {
+                        expected: "}".to_string(),
+                        found: found.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Computes the global rotation and position of each joint for a frame, in BVH space.
+    fn pose(&self, frame: &[f32]) -> Vec<(Quat, Vec3)> {
+        let mut pose: Vec<(Quat, Vec3)> = Vec::with_capacity(self.joints.len());
+
+        for joint in &self.joints {
+            let mut rotation = Quat::IDENTITY;
+            let mut translation = joint.offset;
+
+            for (i, channel) in joint.channels.iter().enumerate() {
+                let value = frame[joint.channel_offset + i];
+                match channel {
+                    Channel::XPosition => translation.x += value,
+                    Channel::YPosition => translation.y += value,
+                    Channel::ZPosition => translation.z += value,
+                    Channel::XRotation => rotation *= Quat::from_rotation_x(value.to_radians()),
+                    Channel::YRotation => rotation *= Quat::from_rotation_y(value.to_radians()),
+                    Channel::ZRotation => rotation *= Quat::from_rotation_z(value.to_radians()),
+                }
+            }
+
+            pose.push(match joint.parent {
+                Some(parent) => {
+                    let (parent_rotation, parent_position) = pose[parent];
+                    (
+                        parent_rotation * rotation,
+                        parent_position + parent_rotation * translation,
+                    )
+                }
+                None => (rotation, translation),
+            });
+        }
+
+        pose
+    }
+
+    /// Height of the hips above the lowest joint in the rest pose, in BVH units.
+    fn rest_hips_height(&self, bones: &[Option<BoneName>], basis: Quat) -> Option<f32> {
+        let hips = bones.iter().position(|b| *b == Some(BoneName::Hips))?;
+
+        let num_channels = self.joints.iter().map(|j| j.channels.len()).sum();
+        let heights = self
+            .pose(&vec![0.0; num_channels])
+            .into_iter()
+            .map(|(_, position)| (basis * position).y)
+            .collect::<Vec<_>>();
+
+        let ground = heights.iter().copied().fold(f32::INFINITY, f32::min);
+        let height = heights[hips] - ground;
+
+        (height > f32::EPSILON).then_some(height)
+    }
+
+    fn to_clip(
+        &self,
+        settings: &BvhLoaderSettings,
+    ) -> Result<(AnimationClip, Option<BvhRootMotion>), BvhError> {
+        let bone_map = settings
+            .bone_map
+            .iter()
+            .map(|(name, bone)| (normalize_joint_name(name), *bone))
+            .collect::<HashMap<_, _>>();
+
+        let bones = self
+            .joints
+            .iter()
+            .map(|joint| bone_map.get(&normalize_joint_name(&joint.name)).copied())
+            .collect::<Vec<_>>();
+
+        if bones.iter().all(Option::is_none) {
+            return Err(BvhError::NoMappedJoints);
+        }
+
+        // Nearest ancestor that is mapped to a bone.
+        let mapped_parents = (0..self.joints.len())
+            .map(|mut i| {
+                while let Some(parent) = self.joints[i].parent {
+                    if bones[parent].is_some() {
+                        return Some(parent);
+                    }
+                    i = parent;
+                }
+                None
+            })
+            .collect::<Vec<_>>();
+
+        let mut basis = match settings.up_axis {
+            BvhUpAxis::Y => Quat::IDENTITY,
+            BvhUpAxis::Z => Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2),
+        };
+        if settings.flip_forward {
+            basis = Quat::from_rotation_y(std::f32::consts::PI) * basis;
+        }
+        let basis_inv = basis.inverse();
+
+        let scale = settings
+            .hips_height
+            .zip(self.rest_hips_height(&bones, basis))
+            .map(|(avatar, capture)| avatar / capture)
+            .unwrap_or(settings.scale);
+
+        let mut rotations = vec![Vec::new(); self.joints.len()];
+        let mut hips = Vec::new();
+
+        // Keyframe curves need at least two samples.
+        let frames = if self.frames.len() == 1 {
+            vec![&self.frames[0], &self.frames[0]]
+        } else {
+            self.frames.iter().collect()
+        };
+
+        for (f, frame) in frames.into_iter().enumerate() {
+            let time = f as f32 * self.frame_time;
+
+            let pose = self
+                .pose(frame)
+                .into_iter()
+                .map(|(rotation, position)| {
+                    (basis * rotation * basis_inv, basis * position * scale)
+                })
+                .collect::<Vec<_>>();
+
+            for (i, bone) in bones.iter().enumerate() {
+                let Some(bone) = bone else {
+                    continue;
+                };
+
+                let (rotation, position) = pose[i];
+
+                let local = match mapped_parents[i] {
+                    Some(parent) => pose[parent].0.inverse() * rotation,
+                    None => rotation,
+                };
+                rotations[i].push((time, local.normalize()));
+
+                if *bone == BoneName::Hips {
+                    hips.push((time, position));
+                }
+            }
+        }
+
+        let root_motion = match settings.root_motion {
+            RootMotion::Keep => None,
+            RootMotion::Extract => {
+                let start = hips.first().map(|(_, p)| *p).unwrap_or_default();
+
+                let keyframes = hips
+                    .iter_mut()
+                    .map(|(time, position)| {
+                        let delta = Vec3::new(position.x - start.x, 0.0, position.z - start.z);
+                        position.x = start.x;
+                        position.z = start.z;
+                        (*time, delta)
+                    })
+                    .collect();
+
+                Some(BvhRootMotion { keyframes })
+            }
+        };
+
+        let mut clip = AnimationClip::default();
+
+        for (i, keyframes) in rotations.into_iter().enumerate() {
+            let Some(bone) = bones[i] else {
+                continue;
+            };
+
+            let Ok(curve) = AnimatableKeyframeCurve::new(keyframes) else {
+                continue;
+            };
+
+            clip.add_curve_to_target(
+                VRM_ANIMATION_TARGETS[&bone],
+                AnimatableCurve::new(animated_field!(Transform::rotation), curve),
+            );
+        }
+
+        if let Ok(curve) = AnimatableKeyframeCurve::new(hips) {
+            clip.add_curve_to_target(
+                VRM_ANIMATION_TARGETS[&BoneName::Hips],
+                AnimatableCurve::new(animated_field!(Transform::translation), curve),
+            );
+        }
+
+        Ok((clip, root_motion))
+    }
+}
+
+/// Lowercases a joint name and strips any namespace prefix, e.g. `mixamorig:LeftArm` -> `leftarm`.
+fn normalize_joint_name(name: &str) -> String {
+    let name = name.rsplit(':').next().unwrap_or(name);
+    name.to_ascii_lowercase()
+}
+
+/// Joint names used by common BVH sources (CMU, Mixamo, Rokoko, MotionBuilder).
+pub fn default_bone_map() -> HashMap<String, BoneName> {
+    let mut map = HashMap::default();
+
+    for (names, bone) in [
+        (&["hips", "pelvis"][..], BoneName::Hips),
+        (&["spine", "spine0"], BoneName::Spine),
+        (&["spine1", "chest"], BoneName::Chest),
+        (&["spine2", "chest2", "upperchest"], BoneName::UpperChest),
+        (&["neck"], BoneName::Neck),
+        (&["head"], BoneName::Head),
+        (&["leftshoulder", "leftcollar"], BoneName::LeftShoulder),
+        (
+            &["leftarm", "leftuparm", "leftupperarm"],
+            BoneName::LeftUpperArm,
+        ),
+        (
+            &["leftforearm", "leftlowarm", "leftlowerarm"],
+            BoneName::LeftLowerArm,
+        ),
+        (&["lefthand", "leftwrist"], BoneName::LeftHand),
+        (&["rightshoulder", "rightcollar"], BoneName::RightShoulder),
+        (
+            &["rightarm", "rightuparm", "rightupperarm"],
+            BoneName::RightUpperArm,
+        ),
+        (
+            &["rightforearm", "rightlowarm", "rightlowerarm"],
+            BoneName::RightLowerArm,
+        ),
+        (&["righthand", "rightwrist"], BoneName::RightHand),
+        (
+            &["leftupleg", "lefthip", "leftupperleg", "leftthigh"],
+            BoneName::LeftUpperLeg,
+        ),
+        (
+            &[
+                "leftleg",
+                "leftlowleg",
+                "leftlowerleg",
+                "leftknee",
+                "leftshin",
+            ],
+            BoneName::LeftLowerLeg,
+        ),
+        (&["leftfoot", "leftankle"], BoneName::LeftFoot),
+        (&["lefttoebase", "lefttoe", "lefttoes"], BoneName::LeftToes),
+        (
+            &["rightupleg", "righthip", "rightupperleg", "rightthigh"],
+            BoneName::RightUpperLeg,
+        ),
+        (
+            &[
+                "rightleg",
+                "rightlowleg",
+                "rightlowerleg",
+                "rightknee",
+                "rightshin",
+            ],
+            BoneName::RightLowerLeg,
+        ),
+        (&["rightfoot", "rightankle"], BoneName::RightFoot),
+        (
+            &["righttoebase", "righttoe", "righttoes"],
+            BoneName::RightToes,
+        ),
+    ] {
+        for name in names {
+            map.insert(name.to_string(), bone);
+        }
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BVH: &str = "HIERARCHY
+ROOT Hips
+{
+    OFFSET 0.0 0.0 0.0
+    CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    JOINT LowerBack
+    {
+        OFFSET 0.0 10.0 0.0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT Spine
+        {
+            OFFSET 0.0 10.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            End Site
+            {
+                OFFSET 0.0 10.0 0.0
+            }
+        }
+    }
+}
+MOTION
+Frames: 2
+Frame Time: 0.5
+0.0 90.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+100.0 90.0 50.0 0.0 0.0 0.0 0.0 90.0 0.0 0.0 0.0 0.0
+";
+
+    #[test]
+    fn parse() {
+        let bvh = Bvh::parse(BVH).unwrap();
+
+        assert_eq!(bvh.joints.len(), 3);
+        assert_eq!(bvh.joints[2].parent, Some(1));
+        assert_eq!(bvh.joints[2].channel_offset, 9);
+        assert_eq!(bvh.frame_time, 0.5);
+        assert_eq!(bvh.frames.len(), 2);
+        assert_eq!(bvh.frames[1][0], 100.0);
+    }
+
+    #[test]
+    fn unmapped_rotation_carries_to_descendants() {
+        let bvh = Bvh::parse(BVH).unwrap();
+
+        let pose = bvh.pose(&bvh.frames[1]);
+        let expected = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+        assert!(pose[2].0.abs_diff_eq(expected, 1e-5));
+    }
+
+    #[test]
+    fn extract_root_motion() {
+        let bvh = Bvh::parse(BVH).unwrap();
+
+        let settings = BvhLoaderSettings {
+            root_motion: RootMotion::Extract,
+            ..default()
+        };
+
+        let (clip, root_motion) = bvh.to_clip(&settings).unwrap();
+        let root_motion = root_motion.unwrap();
+
+        assert_eq!(clip.duration(), 0.5);
+        assert_eq!(root_motion.sample(0.0), Vec3::ZERO);
+        assert!(
+            root_motion
+                .sample(0.5)
+                .abs_diff_eq(Vec3::new(1.0, 0.0, 0.5), 1e-5)
+        );
+        assert!(
+            root_motion
+                .sample(0.25)
+                .abs_diff_eq(Vec3::new(0.5, 0.0, 0.25), 1e-5)
+        );
+    }
+
+    #[test]
+    fn scale_to_hips_height() {
+        let bvh = Bvh::parse(
+            "HIERARCHY
+ROOT Hips
+{
+    OFFSET 0.0 0.0 0.0
+    CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    JOINT LeftUpLeg
+    {
+        OFFSET 10.0 0.0 0.0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT LeftLeg
+        {
+            OFFSET 0.0 -40.0 0.0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT LeftFoot
+            {
+                OFFSET 0.0 -40.0 0.0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+            }
+        }
+    }
+}
+MOTION
+Frames: 2
+Frame Time: 0.5
+0.0 80.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+40.0 80.0 20.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0 0.0
+",
+        )
+        .unwrap();
+
+        let settings = BvhLoaderSettings {
+            hips_height: Some(1.2),
+            root_motion: RootMotion::Extract,
+            ..default()
+        };
+
+        let (_, root_motion) = bvh.to_clip(&settings).unwrap();
+
+        // Capture hips rest at 80 units, so translation is scaled by 1.2 / 80.
+        assert!(
+            root_motion
+                .unwrap()
+                .sample(0.5)
+                .abs_diff_eq(Vec3::new(0.6, 0.0, 0.3), 1e-5)
+        );
+    }
+}
@@ -1,2 +1,3 @@
+pub mod bvh;
 pub mod target_chain;
 pub mod vrm;
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use bevy::{
+    ecs::{entity::MapEntities, reflect::ReflectMapEntities},
+    prelude::*,
+    render::mesh::morph::MorphWeights,
+};
+
+/// Facial expressions of an avatar, built from VRM 0.x blend shape groups.
+/// Set an expression's weight to drive the morph targets it binds to.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component, MapEntities)]
+pub struct VrmExpressions(pub Vec<VrmExpression>);
+
+#[derive(Clone, Debug, Reflect)]
+pub struct VrmExpression {
+    pub name: String,
+    /// VRM 0.x preset name, such as `blink` or `a`.
+    pub preset: Option<String>,
+    pub is_binary: bool,
+    pub binds: Vec<MorphBind>,
+    pub weight: f32,
+}
+
+#[derive(Clone, Debug, Reflect)]
+pub struct MorphBind {
+    /// Entity holding the [MorphWeights] of the bound mesh.
+    pub entity: Entity,
+    pub entity_name: String,
+    /// Morph target index.
+    pub index: usize,
+    /// Morph target weight when the expression is fully applied, from 0 to 1.
+    pub weight: f32,
+}
+
+impl VrmExpression {
+    /// Whether this expression is called `name`, by name or preset.
+    /// Comparison is case-insensitive.
+    pub fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self
+                .preset
+                .as_ref()
+                .is_some_and(|preset| preset.eq_ignore_ascii_case(name))
+    }
+}
+
+impl VrmExpressions {
+    pub fn get(&self, name: &str) -> Option<&VrmExpression> {
+        self.0.iter().find(|e| e.matches(name))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut VrmExpression> {
+        self.0.iter_mut().find(|e| e.matches(name))
+    }
+
+    /// Sets the weight of an expression, returning `false` if it does not exist.
+    pub fn set_weight(&mut self, name: &str, weight: f32) -> bool {
+        match self.get_mut(name) {
+            Some(expression) => {
+                expression.weight = weight;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl MapEntities for VrmExpressions {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for expression in &mut self.0 {
+            for bind in &mut expression.binds {
+                bind.entity = entity_mapper.get_mapped(bind.entity);
+            }
+        }
+    }
+}
+
+pub(crate) fn remap_expression_entities(
+    mut expressions: Query<(Entity, &mut VrmExpressions), Added<VrmExpressions>>,
+    children: Query<&Children>,
+    existing_entities: Query<Entity>,
+    names: Query<&Name>,
+) {
+    for (entity, mut expressions) in expressions.iter_mut() {
+        let needs_remapping = expressions
+            .0
+            .iter()
+            .flat_map(|expression| &expression.binds)
+            .any(|bind| !existing_entities.contains(bind.entity));
+
+        if !needs_remapping {
+            continue;
+        }
+
+        // Only search within this avatar, so multiple instances of a model do not collide.
+        let name_to_entity = children
+            .iter_descendants(entity)
+            .filter_map(|child| Some((names.get(child).ok()?.as_str(), child)))
+            .collect::<HashMap<_, _>>();
+
+        for expression in &mut expressions.0 {
+            expression.binds.retain_mut(|bind| {
+                match name_to_entity.get(bind.entity_name.as_str()) {
+                    Some(found) => {
+                        bind.entity = *found;
+                        true
+                    }
+                    None => false,
+                }
+            });
+        }
+    }
+}
+
+pub(crate) fn apply_expressions(
+    expressions: Query<&VrmExpressions, Changed<VrmExpressions>>,
+    mut morph_weights: Query<&mut MorphWeights>,
+) {
+    for expressions in expressions.iter() {
+        let mut weights = HashMap::<(Entity, usize), f32>::default();
+
+        for expression in expressions.0.iter() {
+            let mut weight = expression.weight.clamp(0.0, 1.0);
+
+            if expression.is_binary {
+                weight = weight.round();
+            }
+
+            for bind in expression.binds.iter() {
+                *weights.entry((bind.entity, bind.index)).or_default() += weight * bind.weight;
+            }
+        }
+
+        for ((entity, index), weight) in weights {
+            let Ok(mut morph_weights) = morph_weights.get_mut(entity) else {
+                continue;
+            };
+
+            if let Some(value) = morph_weights.weights_mut().get_mut(index) {
+                *value = weight.min(1.0);
+            }
+        }
+    }
+}
@@ -1,32 +1,45 @@
+use std::io::Cursor;
+
 use bevy::{
     animation::AnimationTarget,
     ecs::system::{RunSystemError, RunSystemOnce},
     prelude::*,
     transform::systems::{propagate_parent_transforms, sync_simple_transforms},
 };
-use bevy_gltf_kun::import::{extensions::BevyExtensionImport, gltf::document::ImportContext};
+use bevy_gltf_kun::import::{
+    extensions::BevyExtensionImport,
+    gltf::{document::ImportContext, texture::load_texture},
+};
 use gltf_kun::{
     extensions::ExtensionImport,
     graph::{
-        ByteNode, Edge, Extensions, Graph, Weight,
+        ByteNode, Edge, Extensions, Graph, GraphNodeWeight, Weight,
         gltf::{GltfDocument, GltfWeight, Material, Node, Primitive, Scene},
     },
     io::format::gltf::GltfFormat,
 };
-use gltf_kun_vrm::vrm0::{
-    Vrm,
-    mesh_annotation::{MeshAnnotation, MeshAnnotationEdges},
+use gltf_kun_vrm::{
+    vrm0::{
+        Vrm,
+        mesh_annotation::{MeshAnnotation, MeshAnnotationEdges},
+    },
+    vrm1::{vrmc_materials_mtoon::VrmcMaterialsMtoon, vrmc_vrm::VrmcVrm},
+    weight::{TryByteNode, WeightError},
 };
+use image::{ImageError, ImageFormat, ImageReader, imageops::FilterType};
 use petgraph::{Direction, visit::EdgeRef};
 use serde_vrm::vrm0::{BoneName, FirstPersonFlag};
 
 use crate::{
     animations::vrm::VRM_ANIMATION_TARGETS,
+    expressions::{MorphBind, VrmExpression, VrmExpressions},
+    first_person::VrmFirstPerson,
+    loader::{VrmImportError, import_settings, report, report_graph},
+    measurements::VrmMeasurements,
+    meta::{THUMBNAIL_LABEL, VrmMeta},
     spring_bones::{SpringBone, SpringBoneLogicState, SpringBones},
 };
 
-use self::vrm0::{import_material, import_primitive_material};
-
 pub mod vrm0;
 pub mod vrm1;
 
@@ -38,20 +51,62 @@ impl ExtensionImport<GltfDocument, GltfFormat> for VrmExtensions {
         format: &mut GltfFormat,
         doc: &GltfDocument,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        Vrm::import(graph, format, doc)?;
+        if let Err(e) = Vrm::import(graph, format, doc) {
+            report_graph(graph, VrmImportError::InvalidExtension(e.to_string()));
+        }
+
+        if let Err(e) = VrmcVrm::import(graph, format, doc) {
+            report_graph(graph, VrmImportError::InvalidExtension(e.to_string()));
+        }
+
+        if let Err(e) = VrmcMaterialsMtoon::import(graph, format, doc) {
+            report_graph(graph, VrmImportError::InvalidExtension(e.to_string()));
+        }
+
+        for e in weight_errors(graph, doc) {
+            report_graph(graph, VrmImportError::InvalidWeight(e.to_string()));
+        }
 
         Ok(())
     }
 }
 
+/// Reads every imported extension weight, as the rest of the import reads them
+/// with [ByteNode::read], which falls back to defaults on malformed bytes.
+fn weight_errors(graph: &Graph, doc: &GltfDocument) -> Vec<WeightError> {
+    let mut errors = Vec::new();
+
+    if let Some(ext) = doc.get_extension::<Vrm>(graph) {
+        errors.extend(ext.check_weights(graph).err());
+    }
+
+    if let Some(ext) = doc.get_extension::<VrmcVrm>(graph) {
+        errors.extend(ext.try_read(graph).err());
+    }
+
+    for material in doc.materials(graph) {
+        if let Some(ext) = material.get_extension::<VrmcMaterialsMtoon>(graph) {
+            errors.extend(ext.try_read(graph).err());
+        }
+    }
+
+    errors
+}
+
 impl BevyExtensionImport<GltfDocument> for VrmExtensions {
     fn import_material(
         context: &mut ImportContext,
-        _standard_material: &mut StandardMaterial,
+        standard_material: &mut StandardMaterial,
         material: Material,
     ) {
+        if !import_settings(context).mtoon {
+            return;
+        }
+
         if let Some(ext) = context.doc.get_extension::<Vrm>(context.graph) {
-            import_material(context, material, ext);
+            vrm0::import_material(context, material, ext);
+        } else if let Some(ext) = material.get_extension::<VrmcMaterialsMtoon>(context.graph) {
+            vrm1::import_material(context, standard_material, material, ext);
         }
     }
 
@@ -62,53 +117,40 @@ impl BevyExtensionImport<GltfDocument> for VrmExtensions {
         entity: &mut EntityWorldMut,
         primitive: Primitive,
     ) {
-        if let Some(ext) = context.doc.get_extension::<Vrm>(context.graph) {
-            import_primitive_material(context, entity, ext, primitive);
+        let settings = import_settings(context);
+
+        if settings.mtoon && !settings.force_standard_material {
+            match context.doc.get_extension::<Vrm>(context.graph) {
+                Some(ext) => vrm0::import_primitive_material(context, entity, ext, primitive),
+                None => vrm1::import_primitive_material(context, entity, primitive),
+            }
+        }
+
+        if !settings.first_person {
+            return;
         }
 
         let mut flag = context
             .graph
             .edges_directed(primitive.0, Direction::Incoming)
             .find_map(|edge| {
-                if let Edge::Other(name) = edge.weight() {
-                    if name == MeshAnnotationEdges::Mesh.to_string().as_str() {
-                        let annotation = MeshAnnotation(edge.source());
-                        let weight = annotation.read(context.graph);
-                        return Some(weight.first_person_flag);
-                    }
+                if let Edge::Other(name) = edge.weight()
+                    && name == MeshAnnotationEdges::Mesh.to_string().as_str()
+                {
+                    let annotation = MeshAnnotation(edge.source());
+                    let weight = annotation.read(context.graph);
+                    return Some(weight.first_person_flag);
                 }
 
                 None
             })
             .unwrap_or_default();
 
-        if flag == FirstPersonFlag::Auto {
-            let mesh = primitive.mesh(context.graph).unwrap();
-            let nodes = mesh.nodes(context.graph);
-
-            let Some(ext) = get_vrm_extension(context.graph) else {
-                warn!("VRM extension not found");
-                return;
-            };
-
-            let head = ext
-                .human_bones(context.graph)
-                .into_iter()
-                .find(|b| {
-                    let b_weight = b.read(context.graph);
-                    b_weight.name == Some(BoneName::Head)
-                })
-                .unwrap();
-
-            let head_node = head.node(context.graph).unwrap();
-
-            for node in nodes {
-                let is_child = find_child(context.graph, node, head_node);
-
-                if is_child {
-                    flag = FirstPersonFlag::ThirdPersonOnly;
-                    break;
-                }
+        // Missing extensions are reported once by the scene import.
+        if flag == FirstPersonFlag::Auto && get_vrm_extension(context.graph).is_some() {
+            match auto_first_person_flag(context.graph, primitive) {
+                Ok(auto_flag) => flag = auto_flag,
+                Err(e) => report(context, e),
             }
         }
 
@@ -118,11 +160,11 @@ impl BevyExtensionImport<GltfDocument> for VrmExtensions {
     fn import_root(_context: &mut ImportContext) {}
 
     fn import_scene(context: &mut ImportContext, _scene: Scene, world: &mut World) {
+        let settings = import_settings(context);
+
         let _ = world.run_system_once(sync_simple_transforms);
         let _ = world.run_system_once(propagate_parent_transforms);
 
-        let graph = &context.graph;
-
         let names: Result<Vec<(Entity, Name)>, RunSystemError> =
             world.run_system_once(|names: Query<(Entity, &Name)>| -> Vec<(Entity, Name)> {
                 names
@@ -136,86 +178,83 @@ impl BevyExtensionImport<GltfDocument> for VrmExtensions {
             return;
         };
 
-        let Some(ext) = get_vrm_extension(graph) else {
-            warn!("VRM extension not found");
+        let Some(ext) = get_vrm_extension(context.graph) else {
+            // Only the metadata and materials of VRM 1.0 are imported.
+            if context
+                .doc
+                .get_extension::<VrmcVrm>(context.graph)
+                .is_none()
+            {
+                report(context, VrmImportError::MissingExtension);
+            }
             return;
         };
 
-        let mut spring_bones = vec![];
+        if settings.spring_bones {
+            import_spring_bones(context, world, ext, &names);
+        }
 
-        for bone_group in ext.bone_groups(graph) {
-            let (bone_entities, bone_names): (Vec<_>, Vec<_>) = bone_group
-                .bones(graph)
-                .into_iter()
-                .filter_map(|node| {
-                    let node_handle = context.gltf.node_handles.get(&node)?;
-                    let node_name =
-                        context
-                            .gltf
-                            .named_nodes
-                            .iter()
-                            .find_map(|(name, gltf_node)| {
-                                (gltf_node == node_handle).then(|| name.clone())
-                            })?;
-                    let (entity, _) = names.iter().find(|(_, name)| name.as_str() == node_name)?;
-                    Some((*entity, node_name))
-                })
-                .unzip();
-
-            let weight = bone_group.read(graph);
-
-            let gravity_dir = Vec3::new(
-                weight.gravity_dir.x,
-                weight.gravity_dir.y,
-                weight.gravity_dir.z,
-            );
+        let graph = &context.graph;
+
+        let mut expressions = Vec::new();
+
+        for group in ext.blend_shape_groups(graph) {
+            let weight = group.read(graph);
+
+            let mut binds = Vec::new();
+
+            for bind in group.binds(graph) {
+                let bind_weight = bind.read(graph);
+
+                let (Some(mesh), Some(index)) = (bind.mesh(graph), bind_weight.index) else {
+                    continue;
+                };
+
+                // Morph weights live on each node that instances the mesh.
+                for node in mesh.nodes(graph) {
+                    let Some(node_name) = find_node_name(context, node) else {
+                        continue;
+                    };
+                    let Some((entity, _)) =
+                        names.iter().find(|(_, name)| name.as_str() == node_name)
+                    else {
+                        continue;
+                    };
 
-            spring_bones.push(SpringBone {
-                bones: bone_entities,
-                bone_names,
-                center: weight.center.unwrap_or_default(),
-                drag_force: weight.drag_force.unwrap_or_default(),
-                gravity_dir,
-                gravity_power: weight.gravity_power.unwrap_or_default(),
-                hit_radius: weight.hit_radius.unwrap_or_default(),
-                stiffness: weight.stiffiness.unwrap_or_default(),
+                    binds.push(MorphBind {
+                        entity: *entity,
+                        entity_name: node_name,
+                        index: index as usize,
+                        // VRM 0.x bind weights range from 0 to 100.
+                        weight: bind_weight.weight.unwrap_or(100.0) / 100.0,
+                    });
+                }
+            }
+
+            expressions.push(VrmExpression {
+                name: weight.name.unwrap_or_default(),
+                preset: weight
+                    .preset_name
+                    .and_then(|preset| serde_json::to_value(preset).ok())
+                    .and_then(|value| value.as_str().map(str::to_string)),
+                is_binary: weight.is_binary.unwrap_or_default(),
+                binds,
+                weight: 0.0,
             });
         }
 
         let _ = world.run_system_once_with(
-            |In(spring_bones): In<Vec<SpringBone>>,
+            |In(expressions): In<Vec<VrmExpression>>,
              mut commands: Commands,
              query: Query<Entity, Without<ChildOf>>| {
-                commands
-                    .entity(query.single().unwrap())
-                    .insert(SpringBones(spring_bones));
-            },
-            spring_bones,
-        );
-
-        let _ = world.run_system_once(
-            |mut spring_boness: Query<&mut SpringBones>,
-             children: Query<&Children>,
-             names: Query<&Name>| {
-                for mut spring_bones in spring_boness.iter_mut() {
-                    for spring_bone in spring_bones.0.iter_mut() {
-                        let original_bones = spring_bone.bones.clone();
-                        for bone in original_bones {
-                            for child in children.iter_descendants(bone) {
-                                if !spring_bone.bones.contains(&child) {
-                                    spring_bone.bones.push(child);
-                                    if let Ok(name) = names.get(child) {
-                                        spring_bone.bone_names.push(name.to_string());
-                                    }
-                                }
-                            }
-                        }
-                    }
+                if let Ok(root) = query.single() {
+                    commands.entity(root).insert(VrmExpressions(expressions));
                 }
             },
+            expressions,
         );
 
-        let _ = world.run_system_once(add_springbone_logic_state);
+        let mut missing_bones = Vec::new();
 
         for bone in ext.human_bones(graph) {
             let node = match bone.node(graph) {
@@ -248,23 +287,20 @@ impl BevyExtensionImport<GltfDocument> for VrmExtensions {
                 None => continue,
             };
 
-            let _ = world.run_system_once_with(
+            let found = world.run_system_once_with(
                 |In((node_name, bone_name)): In<(String, BoneName)>,
                  mut commands: Commands,
                  names: Query<(Entity, &Name)>,
-                 parents: Query<&ChildOf>| {
-                    let node_entity = match names.iter().find_map(|(entity, name)| {
+                 parents: Query<&ChildOf>|
+                 -> bool {
+                    let Some(node_entity) = names.iter().find_map(|(entity, name)| {
                         if name.as_str() == node_name.as_str() {
                             Some(entity)
                         } else {
                             None
                         }
-                    }) {
-                        Some(e) => e,
-                        None => {
-                            warn!("Could not find entity for bone: {}", bone_name);
-                            return;
-                        }
+                    }) else {
+                        return false;
                     };
 
                     let mut root_entity = node_entity;
@@ -285,11 +321,279 @@ impl BevyExtensionImport<GltfDocument> for VrmExtensions {
                         },
                         bone_name,
                     ));
+
+                    true
                 },
                 (node_name, bone_name),
             );
+
+            if let Ok(false) = found {
+                missing_bones.push(bone_name);
+            }
         }
+
+        let first_person = settings
+            .first_person
+            .then(|| read_first_person(ext, graph))
+            .flatten();
+
+        let _ = world.run_system_once_with(
+            |In(first_person): In<Option<VrmFirstPerson>>,
+             mut commands: Commands,
+             query: Query<Entity, Without<ChildOf>>| {
+                if let (Some(first_person), Ok(root)) = (first_person, query.single()) {
+                    commands.entity(root).insert(first_person);
+                }
+            },
+            first_person.clone(),
+        );
+
+        let _ = world.run_system_once_with(insert_measurements, first_person);
+
+        for bone_name in missing_bones {
+            report(context, VrmImportError::MissingBoneNode(bone_name));
+        }
+    }
+}
+
+fn read_first_person(ext: Vrm, graph: &Graph) -> Option<VrmFirstPerson> {
+    let first_person_bone = ext
+        .first_person_bone(graph)
+        .and_then(|bone| bone.read(graph).name);
+
+    let offset = ext.read(graph).first_person.first_person_bone_offset;
+    // VRM 0.x offsets use Unity's coordinates.
+    let offset = Vec3::new(offset.x, offset.y, -offset.z);
+
+    first_person_bone.map(|bone| VrmFirstPerson { bone, offset })
+}
+
+fn import_spring_bones(
+    context: &ImportContext,
+    world: &mut World,
+    ext: Vrm,
+    names: &[(Entity, Name)],
+) {
+    let graph = &context.graph;
+    let mut spring_bones = vec![];
+
+    for bone_group in ext.bone_groups(graph) {
+        let (bone_entities, bone_names): (Vec<_>, Vec<_>) = bone_group
+            .bones(graph)
+            .into_iter()
+            .filter_map(|node| {
+                let node_handle = context.gltf.node_handles.get(&node)?;
+                let node_name = context
+                    .gltf
+                    .named_nodes
+                    .iter()
+                    .find_map(|(name, gltf_node)| {
+                        (gltf_node == node_handle).then(|| name.clone())
+                    })?;
+                let (entity, _) = names.iter().find(|(_, name)| name.as_str() == node_name)?;
+                Some((*entity, node_name))
+            })
+            .unzip();
+
+        let weight = bone_group.read(graph);
+
+        let gravity_dir = Vec3::new(
+            weight.gravity_dir.x,
+            weight.gravity_dir.y,
+            weight.gravity_dir.z,
+        );
+
+        spring_bones.push(SpringBone {
+            bones: bone_entities,
+            bone_names,
+            center: weight.center.unwrap_or_default(),
+            drag_force: weight.drag_force.unwrap_or_default(),
+            gravity_dir,
+            gravity_power: weight.gravity_power.unwrap_or_default(),
+            hit_radius: weight.hit_radius.unwrap_or_default(),
+            stiffness: weight.stiffiness.unwrap_or_default(),
+        });
     }
+
+    let _ = world.run_system_once_with(
+        |In(spring_bones): In<Vec<SpringBone>>,
+         mut commands: Commands,
+         query: Query<Entity, Without<ChildOf>>| {
+            if let Ok(root) = query.single() {
+                commands.entity(root).insert(SpringBones(spring_bones));
+            }
+        },
+        spring_bones,
+    );
+
+    let _ = world.run_system_once(
+        |mut spring_boness: Query<&mut SpringBones>,
+         children: Query<&Children>,
+         names: Query<&Name>| {
+            for mut spring_bones in spring_boness.iter_mut() {
+                for spring_bone in spring_bones.0.iter_mut() {
+                    let original_bones = spring_bone.bones.clone();
+                    for bone in original_bones {
+                        for child in children.iter_descendants(bone) {
+                            if !spring_bone.bones.contains(&child) {
+                                spring_bone.bones.push(child);
+                                if let Ok(name) = names.get(child) {
+                                    spring_bone.bone_names.push(name.to_string());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    );
+
+    let _ = world.run_system_once(add_springbone_logic_state);
+}
+
+/// Reads the metadata of `VRMC_vrm`, or of VRM 0.x if the file has no 1.0 extension.
+pub(crate) fn read_meta(graph: &Graph, doc: &GltfDocument) -> Option<VrmMeta> {
+    if let Some(ext) = doc.get_extension::<VrmcVrm>(graph) {
+        return Some(VrmMeta::from(&ext.read(graph).0.meta));
+    }
+
+    let ext = doc.get_extension::<Vrm>(graph)?;
+    Some(VrmMeta::from(&ext.read(graph).meta))
+}
+
+/// Adds the thumbnail as a labeled sub-asset, returning its handle.
+pub(crate) fn thumbnail(context: &mut ImportContext) -> Option<Handle<Image>> {
+    let texture = match context.doc.get_extension::<VrmcVrm>(context.graph) {
+        // VRM 1.0 refers to the thumbnail's image, loaded through a texture using it.
+        Some(ext) => {
+            let image = ext.read(context.graph).0.meta.thumbnail_image? as usize;
+            let image = *context.doc.images(context.graph).get(image)?;
+            context
+                .doc
+                .textures(context.graph)
+                .into_iter()
+                .find(|texture| texture.image(context.graph) == Some(image))?
+        }
+        None => {
+            let ext = context.doc.get_extension::<Vrm>(context.graph)?;
+            ext.thumbnail(context.graph)?
+        }
+    };
+    let image = texture.image(context.graph)?;
+
+    match load_texture(context, texture, image, true) {
+        Ok(image) => Some(
+            context
+                .load_context
+                .add_labeled_asset(THUMBNAIL_LABEL.to_string(), image),
+        ),
+        Err(e) => {
+            warn!("Failed to load thumbnail: {}", e);
+            None
+        }
+    }
+}
+
+/// Downscales each image in the document that exceeds `max_size`, before it is imported.
+/// Smaller images are left as they are, after reading their dimensions.
+pub(crate) fn limit_texture_size(graph: &mut Graph, doc: &GltfDocument, max_size: u32) {
+    for (i, mut image) in doc.images(graph).into_iter().enumerate() {
+        let weight = image.get_mut(graph);
+
+        let Ok((width, height)) = ImageReader::new(Cursor::new(&weight.data))
+            .with_guessed_format()
+            .map_err(ImageError::from)
+            .and_then(ImageReader::into_dimensions)
+        else {
+            continue;
+        };
+
+        if width <= max_size && height <= max_size {
+            continue;
+        }
+
+        let resized = image::load_from_memory(&weight.data).and_then(|dynamic| {
+            let mut bytes = Vec::new();
+            dynamic
+                .resize(max_size, max_size, FilterType::Triangle)
+                .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+            Ok(bytes)
+        });
+
+        match resized {
+            Ok(bytes) => {
+                weight.data = bytes;
+                weight.mime_type = Some("image/png".to_string());
+            }
+            Err(e) => warn!("Failed to downscale image {}: {}", i, e),
+        }
+    }
+}
+
+fn insert_measurements(
+    In(first_person): In<Option<VrmFirstPerson>>,
+    mut commands: Commands,
+    bones: Query<(&BoneName, &GlobalTransform)>,
+    roots: Query<(Entity, &GlobalTransform), Without<ChildOf>>,
+) {
+    let Ok((root, root_global)) = roots.single() else {
+        return;
+    };
+
+    let to_root = root_global.affine().inverse();
+
+    let bone_positions = bones
+        .iter()
+        .map(|(bone, global)| (*bone, to_root.transform_point3(global.translation())))
+        .collect();
+
+    let eye_position = first_person.and_then(|first_person| {
+        let (_, global) = bones.iter().find(|(bone, _)| **bone == first_person.bone)?;
+        Some(to_root.transform_point3(global.transform_point(first_person.offset)))
+    });
+
+    commands
+        .entity(root)
+        .insert(VrmMeasurements::from_bones(&bone_positions, eye_position));
+}
+
+fn find_node_name(context: &ImportContext, node: Node) -> Option<String> {
+    let node_handle = context.gltf.node_handles.get(&node)?;
+    context
+        .gltf
+        .named_nodes
+        .iter()
+        .find_map(|(name, gltf_node)| (gltf_node == node_handle).then(|| name.clone()))
+}
+
+/// Hides meshes attached to the head in first-person.
+fn auto_first_person_flag(
+    graph: &Graph,
+    primitive: Primitive,
+) -> Result<FirstPersonFlag, VrmImportError> {
+    let mesh = primitive.mesh(graph).ok_or(VrmImportError::MissingMesh)?;
+    let ext = get_vrm_extension(graph).ok_or(VrmImportError::MissingExtension)?;
+
+    let head = ext
+        .human_bones(graph)
+        .into_iter()
+        .find(|b| b.read(graph).name == Some(BoneName::Head))
+        .ok_or(VrmImportError::MissingHumanBone(BoneName::Head))?;
+
+    let head_node = head
+        .node(graph)
+        .ok_or(VrmImportError::MissingBoneNode(BoneName::Head))?;
+
+    let is_head = mesh
+        .nodes(graph)
+        .into_iter()
+        .any(|node| find_child(graph, node, head_node));
+
+    Ok(if is_head {
+        FirstPersonFlag::ThirdPersonOnly
+    } else {
+        FirstPersonFlag::Auto
+    })
 }
 
 fn find_child(graph: &Graph, target: Node, parent: Node) -> bool {
@@ -336,10 +640,10 @@ fn add_springbone_logic_state(
                         Ok(c) => c,
                         Err(_) => {
                             // Adds an extra spring bone below it to make it look even better.
-                            if let Ok(name) = names.get(*bone) {
-                                if name.as_str() == "donotaddmore" {
-                                    continue;
-                                }
+                            if let Ok(name) = names.get(*bone)
+                                && name.as_str() == "donotaddmore"
+                            {
+                                continue;
                             }
                             let child = commands
                                 .spawn((
@@ -386,3 +690,36 @@ fn add_springbone_logic_state(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use image::DynamicImage;
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::new_rgba8(width, height)
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn downscale_large_images() {
+        let mut graph = Graph::default();
+        let doc = GltfDocument::new(&mut graph);
+
+        let mut large = doc.create_image(&mut graph);
+        large.get_mut(&mut graph).data = png(64, 32);
+
+        let mut small = doc.create_image(&mut graph);
+        small.get_mut(&mut graph).data = png(8, 4);
+
+        limit_texture_size(&mut graph, &doc, 16);
+
+        let large = image::load_from_memory(&large.get(&graph).data).unwrap();
+        assert_eq!((large.width(), large.height()), (16, 8));
+        assert_eq!(small.get(&graph).data, png(8, 4));
+    }
+}
@@ -1,19 +1,26 @@
+use std::f32::consts::TAU;
+
 use bevy::{asset::LoadedAsset, prelude::*};
 use bevy_gltf_kun::import::gltf::document::ImportContext;
 use bevy_shader_mtoon::{MtoonMaterial, OutlineSync, VrmOutlineMode};
 use gltf_kun::graph::{
     ByteNode,
-    gltf::{Material, Primitive},
+    gltf::{Material, Primitive, Texture},
+};
+use gltf_kun_vrm::vrm0::{
+    Vrm,
+    material_property::{MaterialProperty, MaterialPropertyWeight},
 };
-use gltf_kun_vrm::vrm0::{Vrm, material_property::MaterialProperty};
 use serde_vrm::vrm0::Shader;
 
+use crate::loader::{VrmImportError, report};
+
 pub fn import_material(context: &mut ImportContext, material: Material, ext: Vrm) {
     for (i, material_property) in ext.material_properties(context.graph).iter().enumerate() {
         let m = match material_property.material(context.graph) {
             Some(material) => material,
             None => {
-                warn!("Material not found for property {}", i);
+                report(context, VrmImportError::MissingMaterial(i));
                 continue;
             }
         };
@@ -29,7 +36,7 @@ pub fn import_material(context: &mut ImportContext, material: Material, ext: Vrm
                 let label = mtoon_label(i);
 
                 if !context.load_context.has_labeled_asset(label.clone()) {
-                    let mtoon = load_mtoon_shader(context, *material_property);
+                    let mtoon = load_mtoon_shader(context, i, *material_property);
 
                     context
                         .load_context
@@ -38,7 +45,10 @@ pub fn import_material(context: &mut ImportContext, material: Material, ext: Vrm
             }
             Some(Shader::Gltf) => {}
             Some(other) => {
-                warn!("Unsupported shader: {:?}", other);
+                report(
+                    context,
+                    VrmImportError::UnsupportedShader(format!("{:?}", other)),
+                );
             }
             None => {}
         }
@@ -60,7 +70,7 @@ pub fn import_primitive_material(
         let material = match material_property.material(context.graph) {
             Some(material) => material,
             None => {
-                warn!("Material not found for property {}", i);
+                report(context, VrmImportError::MissingMaterial(i));
                 continue;
             }
         };
@@ -76,7 +86,7 @@ pub fn import_primitive_material(
                 let label = mtoon_label(i);
 
                 if !context.load_context.has_labeled_asset(label.clone()) {
-                    warn!("MToon material not found for property {}", i);
+                    report(context, VrmImportError::MissingMtoonMaterial(i));
                     continue;
                 }
 
@@ -88,8 +98,12 @@ pub fn import_primitive_material(
                     .remove::<MeshMaterial3d<StandardMaterial>>()
                     .insert((MeshMaterial3d(handle), OutlineSync));
             }
+            Some(Shader::Gltf) => {}
             Some(other) => {
-                warn!("Unsupported shader: {:?}", other);
+                report(
+                    context,
+                    VrmImportError::UnsupportedShader(format!("{:?}", other)),
+                );
             }
             None => {}
         }
@@ -98,100 +112,208 @@ pub fn import_primitive_material(
 
 fn load_mtoon_shader(
     context: &mut ImportContext,
+    index: usize,
     material_property: MaterialProperty,
 ) -> MtoonMaterial {
+    let weight = material_property.read(context.graph);
+    let mut mtoon = mtoon_properties(&weight);
+
+    if let Some(texture) = material_property.main_texture(context.graph) {
+        mtoon.base_color_texture = texture_handle(context, index, texture);
+    }
+
+    if let Some(texture) = material_property.bump_map(context.graph) {
+        mtoon.normal_map_texture = texture_handle(context, index, texture);
+    }
+
+    if let Some(texture) = material_property.emission_map(context.graph) {
+        mtoon.emissive_texture = texture_handle(context, index, texture);
+    }
+
+    if let Some(texture) = material_property.shade_texture(context.graph) {
+        mtoon.shade_multiply_texture = texture_handle(context, index, texture);
+    }
+
+    // The sphere-add texture is added on top at full strength.
+    if let Some(texture) = material_property.sphere_add(context.graph) {
+        mtoon.matcap_texture = texture_handle(context, index, texture);
+        mtoon.matcap_factor = Vec3::ONE;
+    }
+
+    if let Some(texture) = material_property.rim_texture(context.graph) {
+        mtoon.rim_multiply_texture = texture_handle(context, index, texture);
+    }
+
+    if let Some(texture) = material_property.outline_width_texture(context.graph) {
+        mtoon.outline_width_multiply_texture = texture_handle(context, index, texture);
+    }
+
+    if let Some(texture) = material_property.uv_anim_mask_texture(context.graph) {
+        mtoon.uv_animation_mask_texture = texture_handle(context, index, texture);
+    }
+
+    mtoon
+}
+
+/// Converts the non-texture properties of a UniVRM 0.x MToon material.
+fn mtoon_properties(weight: &MaterialPropertyWeight) -> MtoonMaterial {
     let mut mtoon = MtoonMaterial::default();
 
-    let weight = material_property.read(context.graph);
+    let float = &weight.float;
+    let vector = &weight.vector;
+    let keywords = &weight.keyword_map;
 
-    if let Some(value) = weight.float.double_sided {
-        mtoon.double_sided = value == 0.0;
+    // _BlendMode: 0 opaque, 1 cutout, 2 transparent, 3 transparent with z-write.
+    let blend_mode = float.blend_mode.map(|m| m as u32).or_else(|| {
+        if keywords.alpha_blend == Some(true) {
+            Some(2)
+        } else if keywords.alpha_test == Some(true) {
+            Some(1)
+        } else {
+            None
+        }
+    });
+
+    mtoon.alpha_mode = match blend_mode {
+        Some(1) => AlphaMode::Mask(float.cutoff.unwrap_or(0.5)),
+        Some(2 | 3) => AlphaMode::Blend,
+        _ => AlphaMode::Opaque,
+    };
+
+    // Transparent materials draw at Unity's queue 3000, or 2501 with z-write.
+    // Only the offset from there is kept, within the range MToon 1.0 allows.
+    if let Some(queue) = weight.render_queue {
+        mtoon.render_queue_offset_number = match blend_mode {
+            Some(2) => (queue - 3000).clamp(-9, 0),
+            Some(3) => (queue - 2501).clamp(0, 9),
+            _ => 0,
+        };
     }
 
-    if let Some(value) = weight.float.cutoff {
-        mtoon.alpha_mode = AlphaMode::Mask(value);
+    // _CullMode uses Unity's CullMode, which is 0 for off, 1 for front, and 2 for back.
+    // Front culling is rare and has no equivalent, so it culls back faces instead.
+    if let Some(value) = float.double_sided {
+        mtoon.double_sided = value == 0.0;
     }
 
-    if let Some(value) = weight.vector.color {
+    if let Some(value) = vector.color {
         mtoon.base_color = LinearRgba::from_f32_array(value).into();
     }
 
-    if let Some(texture) = material_property.main_texture(context.graph) {
-        let index = context.doc.texture_index(context.graph, texture).unwrap();
-        let label = texture_label(index);
-        let handle = context.load_context.get_label_handle(&label);
-        mtoon.base_color_texture = Some(handle);
+    if let Some(value) = float.normal_scale {
+        mtoon.normal_map_scale = value;
     }
 
-    if let Some(value) = weight.float.normal_scale {
-        mtoon.normal_map_scale = value;
+    if let Some(value) = vector.emissive_factor {
+        mtoon.emissive_factor = LinearRgba::from_f32_array(value).into();
     }
 
-    if let Some(texture) = material_property.bump_map(context.graph) {
-        let index = context.doc.texture_index(context.graph, texture).unwrap();
-        let label = texture_label(index);
-        let handle = context.load_context.get_label_handle(&label);
-        mtoon.normal_map_texture = Some(handle);
+    if let Some(value) = float.gi_intensity_factor {
+        mtoon.gi_equalization_factor = 1.0 - value;
     }
 
-    if let Some(value) = weight.vector.emissive_factor {
-        mtoon.emissive_factor = LinearRgba::from_f32_array(value).into();
+    // MToon 0.x shades over smoothstep(shift, shift + 1 - toony, n·l), while
+    // MToon 1.0 shades over linearstep(-1 + toony, 1 - toony, n·l + shift).
+    // Matching the edges of both ranges gives the 1.0 factors.
+    if float.shade_shift.is_some() || float.shade_toony.is_some() {
+        let range_min = float.shade_shift.unwrap_or(0.0);
+        let range_max = range_min + 1.0 - float.shade_toony.unwrap_or(0.9);
+
+        mtoon.shading_shift_factor = (-(range_max + range_min) * 0.5).clamp(-1.0, 1.0);
+        mtoon.shading_toony_factor = ((2.0 - (range_max - range_min)) * 0.5).clamp(0.0, 1.0);
     }
 
-    if let Some(texture) = material_property.emission_map(context.graph) {
-        let index = context.doc.texture_index(context.graph, texture).unwrap();
-        let label = texture_label(index);
-        let handle = context.load_context.get_label_handle(&label);
-        mtoon.emissive_texture = Some(handle);
+    if let Some(value) = vector.shade_color {
+        mtoon.shade_factor = LinearRgba::from_f32_array(value).into();
     }
 
-    if let Some(value) = weight.float.outline_factor {
-        mtoon.outline_width = value;
+    if let Some(value) = float.shade_receive_multiply_factor {
+        mtoon.shade_receive_multiply_factor = value;
     }
 
-    if let Some(value) = weight.vector.outline_color {
-        mtoon.outline_color = LinearRgba::from_f32_array(value).into();
+    if let Some(value) = vector.rim_factor {
+        mtoon.parametric_rim_color = LinearRgba::from_f32_array(value).into();
     }
 
-    if let Some(value) = weight.keyword_map.outline_width_world {
-        if value {
-            mtoon.outline_mode = VrmOutlineMode::World;
-        } else {
-            mtoon.outline_mode = VrmOutlineMode::Screen;
-        }
+    if let Some(value) = float.rim_lighting_mix_factor {
+        mtoon.rim_lighting_mix_factor = value;
     }
 
-    if let Some(value) = weight.float.gi_intensity_factor {
-        mtoon.gi_equalization_factor = 1.0 - value;
+    if let Some(value) = float.rim_fresnel_power_factor {
+        mtoon.parametric_rim_fresnel_power = value;
     }
 
-    if let Some(value) = weight.float.shade_shift {
-        mtoon.shading_shift_factor = -value;
+    if let Some(value) = float.rim_lift_factor {
+        mtoon.parametric_rim_lift_factor = value;
     }
 
-    if let Some(value) = weight.float.shade_toony {
-        mtoon.shading_toony_factor = value;
+    // _OutlineWidthMode: 0 none, 1 world, 2 screen.
+    mtoon.outline_mode = match float.outline_width_mode.map(|m| m as u32) {
+        Some(1) => VrmOutlineMode::World,
+        Some(2) => VrmOutlineMode::Screen,
+        Some(_) => VrmOutlineMode::None,
+        None => match keywords.outline_width_world {
+            Some(true) => VrmOutlineMode::World,
+            Some(false) => VrmOutlineMode::Screen,
+            None => VrmOutlineMode::None,
+        },
+    };
+
+    // Widths are in centimeters, or hundredths of the clip space height, which spans two units.
+    if let Some(value) = float.outline_factor {
+        mtoon.outline_width = match mtoon.outline_mode {
+            VrmOutlineMode::World => value * 0.01,
+            VrmOutlineMode::Screen => value * 0.005,
+            VrmOutlineMode::None => 0.0,
+        };
     }
 
-    if let Some(value) = weight.vector.shade_color {
-        mtoon.shade_factor = LinearRgba::from_f32_array(value).into();
+    if let Some(value) = vector.outline_color {
+        mtoon.outline_color = LinearRgba::from_f32_array(value).into();
     }
 
-    if let Some(texture) = material_property.shade_texture(context.graph) {
-        let index = context
-            .doc
-            .textures(context.graph)
-            .iter()
-            .position(|t| t.0 == texture.0)
-            .unwrap();
-        let label = texture_label(index);
-        let handle = context.load_context.get_label_handle(&label);
-        mtoon.shade_multiply_texture = Some(handle);
+    // _OutlineColorMode: 0 fixed, 1 mixed with lighting.
+    let outline_mixed = match float.outline_color_mode {
+        Some(value) => value == 1.0,
+        None => keywords.outline_color_mixed.unwrap_or_default(),
+    };
+
+    mtoon.outline_lighting_mix_factor = if outline_mixed {
+        float.outline_lighting_mix_factor.unwrap_or(1.0)
+    } else {
+        0.0
+    };
+
+    // Unity's V axis points up, mirroring vertical scrolling and rotation.
+    // Rotation is in turns per second.
+    if let Some(value) = float.uv_animation_scroll_x_speed_factor {
+        mtoon.uv_animation_scroll_x_speed_factor = value;
+    }
+
+    if let Some(value) = float.uv_animation_scroll_y_speed_factor {
+        mtoon.uv_animation_scroll_y_speed_factor = -value;
+    }
+
+    if let Some(value) = float.uv_animation_rotation_speed_factor {
+        mtoon.uv_animation_rotation_speed_factor = -value * TAU;
     }
 
     mtoon
 }
 
+fn texture_handle(
+    context: &mut ImportContext,
+    property_index: usize,
+    texture: Texture,
+) -> Option<Handle<Image>> {
+    let Some(index) = context.doc.texture_index(context.graph, texture) else {
+        report(context, VrmImportError::InvalidTextureIndex(property_index));
+        return None;
+    };
+
+    Some(context.load_context.get_label_handle(texture_label(index)))
+}
+
 fn mtoon_label(index: usize) -> String {
     format!("MaterialMtoon{}", index)
 }
@@ -199,3 +321,266 @@ fn mtoon_label(index: usize) -> String {
 fn texture_label(index: usize) -> String {
     format!("Texture{}", index)
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_vrm::vrm0::{FloatProperties, KeywordMap, VectorProperties};
+
+    use super::*;
+
+    #[test]
+    fn mtoon_conversion() {
+        // Outlines use a fixed color unless set otherwise.
+        let base = MtoonMaterial {
+            outline_lighting_mix_factor: 0.0,
+            ..default()
+        };
+
+        let cases = [
+            (
+                "cutout",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        blend_mode: Some(1.0),
+                        cutoff: Some(0.25),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    alpha_mode: AlphaMode::Mask(0.25),
+                    ..base.clone()
+                },
+            ),
+            (
+                "cutoff without blend mode",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        cutoff: Some(0.25),
+                        ..default()
+                    },
+                    ..default()
+                },
+                base.clone(),
+            ),
+            (
+                "transparent",
+                MaterialPropertyWeight {
+                    render_queue: Some(2990),
+                    float: FloatProperties {
+                        blend_mode: Some(2.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    alpha_mode: AlphaMode::Blend,
+                    render_queue_offset_number: -9,
+                    ..base.clone()
+                },
+            ),
+            (
+                "transparent with z-write",
+                MaterialPropertyWeight {
+                    render_queue: Some(2503),
+                    float: FloatProperties {
+                        blend_mode: Some(3.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    alpha_mode: AlphaMode::Blend,
+                    render_queue_offset_number: 2,
+                    ..base.clone()
+                },
+            ),
+            (
+                "opaque render queue",
+                MaterialPropertyWeight {
+                    render_queue: Some(2010),
+                    ..default()
+                },
+                base.clone(),
+            ),
+            (
+                "alpha keywords",
+                MaterialPropertyWeight {
+                    keyword_map: KeywordMap {
+                        alpha_blend: Some(true),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    alpha_mode: AlphaMode::Blend,
+                    ..base.clone()
+                },
+            ),
+            (
+                "cull off",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        double_sided: Some(0.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    double_sided: true,
+                    ..base.clone()
+                },
+            ),
+            (
+                "cull front",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        double_sided: Some(1.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                base.clone(),
+            ),
+            (
+                "cull back",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        double_sided: Some(2.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                base.clone(),
+            ),
+            (
+                "shading",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        shade_shift: Some(-0.5),
+                        shade_toony: Some(0.5),
+                        gi_intensity_factor: Some(0.25),
+                        shade_receive_multiply_factor: Some(0.5),
+                        ..default()
+                    },
+                    vector: VectorProperties {
+                        shade_color: Some([0.5, 0.25, 0.0, 1.0]),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    shading_shift_factor: 0.25,
+                    shading_toony_factor: 0.75,
+                    gi_equalization_factor: 0.75,
+                    shade_receive_multiply_factor: 0.5,
+                    shade_factor: LinearRgba::new(0.5, 0.25, 0.0, 1.0).into(),
+                    ..base.clone()
+                },
+            ),
+            (
+                "rim",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        rim_lighting_mix_factor: Some(0.5),
+                        rim_fresnel_power_factor: Some(2.0),
+                        rim_lift_factor: Some(0.25),
+                        ..default()
+                    },
+                    vector: VectorProperties {
+                        rim_factor: Some([1.0, 0.0, 0.5, 1.0]),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    parametric_rim_color: LinearRgba::new(1.0, 0.0, 0.5, 1.0).into(),
+                    rim_lighting_mix_factor: 0.5,
+                    parametric_rim_fresnel_power: 2.0,
+                    parametric_rim_lift_factor: 0.25,
+                    ..base.clone()
+                },
+            ),
+            (
+                "world outline",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        outline_width_mode: Some(1.0),
+                        outline_factor: Some(0.5),
+                        outline_color_mode: Some(1.0),
+                        outline_lighting_mix_factor: Some(0.75),
+                        ..default()
+                    },
+                    vector: VectorProperties {
+                        outline_color: Some([0.0, 1.0, 0.0, 1.0]),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    outline_mode: VrmOutlineMode::World,
+                    outline_width: 0.005,
+                    outline_color: LinearRgba::new(0.0, 1.0, 0.0, 1.0).into(),
+                    outline_lighting_mix_factor: 0.75,
+                    ..base.clone()
+                },
+            ),
+            (
+                "screen outline",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        outline_width_mode: Some(2.0),
+                        outline_factor: Some(0.5),
+                        outline_color_mode: Some(0.0),
+                        outline_lighting_mix_factor: Some(0.75),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    outline_mode: VrmOutlineMode::Screen,
+                    outline_width: 0.0025,
+                    ..base.clone()
+                },
+            ),
+            (
+                "outline keywords",
+                MaterialPropertyWeight {
+                    keyword_map: KeywordMap {
+                        outline_width_world: Some(true),
+                        outline_color_mixed: Some(true),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    outline_mode: VrmOutlineMode::World,
+                    outline_lighting_mix_factor: 1.0,
+                    ..base.clone()
+                },
+            ),
+            (
+                "uv animation",
+                MaterialPropertyWeight {
+                    float: FloatProperties {
+                        uv_animation_scroll_x_speed_factor: Some(0.5),
+                        uv_animation_scroll_y_speed_factor: Some(0.25),
+                        uv_animation_rotation_speed_factor: Some(0.5),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MtoonMaterial {
+                    uv_animation_scroll_x_speed_factor: 0.5,
+                    uv_animation_scroll_y_speed_factor: -0.25,
+                    uv_animation_rotation_speed_factor: -TAU * 0.5,
+                    ..base.clone()
+                },
+            ),
+        ];
+
+        for (name, weight, expected) in cases {
+            assert_eq!(mtoon_properties(&weight), expected, "{name}");
+        }
+    }
+}
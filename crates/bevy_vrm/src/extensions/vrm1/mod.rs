@@ -1 +1,273 @@
+use bevy::{asset::LoadedAsset, prelude::*};
+use bevy_gltf_kun::import::gltf::{document::ImportContext, texture::texture_label};
+use bevy_shader_mtoon::{MtoonMaterial, OutlineSync, VrmOutlineMode};
+use gltf_kun::graph::{
+    ByteNode, Extensions, GraphNodeWeight,
+    gltf::{Material, Primitive},
+};
+use gltf_kun_vrm::vrm1::vrmc_materials_mtoon::VrmcMaterialsMtoon as MtoonExtension;
+use serde_vrm::vrm1::vrmc_materials_mtoon::{OutlineWidthMode, TextureInfo, VrmcMaterialsMtoon};
 
+use crate::loader::{VrmImportError, report};
+
+/// Loads the `VRMC_materials_mtoon` extension of a material as an [MtoonMaterial] sub-asset,
+/// starting from the glTF material.
+pub fn import_material(
+    context: &mut ImportContext,
+    standard_material: &StandardMaterial,
+    material: Material,
+    ext: MtoonExtension,
+) {
+    let Some(index) = context.doc.material_index(context.graph, material) else {
+        return;
+    };
+
+    let label = mtoon_label(index);
+
+    // Materials are imported once per winding order.
+    if context.load_context.has_labeled_asset(label.clone()) {
+        return;
+    }
+
+    let weight = ext.read(context.graph).0;
+
+    let mut mtoon = MtoonMaterial {
+        alpha_mode: standard_material.alpha_mode,
+        base_color: standard_material.base_color,
+        base_color_texture: standard_material.base_color_texture.clone(),
+        double_sided: standard_material.double_sided,
+        emissive_factor: standard_material.emissive.into(),
+        emissive_texture: standard_material.emissive_texture.clone(),
+        normal_map_scale: material.get(context.graph).normal_scale,
+        normal_map_texture: standard_material.normal_map_texture.clone(),
+        ..mtoon_properties(&weight)
+    };
+
+    let mut texture = |info: &Option<TextureInfo>| {
+        info.as_ref()
+            .and_then(|info| texture_handle(context, index, info.index))
+    };
+
+    mtoon.shade_multiply_texture = texture(&weight.shade_multiply_texture);
+    mtoon.matcap_texture = texture(&weight.matcap_texture);
+    mtoon.rim_multiply_texture = texture(&weight.rim_multiply_texture);
+    mtoon.outline_width_multiply_texture = texture(&weight.outline_width_multiply_texture);
+    mtoon.uv_animation_mask_texture = texture(&weight.uv_animation_mask_texture);
+
+    if let Some(info) = &weight.shading_shift_texture {
+        mtoon.shade_shift_texture = texture_handle(context, index, info.index);
+    }
+
+    context
+        .load_context
+        .add_loaded_labeled_asset(label, LoadedAsset::new_with_dependencies(mtoon));
+}
+
+/// Replaces the glTF material of a primitive with its [MtoonMaterial], if it has one.
+pub fn import_primitive_material(
+    context: &mut ImportContext,
+    entity: &mut EntityWorldMut,
+    primitive: Primitive,
+) {
+    let Some(material) = primitive.material(context.graph) else {
+        return;
+    };
+
+    if material
+        .get_extension::<MtoonExtension>(context.graph)
+        .is_none()
+    {
+        return;
+    }
+
+    let Some(index) = context.doc.material_index(context.graph, material) else {
+        return;
+    };
+
+    let label = mtoon_label(index);
+
+    if !context.load_context.has_labeled_asset(label.clone()) {
+        report(context, VrmImportError::MissingMtoonMaterial(index));
+        return;
+    }
+
+    let handle = context
+        .load_context
+        .get_label_handle::<MtoonMaterial>(&label);
+
+    entity
+        .remove::<MeshMaterial3d<StandardMaterial>>()
+        .insert((MeshMaterial3d(handle), OutlineSync));
+}
+
+fn texture_handle(
+    context: &mut ImportContext,
+    material_index: usize,
+    texture_index: u32,
+) -> Option<Handle<Image>> {
+    let texture_index = texture_index as usize;
+
+    if texture_index >= context.doc.textures(context.graph).len() {
+        report(context, VrmImportError::InvalidTextureIndex(material_index));
+        return None;
+    }
+
+    Some(
+        context
+            .load_context
+            .get_label_handle(texture_label(texture_index)),
+    )
+}
+
+fn mtoon_label(index: usize) -> String {
+    format!("MaterialMtoon{}", index)
+}
+
+/// Converts the non-texture properties of a `VRMC_materials_mtoon` extension.
+/// Properties of the glTF material itself, such as the base color, are left at their defaults.
+pub fn mtoon_properties(ext: &VrmcMaterialsMtoon) -> MtoonMaterial {
+    let mut mtoon = MtoonMaterial::default();
+
+    if let Some(value) = ext.render_queue_offset_number {
+        mtoon.render_queue_offset_number = value;
+    }
+
+    if let Some([r, g, b]) = ext.shade_color_factor {
+        mtoon.shade_factor = LinearRgba::rgb(r, g, b).into();
+    }
+
+    if let Some(value) = ext.shading_shift_factor {
+        mtoon.shading_shift_factor = value;
+    }
+
+    if let Some(value) = ext.shading_toony_factor {
+        mtoon.shading_toony_factor = value;
+    }
+
+    if let Some(value) = ext.gi_equalization_factor {
+        mtoon.gi_equalization_factor = value;
+    }
+
+    // The spec defaults to a white matcap factor.
+    mtoon.matcap_factor = ext.matcap_factor.map(Vec3::from).unwrap_or(Vec3::ONE);
+
+    if let Some([r, g, b]) = ext.parametric_rim_color_factor {
+        mtoon.parametric_rim_color = LinearRgba::rgb(r, g, b).into();
+    }
+
+    if let Some(value) = ext.rim_lighting_mix_factor {
+        mtoon.rim_lighting_mix_factor = value;
+    }
+
+    if let Some(value) = ext.parametric_rim_fresnel_power_factor {
+        mtoon.parametric_rim_fresnel_power = value;
+    }
+
+    if let Some(value) = ext.parametric_rim_lift_factor {
+        mtoon.parametric_rim_lift_factor = value;
+    }
+
+    mtoon.outline_mode = match ext.outline_width_mode.unwrap_or_default() {
+        OutlineWidthMode::None => VrmOutlineMode::None,
+        OutlineWidthMode::WorldCoordinates => VrmOutlineMode::World,
+        OutlineWidthMode::ScreenCoordinates => VrmOutlineMode::Screen,
+    };
+
+    if let Some(value) = ext.outline_width_factor {
+        mtoon.outline_width = value;
+    }
+
+    if let Some([r, g, b]) = ext.outline_color_factor {
+        mtoon.outline_color = LinearRgba::rgb(r, g, b).into();
+    }
+
+    if let Some(value) = ext.outline_lighting_mix_factor {
+        mtoon.outline_lighting_mix_factor = value;
+    }
+
+    if let Some(value) = ext.uv_animation_scroll_x_speed_factor {
+        mtoon.uv_animation_scroll_x_speed_factor = value;
+    }
+
+    if let Some(value) = ext.uv_animation_scroll_y_speed_factor {
+        mtoon.uv_animation_scroll_y_speed_factor = value;
+    }
+
+    if let Some(value) = ext.uv_animation_rotation_speed_factor {
+        mtoon.uv_animation_rotation_speed_factor = value;
+    }
+
+    mtoon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mtoon_conversion() {
+        let base = MtoonMaterial {
+            matcap_factor: Vec3::ONE,
+            ..default()
+        };
+
+        let cases = [
+            ("defaults", VrmcMaterialsMtoon::default(), base.clone()),
+            (
+                "shading",
+                VrmcMaterialsMtoon {
+                    shade_color_factor: Some([0.5, 0.25, 0.0]),
+                    shading_shift_factor: Some(-0.25),
+                    shading_toony_factor: Some(0.75),
+                    gi_equalization_factor: Some(0.5),
+                    matcap_factor: Some([0.5, 0.5, 0.5]),
+                    ..default()
+                },
+                MtoonMaterial {
+                    shade_factor: LinearRgba::rgb(0.5, 0.25, 0.0).into(),
+                    shading_shift_factor: -0.25,
+                    shading_toony_factor: 0.75,
+                    gi_equalization_factor: 0.5,
+                    matcap_factor: Vec3::splat(0.5),
+                    ..base.clone()
+                },
+            ),
+            (
+                "outline",
+                VrmcMaterialsMtoon {
+                    outline_width_mode: Some(OutlineWidthMode::ScreenCoordinates),
+                    outline_width_factor: Some(0.01),
+                    outline_color_factor: Some([1.0, 0.0, 0.0]),
+                    outline_lighting_mix_factor: Some(0.5),
+                    ..default()
+                },
+                MtoonMaterial {
+                    outline_mode: VrmOutlineMode::Screen,
+                    outline_width: 0.01,
+                    outline_color: LinearRgba::rgb(1.0, 0.0, 0.0).into(),
+                    outline_lighting_mix_factor: 0.5,
+                    ..base.clone()
+                },
+            ),
+            (
+                "uv animation",
+                VrmcMaterialsMtoon {
+                    uv_animation_scroll_x_speed_factor: Some(0.5),
+                    uv_animation_scroll_y_speed_factor: Some(-0.25),
+                    uv_animation_rotation_speed_factor: Some(1.5),
+                    ..default()
+                },
+                MtoonMaterial {
+                    uv_animation_scroll_x_speed_factor: 0.5,
+                    uv_animation_scroll_y_speed_factor: -0.25,
+                    uv_animation_rotation_speed_factor: 1.5,
+                    ..base.clone()
+                },
+            ),
+        ];
+
+        for (name, ext, expected) in cases {
+            assert_eq!(mtoon_properties(&ext), expected, "{name}");
+        }
+    }
+}
@@ -0,0 +1,553 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{morph::MeshMorphWeights, skinning::SkinnedMesh},
+        view::RenderLayers,
+    },
+};
+use bevy::{
+    scene::SceneInstanceReady,
+    tasks::{AsyncComputeTaskPool, Task, block_on, poll_once},
+};
+use bevy_shader_mtoon::MtoonMaterial;
+use serde_vrm::vrm0::BoneName;
+
+mod split;
+
+pub use serde_vrm::vrm0::FirstPersonFlag;
+pub use split::{FirstPersonMesh, split_first_person_mesh};
+
+/// Default first-person render layer.
+pub const FIRST_PERSON_LAYER: usize = 7;
+/// Default third-person render layer.
+pub const THIRD_PERSON_LAYER: usize = 8;
+
+/// Render layers an avatar's meshes are split into by [SetupFirstPerson].
+/// Avatars without this component use [FIRST_PERSON_LAYER] and [THIRD_PERSON_LAYER].
+///
+/// Avatars that are never set up, such as remote players, stay on the default
+/// layer and render in every camera.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FirstPersonLayers {
+    pub first_person: usize,
+    pub third_person: usize,
+}
+
+impl Default for FirstPersonLayers {
+    fn default() -> Self {
+        Self {
+            first_person: FIRST_PERSON_LAYER,
+            third_person: THIRD_PERSON_LAYER,
+        }
+    }
+}
+
+impl FirstPersonLayers {
+    /// Layers for a mesh with the given flag.
+    pub fn render_layers(&self, flag: FirstPersonFlag) -> RenderLayers {
+        match flag {
+            FirstPersonFlag::Auto | FirstPersonFlag::Both => {
+                RenderLayers::from_layers(&[0, self.first_person, self.third_person])
+            }
+            FirstPersonFlag::FirstPersonOnly => RenderLayers::layer(self.first_person),
+            FirstPersonFlag::ThirdPersonOnly => RenderLayers::layer(self.third_person),
+        }
+    }
+
+    /// Layers for a camera viewing avatars from the given perspective.
+    pub fn camera_layers(&self, flag: FirstPersonFlag) -> RenderLayers {
+        RenderLayers::layer(0).union(&self.render_layers(flag))
+    }
+}
+
+#[derive(Event)]
+pub struct SetupFirstPerson(pub Entity);
+
+/// Sent once an avatar's meshes have been set up for first-person rendering.
+#[derive(Event)]
+pub struct VrmFirstPersonReady(pub Entity);
+
+/// Automatically sends [SetupFirstPerson] for this avatar once its scene is spawned.
+#[derive(Component, Default)]
+pub struct AutoFirstPerson;
+
+pub(crate) fn auto_setup_first_person(
+    trigger: Trigger<SceneInstanceReady>,
+    avatars: Query<(), With<AutoFirstPerson>>,
+    mut writer: EventWriter<SetupFirstPerson>,
+) {
+    if avatars.contains(trigger.target()) {
+        writer.write(SetupFirstPerson(trigger.target()));
+    }
+}
+
+/// First-person viewpoint of an avatar, from the VRM first-person settings.
+/// Inserted on the scene root when the VRM is imported.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct VrmFirstPerson {
+    pub bone: BoneName,
+    /// Offset from the bone to the viewpoint, in the bone's space.
+    pub offset: Vec3,
+}
+
+impl Default for VrmFirstPerson {
+    fn default() -> Self {
+        Self {
+            bone: BoneName::Head,
+            offset: Vec3::ZERO,
+        }
+    }
+}
+
+/// Places this camera at an avatar's first-person viewpoint each frame,
+/// rendering the first-person layers.
+///
+/// The camera is expected to have no parent.
+#[derive(Component, Clone, Debug)]
+pub struct VrmFirstPersonCamera {
+    /// Entity holding the avatar's [SceneRoot].
+    pub avatar: Entity,
+    /// Follow the rotation of the first-person bone,
+    /// or only its position, such as when the camera is driven by a VR headset.
+    pub follow_rotation: bool,
+}
+
+impl VrmFirstPersonCamera {
+    pub fn new(avatar: Entity) -> Self {
+        Self {
+            avatar,
+            follow_rotation: true,
+        }
+    }
+}
+
+pub(crate) fn set_first_person_camera_layers(
+    mut commands: Commands,
+    cameras: Query<(Entity, &VrmFirstPersonCamera), Added<VrmFirstPersonCamera>>,
+    layers: Query<&FirstPersonLayers>,
+) {
+    for (entity, camera) in cameras.iter() {
+        let layers = layers.get(camera.avatar).copied().unwrap_or_default();
+
+        commands
+            .entity(entity)
+            .insert(layers.camera_layers(FirstPersonFlag::FirstPersonOnly));
+    }
+}
+
+pub(crate) fn follow_first_person_bone(
+    bones: Query<(&BoneName, &GlobalTransform), Without<VrmFirstPersonCamera>>,
+    children: Query<&Children>,
+    first_persons: Query<&VrmFirstPerson>,
+    mut cameras: Query<(&VrmFirstPersonCamera, &mut Transform, &mut GlobalTransform)>,
+) {
+    for (camera, mut transform, mut global) in cameras.iter_mut() {
+        let first_person = children
+            .iter_descendants(camera.avatar)
+            .find_map(|child| first_persons.get(child).ok())
+            .cloned()
+            .unwrap_or_default();
+
+        let Some((_, bone_global)) = children
+            .iter_descendants(camera.avatar)
+            .filter_map(|child| bones.get(child).ok())
+            .find(|(bone, _)| **bone == first_person.bone)
+        else {
+            continue;
+        };
+
+        transform.translation = bone_global.transform_point(first_person.offset);
+
+        if camera.follow_rotation {
+            transform.rotation = bone_global.rotation();
+        }
+
+        // Runs after transform propagation, so update the global transform directly.
+        *global = GlobalTransform::from(*transform);
+    }
+}
+
+/// Carries the camera's new global transform down to its descendants,
+/// which transform propagation already updated from the camera's old one.
+pub(crate) fn propagate_first_person_camera(
+    cameras: Query<(Entity, &GlobalTransform), With<VrmFirstPersonCamera>>,
+    children: Query<&Children>,
+    mut transforms: Query<(&Transform, &mut GlobalTransform), Without<VrmFirstPersonCamera>>,
+) {
+    for (camera, global) in cameras.iter() {
+        let mut stack = vec![(camera, *global)];
+
+        while let Some((parent, parent_global)) = stack.pop() {
+            let Ok(parent_children) = children.get(parent) else {
+                continue;
+            };
+
+            for child in parent_children.iter() {
+                let Ok((transform, mut child_global)) = transforms.get_mut(child) else {
+                    continue;
+                };
+
+                *child_global = parent_global.mul_transform(*transform);
+                stack.push((child, *child_global));
+            }
+        }
+    }
+}
+
+/// Options for splitting an avatar's meshes in [SetupFirstPerson].
+#[derive(Component, Clone, Debug, Default)]
+pub struct FirstPersonSettings {
+    /// Split meshes on the [AsyncComputeTaskPool], instead of blocking the frame.
+    pub async_compute: bool,
+    /// Vertices with a combined head weight above this are hidden in first-person.
+    pub weight_threshold: f32,
+    /// Fill the holes left in first-person meshes, such as the neck opening.
+    pub cap_holes: bool,
+}
+
+/// First-person variant of a mesh, being built.
+#[derive(Component)]
+pub(crate) struct FirstPersonSplit {
+    avatar: Entity,
+    layers: FirstPersonLayers,
+    state: SplitState,
+}
+
+enum SplitState {
+    Ready(Box<Option<FirstPersonMesh>>),
+    Running(Task<Option<FirstPersonMesh>>),
+    /// Uses the result of another entity's split, which shares the same mesh.
+    Shared(Entity),
+}
+
+/// First-person variant of a third-person mesh, created by [SetupFirstPerson].
+/// As a sibling of the third-person mesh, it inherits the same morph weights.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct FirstPersonVariant {
+    pub third_person: Entity,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handle_setup_events(
+    bones: Query<(Entity, &BoneName)>,
+    children: Query<&Children>,
+    mut commands: Commands,
+    mut events: EventReader<SetupFirstPerson>,
+    flags: Query<(&FirstPersonFlag, &Mesh3d, Option<&SkinnedMesh>)>,
+    images: Res<Assets<Image>>,
+    meshes: Res<Assets<Mesh>>,
+    parents: Query<&ChildOf>,
+    mut ready: EventWriter<VrmFirstPersonReady>,
+    settings: Query<(Option<&FirstPersonLayers>, Option<&FirstPersonSettings>)>,
+) {
+    if bones.is_empty() {
+        return;
+    }
+
+    for event in events.read() {
+        let Some((head_ent, _)) = bones
+            .iter()
+            .find(|(e, name)| **name == BoneName::Head && is_child(*e, event.0, &parents))
+        else {
+            warn!("Head bone not found for {}", event.0);
+            continue;
+        };
+
+        let (layers, settings) = settings.get(event.0).unwrap_or_default();
+        let layers = layers.copied().unwrap_or_default();
+        let settings = settings.cloned().unwrap_or_default();
+
+        let head_joints = std::iter::once(head_ent)
+            .chain(children.iter_descendants(head_ent))
+            .collect::<HashSet<_>>();
+
+        let mut splitting = false;
+
+        // Primitives sharing a mesh and skin are split once.
+        let mut shared = HashMap::<(AssetId<Mesh>, Vec<bool>), Entity>::default();
+
+        // Only set up this avatar's meshes, other instances of the model are handled separately.
+        for ent in children.iter_descendants(event.0) {
+            let Ok((flag, mesh_handle, skin)) = flags.get(ent) else {
+                continue;
+            };
+
+            // If auto, split the mesh into first-person and third-person variants.
+            if *flag == FirstPersonFlag::Auto
+                && let (Some(mesh), Some(skin)) = (meshes.get(mesh_handle), skin)
+            {
+                let head_mask = skin
+                    .joints
+                    .iter()
+                    .map(|joint| head_joints.contains(joint))
+                    .collect::<Vec<_>>();
+
+                splitting = true;
+
+                if let Some(owner) = shared.get(&(mesh_handle.id(), head_mask.clone())) {
+                    commands.entity(ent).insert(FirstPersonSplit {
+                        avatar: event.0,
+                        layers,
+                        state: SplitState::Shared(*owner),
+                    });
+                    continue;
+                }
+                shared.insert((mesh_handle.id(), head_mask.clone()), ent);
+
+                let morph_targets = mesh
+                    .morph_targets()
+                    .and_then(|handle| images.get(handle))
+                    .cloned();
+                let mesh = mesh.clone();
+
+                let state = if settings.async_compute {
+                    let settings = settings.clone();
+                    SplitState::Running(AsyncComputeTaskPool::get().spawn(async move {
+                        split_first_person_mesh(mesh, morph_targets.as_ref(), &head_mask, &settings)
+                    }))
+                } else {
+                    SplitState::Ready(Box::new(split_first_person_mesh(
+                        mesh,
+                        morph_targets.as_ref(),
+                        &head_mask,
+                        &settings,
+                    )))
+                };
+
+                commands.entity(ent).insert(FirstPersonSplit {
+                    avatar: event.0,
+                    layers,
+                    state,
+                });
+                continue;
+            }
+
+            commands.entity(ent).insert(layers.render_layers(*flag));
+        }
+
+        if !splitting {
+            ready.write(VrmFirstPersonReady(event.0));
+        }
+    }
+}
+
+pub(crate) fn finish_first_person_splits(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    parents: Query<&ChildOf>,
+    mut ready: EventWriter<VrmFirstPersonReady>,
+    mut splits: Query<(Entity, &mut FirstPersonSplit)>,
+    mut sources: Query<(
+        &mut FirstPersonFlag,
+        &Transform,
+        &SkinnedMesh,
+        Option<&Name>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&MeshMaterial3d<MtoonMaterial>>,
+        Option<&MeshMorphWeights>,
+    )>,
+) {
+    let mut finished = HashSet::new();
+    let mut pending = HashSet::new();
+
+    // Resolve each split before the entities sharing it.
+    let mut results = HashMap::<Entity, Option<Handle<Mesh>>>::default();
+    let mut shared = Vec::new();
+
+    for (ent, mut split) in splits.iter_mut() {
+        let result = match &mut split.state {
+            SplitState::Ready(result) => result.take(),
+            SplitState::Running(task) => match block_on(poll_once(task)) {
+                Some(result) => result,
+                None => {
+                    pending.insert(split.avatar);
+                    continue;
+                }
+            },
+            SplitState::Shared(owner) => {
+                shared.push((ent, *owner, split.avatar, split.layers));
+                continue;
+            }
+        };
+
+        let handle = result.map(
+            |FirstPersonMesh {
+                 mut mesh,
+                 morph_targets,
+             }| {
+                if let Some(image) = morph_targets {
+                    mesh.set_morph_targets(images.add(image));
+                }
+                meshes.add(mesh)
+            },
+        );
+
+        results.insert(ent, handle.clone());
+        finish_split(
+            &mut commands,
+            ent,
+            handle,
+            split.layers,
+            &parents,
+            &mut sources,
+        );
+        finished.insert(split.avatar);
+    }
+
+    for (ent, owner, avatar, layers) in shared {
+        let Some(handle) = results.get(&owner) else {
+            pending.insert(avatar);
+            continue;
+        };
+
+        finish_split(
+            &mut commands,
+            ent,
+            handle.clone(),
+            layers,
+            &parents,
+            &mut sources,
+        );
+        finished.insert(avatar);
+    }
+
+    for avatar in finished.difference(&pending) {
+        ready.write(VrmFirstPersonReady(*avatar));
+    }
+}
+
+/// Spawns the first-person variant of `ent` as a sibling, so it inherits
+/// the same morph weights and transform parent.
+#[allow(clippy::type_complexity)]
+fn finish_split(
+    commands: &mut Commands,
+    ent: Entity,
+    mesh: Option<Handle<Mesh>>,
+    layers: FirstPersonLayers,
+    parents: &Query<&ChildOf>,
+    sources: &mut Query<(
+        &mut FirstPersonFlag,
+        &Transform,
+        &SkinnedMesh,
+        Option<&Name>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&MeshMaterial3d<MtoonMaterial>>,
+        Option<&MeshMorphWeights>,
+    )>,
+) {
+    commands.entity(ent).remove::<FirstPersonSplit>();
+
+    let Ok((mut flag, transform, skin, name, standard_material, mtoon_material, morph_weights)) =
+        sources.get_mut(ent)
+    else {
+        return;
+    };
+
+    let Some(mesh) = mesh else {
+        commands.entity(ent).insert(layers.render_layers(*flag));
+        return;
+    };
+
+    let new_ent = commands
+        .spawn((
+            *transform,
+            Mesh3d(mesh),
+            skin.clone(),
+            layers.render_layers(FirstPersonFlag::FirstPersonOnly),
+            FirstPersonFlag::FirstPersonOnly,
+            FirstPersonVariant { third_person: ent },
+        ))
+        .id();
+
+    if let Some(v) = mtoon_material {
+        commands.entity(new_ent).insert(v.clone());
+    }
+
+    if let Some(v) = standard_material {
+        commands.entity(new_ent).insert(v.clone());
+    }
+
+    if let Some(v) = name {
+        commands.entity(new_ent).insert(v.clone());
+    }
+
+    if let Some(v) = morph_weights {
+        commands.entity(new_ent).insert(v.clone());
+    }
+
+    match parents.get(ent) {
+        Ok(parent) => {
+            commands.entity(parent.parent()).add_child(new_ent);
+        }
+        Err(_) => {
+            commands.entity(ent).add_child(new_ent);
+        }
+    }
+
+    *flag = FirstPersonFlag::ThirdPersonOnly;
+    commands.entity(ent).insert(layers.render_layers(*flag));
+}
+
+/// Walks up the parent tree, searching for a specific Entity.
+fn is_child(target_child: Entity, target_parent: Entity, parents: &Query<&ChildOf>) -> bool {
+    if target_child == target_parent {
+        true
+    } else if let Ok(child_of) = parents.get(target_child) {
+        is_child(child_of.parent(), target_parent, parents)
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn camera_children_follow_bone() {
+        let mut world = World::new();
+
+        let head = Vec3::new(0.0, 1.5, 0.0);
+        let bone = world
+            .spawn((
+                BoneName::Head,
+                Transform::from_translation(head),
+                GlobalTransform::from_translation(head),
+            ))
+            .id();
+        let avatar = world
+            .spawn((Transform::default(), GlobalTransform::default()))
+            .add_child(bone)
+            .id();
+
+        // Propagated from the camera's old transform at the origin.
+        let offset = Vec3::new(0.0, 0.0, -1.0);
+        let child = world
+            .spawn((
+                Transform::from_translation(offset),
+                GlobalTransform::from_translation(offset),
+            ))
+            .id();
+        world
+            .spawn((
+                VrmFirstPersonCamera::new(avatar),
+                Transform::default(),
+                GlobalTransform::default(),
+            ))
+            .add_child(child);
+
+        world.run_system_once(follow_first_person_bone).unwrap();
+        world
+            .run_system_once(propagate_first_person_camera)
+            .unwrap();
+
+        let global = world.get::<GlobalTransform>(child).unwrap();
+        assert_eq!(global.translation(), head + offset);
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    prelude::*,
+    render::mesh::{
+        Indices, VertexAttributeValues,
+        morph::{MorphAttributes, MorphTargetImage},
+    },
+};
+
+use super::FirstPersonSettings;
+
+/// First-person variant of a mesh.
+pub struct FirstPersonMesh {
+    pub mesh: Mesh,
+    /// Compacted morph targets, to replace those of the original mesh.
+    /// `None` if the original morph targets are kept.
+    pub morph_targets: Option<Image>,
+}
+
+/// Builds the first-person variant of a skinned mesh, removing each triangle
+/// weighted to the head and dropping vertices that are no longer used.
+///
+/// `head_joints` is indexed by skin joint, and is `true` for the head and its descendants.
+/// `morph_targets` is the mesh's morph target image, compacted along with the vertices.
+/// If the mesh has morph targets but the image or its data is not available,
+/// vertices are kept so the original morph targets still line up.
+///
+/// Returns `None` if the mesh has no joint data, or indices out of range.
+pub fn split_first_person_mesh(
+    mut mesh: Mesh,
+    morph_targets: Option<&Image>,
+    head_joints: &[bool],
+    settings: &FirstPersonSettings,
+) -> Option<FirstPersonMesh> {
+    let Some(VertexAttributeValues::Uint16x4(joints)) = mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX)
+    else {
+        return None;
+    };
+
+    let Some(VertexAttributeValues::Float32x4(weights)) =
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT)
+    else {
+        return None;
+    };
+
+    let removed = joints
+        .iter()
+        .zip(weights)
+        .map(|(joints, weights)| {
+            let head_weight = joints
+                .iter()
+                .zip(weights)
+                .filter(|(joint, _)| {
+                    head_joints
+                        .get(**joint as usize)
+                        .copied()
+                        .unwrap_or_default()
+                })
+                .map(|(_, weight)| *weight)
+                .sum::<f32>();
+
+            head_weight > settings.weight_threshold
+        })
+        .collect::<Vec<_>>();
+
+    let Some(original) = mesh.indices() else {
+        return Some(FirstPersonMesh {
+            mesh,
+            morph_targets: None,
+        });
+    };
+
+    let vertex_count = mesh.count_vertices();
+
+    let is_u16 = matches!(original, Indices::U16(_));
+    let triangles = original
+        .iter()
+        .map(|i| i as u32)
+        .collect::<Vec<_>>()
+        .chunks_exact(3)
+        .map(|t| [t[0], t[1], t[2]])
+        .collect::<Vec<_>>();
+
+    if triangles
+        .iter()
+        .flatten()
+        .any(|i| *i as usize >= vertex_count.min(removed.len()))
+    {
+        return None;
+    }
+
+    let (mut kept, removed_triangles): (Vec<_>, Vec<_>) = triangles
+        .into_iter()
+        .partition(|t| !t.iter().any(|i| removed[*i as usize]));
+
+    if settings.cap_holes {
+        kept.extend(cap_triangles(&kept, &removed_triangles));
+    }
+
+    let mut used = vec![false; vertex_count];
+    for i in kept.iter().flatten() {
+        used[*i as usize] = true;
+    }
+    let keep = (0..vertex_count).filter(|i| used[*i]).collect::<Vec<_>>();
+
+    let compacted = match morph_targets {
+        Some(image) => compact_morph_targets(image, vertex_count, &keep).map(Some),
+        None if mesh.morph_targets().is_some() => None,
+        None => Some(None),
+    };
+
+    let Some(new_morph_targets) = compacted else {
+        // Keep every vertex so the original morph targets still apply.
+        set_indices(&mut mesh, kept.into_iter().flatten(), is_u16);
+        return Some(FirstPersonMesh {
+            mesh,
+            morph_targets: None,
+        });
+    };
+
+    let mut remap = vec![0; vertex_count];
+    for (new, old) in keep.iter().enumerate() {
+        remap[*old] = new as u32;
+    }
+
+    for (_, values) in mesh.attributes_mut() {
+        *values = select_vertices(values, &keep);
+    }
+
+    set_indices(
+        &mut mesh,
+        kept.into_iter().flatten().map(|i| remap[i as usize]),
+        is_u16,
+    );
+
+    Some(FirstPersonMesh {
+        mesh,
+        morph_targets: new_morph_targets,
+    })
+}
+
+fn set_indices(mesh: &mut Mesh, indices: impl Iterator<Item = u32>, is_u16: bool) {
+    let indices = if is_u16 {
+        Indices::U16(indices.map(|i| i as u16).collect())
+    } else {
+        Indices::U32(indices.collect())
+    };
+
+    mesh.insert_indices(indices);
+}
+
+/// Fills the holes left by removed triangles, fanning across each boundary loop.
+fn cap_triangles(kept: &[[u32; 3]], removed: &[[u32; 3]]) -> Vec<[u32; 3]> {
+    let edges = |t: &[u32; 3]| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])];
+
+    let removed_edges = removed.iter().flat_map(edges).collect::<HashSet<_>>();
+
+    // Boundary edges, wound like the removed triangles they border.
+    let next = kept
+        .iter()
+        .flat_map(edges)
+        .filter(|(a, b)| removed_edges.contains(&(*b, *a)))
+        .map(|(a, b)| (b, a))
+        .collect::<HashMap<_, _>>();
+
+    let mut visited = HashSet::new();
+    let mut caps = Vec::new();
+
+    for start in next.keys() {
+        if visited.contains(start) {
+            continue;
+        }
+
+        let mut boundary = vec![*start];
+        visited.insert(*start);
+
+        let mut closed = false;
+        let mut current = *start;
+        while let Some(&vertex) = next.get(&current) {
+            if vertex == *start {
+                closed = true;
+                break;
+            }
+            if !visited.insert(vertex) {
+                break;
+            }
+            boundary.push(vertex);
+            current = vertex;
+        }
+
+        // Open boundaries run along the edge of the mesh, and have no hole to fill.
+        if !closed {
+            continue;
+        }
+
+        for pair in boundary[1..].windows(2) {
+            caps.push([boundary[0], pair[0], pair[1]]);
+        }
+    }
+
+    caps
+}
+
+fn select_vertices(values: &VertexAttributeValues, keep: &[usize]) -> VertexAttributeValues {
+    macro_rules! select {
+        ($($variant:ident),*) => {
+            match values {
+                $(VertexAttributeValues::$variant(v) => {
+                    VertexAttributeValues::$variant(keep.iter().map(|i| v[*i]).collect())
+                })*
+            }
+        };
+    }
+
+    select!(
+        Float32, Sint32, Uint32, Float32x2, Sint32x2, Uint32x2, Float32x3, Sint32x3, Uint32x3,
+        Float32x4, Sint32x4, Uint32x4, Sint16x2, Snorm16x2, Uint16x2, Unorm16x2, Sint16x4,
+        Snorm16x4, Uint16x4, Unorm16x4, Sint8x2, Snorm8x2, Uint8x2, Unorm8x2, Sint8x4, Snorm8x4,
+        Uint8x4, Unorm8x4
+    )
+}
+
+/// Rebuilds a morph target image with only the kept vertices.
+/// Returns `None` if the image data is not available in the main world.
+fn compact_morph_targets(image: &Image, vertex_count: usize, keep: &[usize]) -> Option<Image> {
+    const COMPONENTS: usize = size_of::<MorphAttributes>() / size_of::<f32>();
+
+    let data = image.data.as_ref()?;
+    let size = image.texture_descriptor.size;
+
+    let floats = data
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect::<Vec<_>>();
+
+    // Each target is a layer, padded to fill the texture.
+    let layer_len = (size.width * size.height) as usize;
+    let target_count = size.depth_or_array_layers as usize;
+
+    if layer_len < vertex_count * COMPONENTS || floats.len() < layer_len * target_count {
+        return None;
+    }
+
+    let targets = (0..target_count).map(|target| {
+        let floats = &floats;
+        keep.iter().map(move |vertex| {
+            let start = target * layer_len + vertex * COMPONENTS;
+            let v = &floats[start..start + COMPONENTS];
+            MorphAttributes {
+                position: Vec3::new(v[0], v[1], v[2]),
+                normal: Vec3::new(v[3], v[4], v[5]),
+                tangent: Vec3::new(v[6], v[7], v[8]),
+            }
+        })
+    });
+
+    MorphTargetImage::new(targets, keep.len(), image.asset_usage)
+        .ok()
+        .map(|image| image.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::RenderAssetUsages, render::mesh::PrimitiveTopology};
+
+    use super::*;
+
+    /// A quad of two triangles, with the last vertex weighted to joint 1.
+    fn quad(head_weight: f32) -> Mesh {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 1.0, 0.0],
+            ],
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(vec![
+                [0, 0, 0, 0],
+                [0, 0, 0, 0],
+                [0, 0, 0, 0],
+                [0, 1, 0, 0],
+            ]),
+        );
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_WEIGHT,
+            vec![
+                [1.0, 0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0, 0.0],
+                [1.0 - head_weight, head_weight, 0.0, 0.0],
+            ],
+        );
+        mesh.insert_indices(Indices::U16(vec![0, 1, 2, 2, 1, 3]));
+        mesh
+    }
+
+    #[test]
+    fn split_mesh() {
+        let split = split_first_person_mesh(quad(1.0), None, &[false, true], &default()).unwrap();
+
+        let Some(Indices::U16(indices)) = split.mesh.indices() else {
+            panic!("Missing indices");
+        };
+        assert_eq!(indices, &[0, 1, 2]);
+        assert_eq!(split.mesh.count_vertices(), 3);
+    }
+
+    #[test]
+    fn weight_threshold() {
+        let settings = FirstPersonSettings {
+            weight_threshold: 0.5,
+            ..default()
+        };
+
+        let split = split_first_person_mesh(quad(0.25), None, &[false, true], &settings).unwrap();
+        assert_eq!(split.mesh.indices().unwrap().len(), 6);
+
+        let split = split_first_person_mesh(quad(0.75), None, &[false, true], &settings).unwrap();
+        assert_eq!(split.mesh.indices().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn compact_morph_targets() {
+        let image = MorphTargetImage::new(
+            [(0..4).map(|i| MorphAttributes {
+                position: Vec3::splat(i as f32),
+                ..default()
+            })]
+            .into_iter(),
+            4,
+            RenderAssetUsages::default(),
+        )
+        .unwrap()
+        .0;
+
+        let split =
+            split_first_person_mesh(quad(1.0), Some(&image), &[false, true], &default()).unwrap();
+
+        let data = split.morph_targets.unwrap().data.unwrap();
+        let position_y = |vertex: usize| {
+            let start = (vertex * 9 + 1) * 4;
+            f32::from_le_bytes(data[start..start + 4].try_into().unwrap())
+        };
+
+        assert_eq!(position_y(0), 0.0);
+        assert_eq!(position_y(2), 2.0);
+    }
+
+    #[test]
+    fn unreadable_morph_targets() {
+        let mut mesh = quad(1.0);
+        mesh.set_morph_targets(Handle::default());
+
+        let split = split_first_person_mesh(mesh, None, &[false, true], &default()).unwrap();
+
+        let Some(Indices::U16(indices)) = split.mesh.indices() else {
+            panic!("Missing indices");
+        };
+        assert_eq!(indices, &[0, 1, 2]);
+        assert_eq!(split.mesh.count_vertices(), 4);
+        assert!(split.morph_targets.is_none());
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let mut mesh = quad(1.0);
+        mesh.insert_indices(Indices::U16(vec![0, 1, 2, 2, 1, 9]));
+
+        assert!(split_first_person_mesh(mesh, None, &[false, true], &default()).is_none());
+    }
+
+    #[test]
+    fn cap_hole() {
+        // A square pyramid with its sides removed, leaving the base.
+        let kept = [[0, 2, 1], [0, 3, 2]];
+        let removed = [[0, 1, 4], [1, 2, 4], [2, 3, 4], [3, 0, 4]];
+
+        let caps = cap_triangles(&kept, &removed);
+        assert_eq!(caps.len(), 2);
+        assert!(caps.iter().flatten().all(|i| *i < 4));
+    }
+}
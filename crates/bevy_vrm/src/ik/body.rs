@@ -0,0 +1,482 @@
+//! Full-body solver for VR tracking, driving an avatar from a head and hands,
+//! and optionally hips and feet.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde_vrm::vrm0::BoneName;
+
+use super::{Limb, find_bones, solve_limb, world_transform};
+
+const SPINE: [BoneName; 4] = [
+    BoneName::Spine,
+    BoneName::Chest,
+    BoneName::UpperChest,
+    BoneName::Neck,
+];
+
+/// Tracked points driving this avatar.
+///
+/// Calibration happens when [VrBodyCalibration] is missing, and expects the
+/// user to be standing in the avatar's rest pose. Remove [VrBodyCalibration]
+/// to recalibrate.
+///
+/// The avatar root is expected to have no parent, and stays at its current height.
+#[derive(Component, Clone, Debug)]
+pub struct VrBody {
+    pub head: Entity,
+    pub left_hand: Entity,
+    pub right_hand: Entity,
+    pub hips: Option<Entity>,
+    pub left_foot: Option<Entity>,
+    pub right_foot: Option<Entity>,
+    /// Scale the avatar during calibration so its eye height matches the user's.
+    pub scale_avatar: bool,
+}
+
+impl VrBody {
+    pub fn new(head: Entity, left_hand: Entity, right_hand: Entity) -> Self {
+        Self {
+            head,
+            left_hand,
+            right_hand,
+            hips: None,
+            left_foot: None,
+            right_foot: None,
+            scale_avatar: true,
+        }
+    }
+}
+
+/// Measurements taken when calibrating a [VrBody].
+#[derive(Component, Clone, Debug)]
+pub struct VrBodyCalibration {
+    /// Avatar scale, the user's eye height over the avatar's.
+    pub scale: f32,
+    /// Direction the avatar faces, in root space.
+    pub forward: Vec3,
+    /// Rest position of the head bone, in root space.
+    pub head_position: Vec3,
+    /// Rest position of the hips bone relative to the head bone, in root space.
+    pub hips_from_head: Vec3,
+    /// Rest rotation of the hips bone, in root space.
+    pub hips_rotation: Quat,
+    /// Offset from each tracker to the bone it drives.
+    pub offsets: HashMap<BoneName, Transform>,
+}
+
+fn yaw(dir: Vec3) -> f32 {
+    dir.x.atan2(dir.z)
+}
+
+/// Rotation about Y turning `from` to face the horizontal direction of `to`.
+fn yaw_between(from: Vec3, to: Vec3) -> Quat {
+    Quat::from_rotation_y(yaw(to) - yaw(from))
+}
+
+fn trackers(body: &VrBody) -> impl Iterator<Item = (BoneName, Entity)> {
+    [
+        (BoneName::Head, Some(body.head)),
+        (BoneName::LeftHand, Some(body.left_hand)),
+        (BoneName::RightHand, Some(body.right_hand)),
+        (BoneName::Hips, body.hips),
+        (BoneName::LeftFoot, body.left_foot),
+        (BoneName::RightFoot, body.right_foot),
+    ]
+    .into_iter()
+    .filter_map(|(bone, tracker)| Some((bone, tracker?)))
+}
+
+/// Places the avatar root beneath the head, facing the head's direction.
+fn place_root(root: &mut Transform, calibration: &VrBodyCalibration, head: &GlobalTransform) {
+    let head_forward = head.rotation() * Vec3::NEG_Z;
+    root.rotation = yaw_between(calibration.forward, head_forward);
+
+    let offset = root.rotation * (calibration.head_position * root.scale);
+    let translation = head.translation() - offset;
+    root.translation.x = translation.x;
+    root.translation.z = translation.z;
+}
+
+pub(super) fn calibrate_vr_bodies(
+    mut commands: Commands,
+    avatars: Query<(Entity, &VrBody), Without<VrBodyCalibration>>,
+    bones: Query<&BoneName>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (root, body) in avatars.iter() {
+        let bone_map = find_bones(root, &bones, &children);
+
+        let (Some(&head), Some(&hips)) =
+            (bone_map.get(&BoneName::Head), bone_map.get(&BoneName::Hips))
+        else {
+            // Bones are not spawned yet.
+            continue;
+        };
+
+        let Ok(root_transform) = transforms.get(root).copied() else {
+            continue;
+        };
+
+        let root_inverse = GlobalTransform::from(root_transform).affine().inverse();
+        let to_root = |entity: Entity, transforms: &Query<&mut Transform>| {
+            let global = world_transform(entity, &parents, transforms);
+            let rotation = root_transform.rotation.inverse() * global.rotation();
+            (
+                root_inverse.transform_point3(global.translation()),
+                rotation,
+            )
+        };
+
+        let (head_position, _) = to_root(head, &transforms);
+        let (hips_position, hips_rotation) = to_root(hips, &transforms);
+
+        let eyes = [BoneName::LeftEye, BoneName::RightEye]
+            .iter()
+            .filter_map(|bone| bone_map.get(bone))
+            .map(|eye| to_root(*eye, &transforms).0)
+            .collect::<Vec<_>>();
+
+        let eye_position = if eyes.is_empty() {
+            head_position
+        } else {
+            eyes.iter().sum::<Vec3>() / eyes.len() as f32
+        };
+
+        let forward = match (
+            bone_map.get(&BoneName::LeftUpperLeg),
+            bone_map.get(&BoneName::RightUpperLeg),
+        ) {
+            (Some(left), Some(right)) => {
+                let right_dir = to_root(*right, &transforms).0 - to_root(*left, &transforms).0;
+                Vec3::Y
+                    .cross(right_dir)
+                    .with_y(0.0)
+                    .try_normalize()
+                    .unwrap_or(Vec3::NEG_Z)
+            }
+            _ => Vec3::NEG_Z,
+        };
+
+        let head_tracker = world_transform(body.head, &parents, &transforms);
+        let user_eye_height = head_tracker.translation().y - root_transform.translation.y;
+
+        let scale = if body.scale_avatar && eye_position.y > 0.0 && user_eye_height > 0.0 {
+            user_eye_height / eye_position.y
+        } else {
+            root_transform.scale.y
+        };
+
+        let mut calibration = VrBodyCalibration {
+            scale,
+            forward,
+            head_position,
+            hips_from_head: hips_position - head_position,
+            hips_rotation,
+            offsets: HashMap::default(),
+        };
+
+        if let Ok(mut transform) = transforms.get_mut(root) {
+            if body.scale_avatar {
+                transform.scale = Vec3::splat(scale);
+            }
+            place_root(&mut transform, &calibration, &head_tracker);
+        }
+
+        for (bone, tracker) in trackers(body) {
+            let Some(&bone_entity) = bone_map.get(&bone) else {
+                continue;
+            };
+
+            let tracker_global = world_transform(tracker, &parents, &transforms);
+            let bone_global = world_transform(bone_entity, &parents, &transforms);
+
+            // The head tracker sits at the eyes rather than the head bone.
+            let origin = if bone == BoneName::Head {
+                let root_global =
+                    GlobalTransform::from(transforms.get(root).copied().unwrap_or_default());
+                bone_global.translation() - root_global.transform_point(eye_position)
+                    + tracker_global.translation()
+            } else {
+                bone_global.translation()
+            };
+
+            let inverse = tracker_global.rotation().inverse();
+
+            calibration.offsets.insert(
+                bone,
+                Transform {
+                    translation: inverse * (origin - tracker_global.translation()),
+                    rotation: inverse * bone_global.rotation(),
+                    ..default()
+                },
+            );
+        }
+
+        commands.entity(root).insert(calibration);
+    }
+}
+
+/// Sets the world rotation of `entity`, and optionally its world position.
+fn set_world(
+    entity: Entity,
+    translation: Option<Vec3>,
+    rotation: Quat,
+    parents: &Query<&ChildOf>,
+    transforms: &mut Query<&mut Transform>,
+) {
+    let parent = parents
+        .get(entity)
+        .map(|parent| world_transform(parent.parent(), parents, transforms))
+        .unwrap_or_default();
+
+    let Ok(mut transform) = transforms.get_mut(entity) else {
+        return;
+    };
+
+    if let Some(translation) = translation {
+        transform.translation = parent.affine().inverse().transform_point3(translation);
+    }
+    transform.rotation = (parent.rotation().inverse() * rotation).normalize();
+}
+
+pub(super) fn solve_vr_bodies(
+    avatars: Query<(Entity, &VrBody, &VrBodyCalibration)>,
+    bones: Query<&BoneName>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (root, body, calibration) in avatars.iter() {
+        let bone_map = find_bones(root, &bones, &children);
+
+        // Where each tracker wants its bone, in world space.
+        let targets = trackers(body)
+            .filter_map(|(bone, tracker)| {
+                let global = world_transform(tracker, &parents, &transforms);
+                let offset = calibration.offsets.get(&bone)?;
+                Some((
+                    bone,
+                    (
+                        global.translation() + global.rotation() * offset.translation,
+                        global.rotation() * offset.rotation,
+                    ),
+                ))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let Some(&(head_position, head_rotation)) = targets.get(&BoneName::Head) else {
+            continue;
+        };
+
+        let head_tracker = world_transform(body.head, &parents, &transforms);
+        let Ok(mut root_transform) = transforms.get_mut(root) else {
+            continue;
+        };
+        place_root(&mut root_transform, calibration, &head_tracker);
+        let root_transform = *root_transform;
+
+        // Hips follow their tracker, or hang below the head.
+        if let Some(&hips) = bone_map.get(&BoneName::Hips) {
+            let (position, rotation) = targets.get(&BoneName::Hips).copied().unwrap_or((
+                head_position
+                    + root_transform.rotation * (calibration.hips_from_head * root_transform.scale),
+                root_transform.rotation * calibration.hips_rotation,
+            ));
+            set_world(hips, Some(position), rotation, &parents, &mut transforms);
+        }
+
+        // Bend the spine evenly so the head reaches its target.
+        let spine = SPINE
+            .iter()
+            .filter_map(|bone| bone_map.get(bone).copied())
+            .collect::<Vec<_>>();
+
+        if let (Some(&hips), Some(&head)) =
+            (bone_map.get(&BoneName::Hips), bone_map.get(&BoneName::Head))
+        {
+            let hips_position = world_transform(hips, &parents, &transforms).translation();
+            let current = world_transform(head, &parents, &transforms).translation();
+
+            let bend = match (
+                (current - hips_position).try_normalize(),
+                (head_position - hips_position).try_normalize(),
+            ) {
+                (Some(from), Some(to)) => Quat::from_rotation_arc(from, to),
+                _ => Quat::IDENTITY,
+            };
+            let step = Quat::IDENTITY.slerp(bend, 1.0 / spine.len().max(1) as f32);
+
+            for bone in spine {
+                let rotation = world_transform(bone, &parents, &transforms).rotation();
+                set_world(bone, None, step * rotation, &parents, &mut transforms);
+            }
+
+            set_world(head, None, head_rotation, &parents, &mut transforms);
+        }
+
+        let forward = root_transform.rotation * calibration.forward;
+        let right = forward.cross(Vec3::Y);
+
+        for (limb, bone, pole_dir) in [
+            (
+                Limb::LeftArm,
+                BoneName::LeftHand,
+                -right - forward - Vec3::Y,
+            ),
+            (
+                Limb::RightArm,
+                BoneName::RightHand,
+                right - forward - Vec3::Y,
+            ),
+            (Limb::LeftLeg, BoneName::LeftFoot, forward),
+            (Limb::RightLeg, BoneName::RightFoot, forward),
+        ] {
+            let Some(&(target, rotation)) = targets.get(&bone) else {
+                continue;
+            };
+
+            let Some(upper) = bone_map.get(&limb.bones()[0]) else {
+                continue;
+            };
+            let upper_position = world_transform(*upper, &parents, &transforms).translation();
+            let pole = upper_position + pole_dir * root_transform.scale;
+
+            solve_limb(
+                limb,
+                &bone_map,
+                target,
+                Some(pole),
+                1.0,
+                &parents,
+                &mut transforms,
+                |_| rotation,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn spawn_bone(world: &mut World, bone: BoneName, parent: Entity, at: Vec3) -> Entity {
+        world
+            .spawn((bone, Transform::from_translation(at), ChildOf(parent)))
+            .id()
+    }
+
+    /// Spawns an avatar with eyes at 1.7m and hands at the end of outstretched arms.
+    fn spawn_avatar(world: &mut World) -> (Entity, HashMap<BoneName, Entity>) {
+        let root = world.spawn(Transform::default()).id();
+        let mut bones = HashMap::new();
+
+        let hips = spawn_bone(world, BoneName::Hips, root, Vec3::new(0.0, 1.0, 0.0));
+        let spine = spawn_bone(world, BoneName::Spine, hips, Vec3::new(0.0, 0.2, 0.0));
+        let neck = spawn_bone(world, BoneName::Neck, spine, Vec3::new(0.0, 0.3, 0.0));
+        let head = spawn_bone(world, BoneName::Head, neck, Vec3::new(0.0, 0.1, 0.0));
+        let eye = spawn_bone(world, BoneName::LeftEye, head, Vec3::new(0.03, 0.1, 0.05));
+        bones.extend([
+            (BoneName::Hips, hips),
+            (BoneName::Spine, spine),
+            (BoneName::Neck, neck),
+            (BoneName::Head, head),
+            (BoneName::LeftEye, eye),
+        ]);
+
+        for (side, limb) in [(1.0, Limb::LeftArm), (-1.0, Limb::RightArm)] {
+            let [upper, lower, hand] = limb.bones();
+            let upper_entity = spawn_bone(world, upper, spine, Vec3::new(side * 0.2, 0.15, 0.0));
+            let lower_entity = spawn_bone(
+                world,
+                lower,
+                upper_entity,
+                Vec3::new(side * 0.25, 0.0, 0.01),
+            );
+            let hand_entity = spawn_bone(
+                world,
+                hand,
+                lower_entity,
+                Vec3::new(side * 0.25, 0.0, -0.01),
+            );
+            bones.extend([
+                (upper, upper_entity),
+                (lower, lower_entity),
+                (hand, hand_entity),
+            ]);
+        }
+
+        (root, bones)
+    }
+
+    fn world_position(world: &mut World, entity: Entity) -> Vec3 {
+        world
+            .run_system_once(
+                move |parents: Query<&ChildOf>, transforms: Query<&mut Transform>| {
+                    world_transform(entity, &parents, &transforms).translation()
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn calibration_scale() {
+        let mut world = World::new();
+        let (root, _) = spawn_avatar(&mut world);
+
+        let head = world.spawn(Transform::from_xyz(0.0, 1.53, 0.0)).id();
+        let left_hand = world.spawn(Transform::default()).id();
+        let right_hand = world.spawn(Transform::default()).id();
+        world
+            .entity_mut(root)
+            .insert(VrBody::new(head, left_hand, right_hand));
+
+        world.run_system_once(calibrate_vr_bodies).unwrap();
+
+        let calibration = world.get::<VrBodyCalibration>(root).unwrap();
+        assert!((calibration.scale - 0.9).abs() < 1e-4);
+        assert!(
+            world
+                .get::<Transform>(root)
+                .unwrap()
+                .scale
+                .abs_diff_eq(Vec3::splat(0.9), 1e-4)
+        );
+    }
+
+    #[test]
+    fn hands_reach_targets() {
+        let mut world = World::new();
+        let (root, bones) = spawn_avatar(&mut world);
+
+        // Start the user in the avatar's rest pose.
+        let left_rest = world_position(&mut world, bones[&BoneName::LeftHand]);
+        let right_rest = world_position(&mut world, bones[&BoneName::RightHand]);
+
+        let head = world.spawn(Transform::from_xyz(0.0, 1.7, 0.0)).id();
+        let left_hand = world.spawn(Transform::from_translation(left_rest)).id();
+        let right_hand = world.spawn(Transform::from_translation(right_rest)).id();
+        world
+            .entity_mut(root)
+            .insert(VrBody::new(head, left_hand, right_hand));
+
+        world.run_system_once(calibrate_vr_bodies).unwrap();
+
+        // Bring the hands in front of the chest.
+        let left_target = Vec3::new(0.3, 1.2, 0.25);
+        let right_target = Vec3::new(-0.25, 1.4, 0.2);
+        world.get_mut::<Transform>(left_hand).unwrap().translation = left_target;
+        world.get_mut::<Transform>(right_hand).unwrap().translation = right_target;
+
+        world.run_system_once(solve_vr_bodies).unwrap();
+
+        let left = world_position(&mut world, bones[&BoneName::LeftHand]);
+        let right = world_position(&mut world, bones[&BoneName::RightHand]);
+        assert!(left.abs_diff_eq(left_target, 1e-3), "{left}");
+        assert!(right.abs_diff_eq(right_target, 1e-3), "{right}");
+    }
+}
@@ -0,0 +1,626 @@
+//! Two-bone inverse kinematics for VRM humanoid limbs.
+//! See [body] for driving a full avatar from VR tracking.
+//!
+//! Solving runs in [PostUpdate] after animation and before spring bones,
+//! see [VrmSystems](crate::VrmSystems).
+
+use std::{collections::HashMap, marker::PhantomData};
+
+use bevy::{
+    ecs::system::{ReadOnlySystemParam, StaticSystemParam, SystemParamItem},
+    prelude::*,
+};
+use serde_vrm::vrm0::BoneName;
+
+use crate::VrmSystems;
+
+pub mod body;
+
+pub struct IkPlugin;
+
+impl Plugin for IkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                body::calibrate_vr_bodies,
+                body::solve_vr_bodies,
+                solve_humanoid_ik,
+            )
+                .chain()
+                .in_set(VrmSystems::Ik),
+        );
+    }
+}
+
+/// Solves the limbs of this avatar toward target entities.
+#[derive(Component, Clone, Debug, Default)]
+pub struct HumanoidIk {
+    pub left_hand: Option<IkTarget>,
+    pub right_hand: Option<IkTarget>,
+    pub left_foot: Option<IkTarget>,
+    pub right_foot: Option<IkTarget>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IkTarget {
+    /// Entity the end bone reaches toward, matching its world rotation.
+    pub target: Entity,
+    /// Entity the middle joint (elbow or knee) bends toward.
+    /// If unset, the limb keeps its current bend direction.
+    pub pole: Option<Entity>,
+    /// Blend between the animated pose (0) and the solved pose (1).
+    pub weight: f32,
+}
+
+impl IkTarget {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            pole: None,
+            weight: 1.0,
+        }
+    }
+
+    pub fn with_pole(mut self, pole: Entity) -> Self {
+        self.pole = Some(pole);
+        self
+    }
+
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limb {
+    LeftArm,
+    RightArm,
+    LeftLeg,
+    RightLeg,
+}
+
+impl Limb {
+    /// Upper, middle, and end bones of the limb.
+    pub fn bones(self) -> [BoneName; 3] {
+        match self {
+            Limb::LeftArm => [
+                BoneName::LeftUpperArm,
+                BoneName::LeftLowerArm,
+                BoneName::LeftHand,
+            ],
+            Limb::RightArm => [
+                BoneName::RightUpperArm,
+                BoneName::RightLowerArm,
+                BoneName::RightHand,
+            ],
+            Limb::LeftLeg => [
+                BoneName::LeftUpperLeg,
+                BoneName::LeftLowerLeg,
+                BoneName::LeftFoot,
+            ],
+            Limb::RightLeg => [
+                BoneName::RightUpperLeg,
+                BoneName::RightLowerLeg,
+                BoneName::RightFoot,
+            ],
+        }
+    }
+}
+
+/// Solves a two-bone chain with joints at `upper`, `middle`, and `end`,
+/// returning world-space rotations to apply to the upper and middle bones.
+///
+/// The chain bends in the plane containing `pole`. Out of reach targets
+/// are approached as closely as possible.
+pub fn solve_two_bone(
+    upper: Vec3,
+    middle: Vec3,
+    end: Vec3,
+    target: Vec3,
+    pole: Vec3,
+) -> (Quat, Quat) {
+    const EPSILON: f32 = 1e-5;
+
+    let upper_len = upper.distance(middle);
+    let lower_len = middle.distance(end);
+
+    let Some(dir) = (target - upper).try_normalize() else {
+        return (Quat::IDENTITY, Quat::IDENTITY);
+    };
+
+    let min_reach = (upper_len - lower_len).abs() + EPSILON;
+    let max_reach = upper_len + lower_len - EPSILON;
+    let reach = upper
+        .distance(target)
+        .clamp(min_reach, max_reach.max(min_reach));
+
+    let to_pole = pole - upper;
+    let bend = (to_pole - dir * to_pole.dot(dir))
+        .try_normalize()
+        .unwrap_or_else(|| dir.any_orthonormal_vector());
+
+    // Law of cosines for the angle at the upper joint.
+    let cos_upper = ((upper_len * upper_len + reach * reach - lower_len * lower_len)
+        / (2.0 * upper_len * reach))
+        .clamp(-1.0, 1.0);
+    let sin_upper = (1.0 - cos_upper * cos_upper).sqrt();
+
+    let new_middle = upper + (dir * cos_upper + bend * sin_upper) * upper_len;
+    let new_end = upper + dir * reach;
+
+    let upper_rot = rotation_between(middle - upper, new_middle - upper);
+
+    let rotated_end = upper + upper_rot * (end - upper);
+    let middle_rot = rotation_between(rotated_end - new_middle, new_end - new_middle);
+
+    (upper_rot, middle_rot)
+}
+
+fn rotation_between(from: Vec3, to: Vec3) -> Quat {
+    match (from.try_normalize(), to.try_normalize()) {
+        (Some(from), Some(to)) => Quat::from_rotation_arc(from, to),
+        _ => Quat::IDENTITY,
+    }
+}
+
+/// Computes an entity's world transform from local [Transform]s.
+///
+/// IK runs before transform propagation, so [GlobalTransform] is a frame behind.
+fn world_transform(
+    entity: Entity,
+    parents: &Query<&ChildOf>,
+    transforms: &Query<&mut Transform>,
+) -> GlobalTransform {
+    let mut global = transforms
+        .get(entity)
+        .map(|t| GlobalTransform::from(*t))
+        .unwrap_or_default();
+
+    let mut current = entity;
+    while let Ok(parent) = parents.get(current) {
+        current = parent.parent();
+
+        if let Ok(t) = transforms.get(current) {
+            global = GlobalTransform::from(*t) * global;
+        }
+    }
+
+    global
+}
+
+fn find_bones(
+    root: Entity,
+    bones: &Query<&BoneName>,
+    children: &Query<&Children>,
+) -> HashMap<BoneName, Entity> {
+    children
+        .iter_descendants(root)
+        .filter_map(|child| Some((*bones.get(child).ok()?, child)))
+        .collect()
+}
+
+/// Solves a limb toward `target` in world space.
+#[allow(clippy::too_many_arguments)]
+fn solve_limb(
+    limb: Limb,
+    bone_map: &HashMap<BoneName, Entity>,
+    target: Vec3,
+    pole: Option<Vec3>,
+    weight: f32,
+    parents: &Query<&ChildOf>,
+    transforms: &mut Query<&mut Transform>,
+    end_rotation: impl FnOnce(Quat) -> Quat,
+) {
+    let [upper, middle, end] = limb.bones().map(|bone| bone_map.get(&bone).copied());
+    let (Some(upper), Some(middle), Some(end)) = (upper, middle, end) else {
+        return;
+    };
+
+    if parents.get(middle).map(ChildOf::parent) != Ok(upper)
+        || parents.get(end).map(ChildOf::parent) != Ok(middle)
+    {
+        return;
+    }
+
+    let Ok(upper_parent) = parents.get(upper).map(ChildOf::parent) else {
+        return;
+    };
+
+    let parent_global = world_transform(upper_parent, parents, transforms);
+    let (Ok(upper_local), Ok(middle_local), Ok(end_local)) = (
+        transforms.get(upper).copied(),
+        transforms.get(middle).copied(),
+        transforms.get(end).copied(),
+    ) else {
+        return;
+    };
+
+    let upper_global = parent_global * GlobalTransform::from(upper_local);
+    let middle_global = upper_global * GlobalTransform::from(middle_local);
+    let end_global = middle_global * GlobalTransform::from(end_local);
+
+    let middle_pos = middle_global.translation();
+    let (upper_delta, middle_delta) = solve_two_bone(
+        upper_global.translation(),
+        middle_pos,
+        end_global.translation(),
+        target,
+        pole.unwrap_or(middle_pos),
+    );
+
+    let parent_rot = parent_global.rotation();
+    let upper_world = upper_delta * upper_global.rotation();
+    let middle_world = middle_delta * upper_delta * middle_global.rotation();
+    let end_world = end_rotation(end_global.rotation());
+
+    let new_upper = parent_rot.inverse() * upper_world;
+    let new_middle = upper_world.inverse() * middle_world;
+    let new_end = middle_world.inverse() * end_world;
+
+    for (entity, original, solved) in [
+        (upper, upper_local.rotation, new_upper),
+        (middle, middle_local.rotation, new_middle),
+        (end, end_local.rotation, new_end),
+    ] {
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.rotation = original.slerp(solved.normalize(), weight);
+        }
+    }
+}
+
+fn solve_humanoid_ik(
+    avatars: Query<(Entity, &HumanoidIk)>,
+    bones: Query<&BoneName>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (root, ik) in avatars.iter() {
+        let bone_map = find_bones(root, &bones, &children);
+
+        for (limb, ik_target) in [
+            (Limb::LeftArm, ik.left_hand),
+            (Limb::RightArm, ik.right_hand),
+            (Limb::LeftLeg, ik.left_foot),
+            (Limb::RightLeg, ik.right_foot),
+        ] {
+            let Some(ik_target) = ik_target else {
+                continue;
+            };
+
+            let target = world_transform(ik_target.target, &parents, &transforms);
+            let pole = ik_target
+                .pole
+                .map(|pole| world_transform(pole, &parents, &transforms).translation());
+
+            solve_limb(
+                limb,
+                &bone_map,
+                target.translation(),
+                pole,
+                ik_target.weight,
+                &parents,
+                &mut transforms,
+                |_| target.rotation(),
+            );
+        }
+    }
+}
+
+/// Result of a [GroundQuery] ray cast.
+#[derive(Clone, Copy, Debug)]
+pub struct GroundHit {
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+/// Finds the ground beneath a foot.
+/// Implement this for a [SystemParam](bevy::ecs::system::SystemParam) to plug in
+/// a physics engine's ray casting.
+pub trait GroundQuery: ReadOnlySystemParam + 'static {
+    fn cast_ground(
+        param: &SystemParamItem<Self>,
+        ray: Ray3d,
+        max_distance: f32,
+    ) -> Option<GroundHit>;
+}
+
+/// An infinite horizontal ground plane, for use without a physics engine.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct GroundPlane {
+    pub height: f32,
+}
+
+impl GroundQuery for Res<'static, GroundPlane> {
+    fn cast_ground(
+        param: &SystemParamItem<Self>,
+        ray: Ray3d,
+        max_distance: f32,
+    ) -> Option<GroundHit> {
+        let distance =
+            ray.intersect_plane(Vec3::Y * param.height, InfinitePlane3d::new(Vec3::Y))?;
+
+        if distance > max_distance {
+            return None;
+        }
+
+        Some(GroundHit {
+            point: ray.get_point(distance),
+            normal: Vec3::Y,
+        })
+    }
+}
+
+/// Plants the feet of [FootPlanting] avatars on the ground found by `G`.
+pub struct FootPlantingPlugin<G: GroundQuery>(PhantomData<fn() -> G>);
+
+impl<G: GroundQuery> Default for FootPlantingPlugin<G> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<G: GroundQuery> Plugin for FootPlantingPlugin<G> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, plant_feet::<G>.in_set(VrmSystems::FootPlanting));
+    }
+}
+
+/// Keeps this avatar's feet on the ground, lowering the hips when
+/// the ground drops away beneath a planted foot.
+#[derive(Component, Clone, Debug)]
+pub struct FootPlanting {
+    /// Height of the ankle above the sole.
+    pub foot_height: f32,
+    /// Feet within this distance above the ground are treated as planted.
+    /// Feet higher than this are left to the animation, as when stepping,
+    /// so the hips never drop by more than this distance.
+    pub snap_distance: f32,
+    /// How far above the foot to start ray casts, so feet below the ground are found.
+    pub ray_offset: f32,
+    /// Rotate feet to match the ground normal.
+    pub align_to_ground: bool,
+    pub weight: f32,
+}
+
+impl Default for FootPlanting {
+    fn default() -> Self {
+        Self {
+            foot_height: 0.1,
+            snap_distance: 0.05,
+            ray_offset: 0.5,
+            align_to_ground: true,
+            weight: 1.0,
+        }
+    }
+}
+
+fn plant_feet<G: GroundQuery>(
+    ground: StaticSystemParam<G>,
+    avatars: Query<(Entity, &FootPlanting)>,
+    bones: Query<&BoneName>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (root, planting) in avatars.iter() {
+        let bone_map = find_bones(root, &bones, &children);
+
+        let mut feet = Vec::new();
+
+        for limb in [Limb::LeftLeg, Limb::RightLeg] {
+            let Some(foot) = bone_map.get(&limb.bones()[2]) else {
+                continue;
+            };
+
+            let foot_pos = world_transform(*foot, &parents, &transforms).translation();
+
+            let ray = Ray3d::new(foot_pos + Vec3::Y * planting.ray_offset, Dir3::NEG_Y);
+            let max_distance = planting.ray_offset + planting.foot_height + planting.snap_distance;
+
+            let Some(hit) = G::cast_ground(&ground, ray, max_distance) else {
+                continue;
+            };
+
+            let offset = hit.point.y + planting.foot_height - foot_pos.y;
+            if offset < -planting.snap_distance {
+                continue;
+            }
+
+            feet.push((limb, foot_pos + Vec3::Y * offset, hit.normal, offset));
+        }
+
+        // Lower the hips so the legs can reach ground below the animated pose.
+        let drop = feet
+            .iter()
+            .map(|(_, _, _, offset)| *offset)
+            .fold(0.0, f32::min)
+            * planting.weight;
+
+        if drop < 0.0
+            && let Some(&hips) = bone_map.get(&BoneName::Hips)
+        {
+            let parent_global = parents
+                .get(hips)
+                .map(|parent| world_transform(parent.parent(), &parents, &transforms))
+                .unwrap_or_default();
+
+            let local_drop = parent_global
+                .affine()
+                .inverse()
+                .transform_vector3(Vec3::Y * drop);
+
+            if let Ok(mut transform) = transforms.get_mut(hips) {
+                transform.translation += local_drop;
+            }
+        }
+
+        for (limb, target, normal, _) in feet {
+            let align = planting.align_to_ground;
+
+            solve_limb(
+                limb,
+                &bone_map,
+                target,
+                None,
+                planting.weight,
+                &parents,
+                &mut transforms,
+                |rotation| {
+                    if align {
+                        Quat::from_rotation_arc(Vec3::Y, normal) * rotation
+                    } else {
+                        rotation
+                    }
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// Spawns hips at 1m with a left leg reaching down to a foot at 0.1m.
+    fn spawn_legs(world: &mut World) -> (Entity, Entity, Entity) {
+        let root = world.spawn(Transform::default()).id();
+        let hips = world
+            .spawn((
+                BoneName::Hips,
+                Transform::from_xyz(0.0, 1.0, 0.0),
+                ChildOf(root),
+            ))
+            .id();
+        let upper = world
+            .spawn((
+                BoneName::LeftUpperLeg,
+                Transform::from_xyz(0.1, 0.0, 0.0),
+                ChildOf(hips),
+            ))
+            .id();
+        let lower = world
+            .spawn((
+                BoneName::LeftLowerLeg,
+                // Slightly bent, so the knee has a direction to bend in.
+                Transform::from_xyz(0.0, -0.45, 0.01),
+                ChildOf(upper),
+            ))
+            .id();
+        let foot = world
+            .spawn((
+                BoneName::LeftFoot,
+                Transform::from_xyz(0.0, -0.45, -0.01),
+                ChildOf(lower),
+            ))
+            .id();
+
+        (root, hips, foot)
+    }
+
+    fn world_position(world: &mut World, entity: Entity) -> GlobalTransform {
+        world
+            .run_system_once(
+                move |parents: Query<&ChildOf>, transforms: Query<&mut Transform>| {
+                    world_transform(entity, &parents, &transforms)
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn foot_planting() {
+        for (ground, expected_hips, expected_foot) in [
+            // Raised ground lifts the foot.
+            (0.03, 1.0, 0.13),
+            // Lowered ground within the snap distance drops the hips.
+            (-0.04, 0.96, 0.06),
+            // Ground further below is ignored, as if the foot were stepping.
+            (-0.2, 1.0, 0.1),
+        ] {
+            let mut world = World::new();
+            world.insert_resource(GroundPlane { height: ground });
+
+            let (root, hips, foot) = spawn_legs(&mut world);
+            world.entity_mut(root).insert(FootPlanting {
+                foot_height: 0.1,
+                ..default()
+            });
+
+            world
+                .run_system_once(plant_feet::<Res<GroundPlane>>)
+                .unwrap();
+
+            let hips = world_position(&mut world, hips).translation();
+            let foot = world_position(&mut world, foot).translation();
+
+            assert!((hips.y - expected_hips).abs() < 1e-4, "{ground}: {hips}");
+            assert!((foot.y - expected_foot).abs() < 1e-3, "{ground}: {foot}");
+        }
+    }
+
+    #[test]
+    fn matches_target_rotation() {
+        let mut world = World::new();
+        let (root, _, foot) = spawn_legs(&mut world);
+
+        let rotation = Quat::from_rotation_y(1.0);
+        let target = world
+            .spawn(Transform::from_xyz(0.3, 0.3, 0.2).with_rotation(rotation))
+            .id();
+
+        world.entity_mut(root).insert(HumanoidIk {
+            left_foot: Some(IkTarget::new(target)),
+            ..default()
+        });
+
+        world.run_system_once(solve_humanoid_ik).unwrap();
+
+        let foot = world_position(&mut world, foot);
+        assert!(
+            foot.translation()
+                .abs_diff_eq(Vec3::new(0.3, 0.3, 0.2), 1e-3)
+        );
+        assert!(foot.rotation().abs_diff_eq(rotation, 1e-4));
+    }
+
+    #[test]
+    fn reaches_target() {
+        let upper = Vec3::ZERO;
+        let middle = Vec3::new(0.0, -1.0, 0.0);
+        let end = Vec3::new(0.0, -2.0, 0.0);
+        let target = Vec3::new(1.0, -1.0, 0.0);
+        let pole = Vec3::new(0.0, 0.0, 1.0);
+
+        let (upper_rot, middle_rot) = solve_two_bone(upper, middle, end, target, pole);
+
+        let new_middle = upper + upper_rot * (middle - upper);
+        let new_end = new_middle + middle_rot * upper_rot * (end - middle);
+
+        assert!(new_end.abs_diff_eq(target, 1e-4));
+        assert!((new_middle.distance(upper) - 1.0).abs() < 1e-4);
+
+        // Bends toward the pole.
+        assert!(new_middle.z > 0.0);
+    }
+
+    #[test]
+    fn out_of_reach() {
+        let (upper_rot, middle_rot) = solve_two_bone(
+            Vec3::ZERO,
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, -2.0, 0.0),
+            Vec3::new(5.0, 0.0, 0.0),
+            Vec3::Z,
+        );
+
+        let new_middle = upper_rot * Vec3::new(0.0, -1.0, 0.0);
+        let new_end = new_middle + middle_rot * upper_rot * Vec3::new(0.0, -1.0, 0.0);
+
+        assert!(new_end.abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-3));
+    }
+}
@@ -2,22 +2,32 @@
 //! Aims to support both the VRM 0.0 and VRM 1.0 standards.
 
 use auto_scene::AutoScene;
-use bevy::{app::PluginGroupBuilder, prelude::*};
+use bevy::{app::PluginGroupBuilder, prelude::*, render::view::VisibilitySystems};
 use bevy_gltf_kun::GltfKunPlugin;
 use bevy_shader_mtoon::MtoonPlugin;
-use first_person::SetupFirstPerson;
+use expressions::VrmExpressions;
+use first_person::{SetupFirstPerson, VrmFirstPerson, VrmFirstPersonReady};
 use loader::{Vrm, VrmLoader};
+use measurements::VrmMeasurements;
+use meta::VrmMeta;
 use serde_vrm::vrm0::FirstPersonFlag;
 
-use crate::spring_bones::SpringBonePlugin;
+use crate::{ik::IkPlugin, spring_bones::SpringBonePlugin};
 
 #[cfg(feature = "animations")]
 pub mod animations;
 pub mod auto_scene;
+pub mod expressions;
 pub mod extensions;
 pub mod first_person;
+pub mod ik;
 pub mod loader;
+pub mod measurements;
+pub mod meta;
+pub mod policy;
 pub mod spring_bones;
+#[cfg(feature = "vmc")]
+pub mod vmc;
 
 pub mod mtoon {
     pub use bevy_shader_mtoon::*;
@@ -26,12 +36,22 @@ pub mod mtoon {
 pub use serde_vrm::vrm0::BoneName;
 
 pub struct VrmPlugin;
+
+/// Pose systems, run in [PostUpdate] after animation and before transform propagation.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VrmSystems {
+    FootPlanting,
+    Ik,
+    SpringBones,
+}
+
 pub struct VrmPlugins;
 
 impl PluginGroup for VrmPlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
             .add(VrmPlugin)
+            .add(IkPlugin)
             .add(SpringBonePlugin)
     }
 }
@@ -41,14 +61,62 @@ impl Plugin for VrmPlugin {
         // TODO: Dont use default GltfKunPlugin
         app.add_plugins((GltfKunPlugin::default(), MtoonPlugin))
             .add_event::<SetupFirstPerson>()
+            .add_event::<VrmFirstPersonReady>()
+            .add_observer(first_person::auto_setup_first_person)
             .init_asset::<Vrm>()
+            .init_asset::<VrmMeta>()
             .init_asset_loader::<VrmLoader>()
             .register_type::<BoneName>()
             .register_type::<FirstPersonFlag>()
+            .register_type::<VrmExpressions>()
+            .register_type::<VrmFirstPerson>()
+            .register_type::<VrmMeasurements>()
+            .register_type::<VrmMeta>()
+            .add_systems(First, policy::sync_loader_policy)
+            .configure_sets(
+                PostUpdate,
+                (
+                    VrmSystems::FootPlanting,
+                    VrmSystems::Ik,
+                    VrmSystems::SpringBones,
+                )
+                    .chain()
+                    .after(bevy::app::Animation)
+                    .before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    first_person::set_first_person_camera_layers,
+                    (
+                        first_person::follow_first_person_bone,
+                        first_person::propagate_first_person_camera,
+                    )
+                        .chain(),
+                )
+                    .after(TransformSystem::TransformPropagate)
+                    .before(VisibilitySystems::UpdateFrusta),
+            )
             .add_systems(
                 Update,
-                (auto_scene::set_vrm_scene, first_person::handle_setup_events).chain(),
+                (
+                    (
+                        auto_scene::set_vrm_scene,
+                        first_person::handle_setup_events,
+                        first_person::finish_first_person_splits,
+                    )
+                        .chain(),
+                    (
+                        expressions::remap_expression_entities,
+                        expressions::apply_expressions,
+                    )
+                        .chain(),
+                ),
             );
+
+        #[cfg(feature = "animations")]
+        app.init_asset::<animations::bvh::BvhRootMotion>()
+            .init_asset_loader::<animations::bvh::BvhLoader>();
     }
 }
 
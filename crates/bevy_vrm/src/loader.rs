@@ -1,40 +1,241 @@
-use std::fmt::Debug;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
 
 use bevy::{
-    asset::{
-        AssetLoader, LoadContext,
-        io::{Reader, VecReader},
-    },
+    asset::{AssetLoader, LoadContext, io::Reader},
+    platform::collections::HashMap as BevyHashMap,
     prelude::*,
 };
-use bevy_gltf_kun::import::gltf::{
-    GltfKun,
-    loader::{GlbLoader, GltfError, GltfLoader},
+use bevy_gltf_kun::import::{
+    gltf::{
+        GltfKun,
+        document::{ImportContext, import_gltf_document},
+        loader::GltfError,
+    },
+    resolver::BevyAssetResolver,
+};
+use gltf_kun::{
+    graph::Graph,
+    io::format::{
+        glb::GlbImport,
+        gltf::{GltfFormat, GltfImport},
+    },
 };
+use serde::{Deserialize, Serialize};
+use serde_vrm::vrm0::BoneName;
 use thiserror::Error;
 
-use crate::extensions::VrmExtensions;
+use crate::{
+    extensions::{VrmExtensions, limit_texture_size, read_meta, thumbnail},
+    meta::{META_LABEL, VrmMeta},
+    policy::{PermissionViolation, PolicyDecision, SharedPolicy, VrmLoaderPolicy},
+};
 
 #[derive(Asset, TypePath, Debug)]
 pub struct Vrm {
     pub gltf: GltfKun,
+    /// Also available as the `Meta` sub-asset.
+    pub meta: Handle<VrmMeta>,
+    /// Also available as the `Thumbnail` sub-asset.
+    pub thumbnail: Option<Handle<Image>>,
+    /// Permissions this avatar's license does not grant, flagged by the loader's [VrmPolicy].
+    pub permission_violations: Vec<PermissionViolation>,
+    /// Problems found while importing, if not loaded in [strict](VrmLoaderSettings::strict) mode.
+    pub warnings: Vec<VrmImportError>,
 }
 
-#[derive(Default)]
 pub struct VrmLoader {
-    pub gltf_loader: GltfLoader<VrmExtensions>,
-    pub glb_loader: GlbLoader<VrmExtensions>,
+    policy: SharedPolicy,
+}
+
+impl FromWorld for VrmLoader {
+    fn from_world(world: &mut World) -> Self {
+        let policy = world.get_resource_or_init::<SharedPolicy>().clone();
+
+        if let Some(current) = world.get_resource::<VrmLoaderPolicy>() {
+            policy.set(Some(current.0.clone()));
+        }
+
+        Self { policy }
+    }
+}
+
+/// Options for importing a VRM, set through `.meta` files or
+/// [AssetServer::load_with_settings].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct VrmLoaderSettings {
+    /// Create [MtoonMaterial](bevy_shader_mtoon::MtoonMaterial) assets for MToon materials.
+    /// If disabled, those meshes fall back to their glTF [StandardMaterial].
+    pub mtoon: bool,
+    /// Use the glTF [StandardMaterial] on every mesh, such as for low-end clients.
+    /// MToon materials are still loaded as labeled assets if [Self::mtoon] is set.
+    pub force_standard_material: bool,
+    /// Import spring bones.
+    pub spring_bones: bool,
+    /// Import first-person flags and the first-person viewpoint.
+    pub first_person: bool,
+    /// Index of the scene to use as the default, instead of the file's default scene.
+    pub scene: Option<usize>,
+    /// Downscale textures so neither dimension exceeds this size.
+    pub max_texture_size: Option<u32>,
+    /// Fail the load on any [VrmImportError], instead of recording it in [Vrm::warnings].
+    pub strict: bool,
+}
+
+impl Default for VrmLoaderSettings {
+    fn default() -> Self {
+        Self {
+            mtoon: true,
+            force_standard_material: false,
+            spring_bones: true,
+            first_person: true,
+            scene: None,
+            max_texture_size: None,
+            strict: false,
+        }
+    }
+}
+
+/// Settings and issues of a VRM import.
+#[derive(Default)]
+struct ImportState {
+    settings: VrmLoaderSettings,
+    issues: Vec<VrmImportError>,
+}
+
+/// Imports in progress, keyed by the address of the [Graph] each one imports into.
+///
+/// The glTF extension hooks are only given the graph, through their [ImportContext],
+/// so this is how they find the settings of the load they are part of.
+static IMPORTS: Mutex<Vec<(usize, ImportState)>> = Mutex::new(Vec::new());
+
+fn imports() -> MutexGuard<'static, Vec<(usize, ImportState)>> {
+    IMPORTS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn graph_key(graph: &Graph) -> usize {
+    graph as *const Graph as usize
+}
+
+/// Registers an import into a graph, until dropped.
+///
+/// Hooks find the import by the graph's address, so the graph must stay in place until then.
+pub(crate) struct ImportGuard(usize);
+
+impl ImportGuard {
+    pub(crate) fn new(graph: &Graph, settings: VrmLoaderSettings) -> Self {
+        let key = graph_key(graph);
+        let state = ImportState {
+            settings,
+            ..default()
+        };
+        imports().push((key, state));
+        Self(key)
+    }
+
+    /// Ends the import, returning the recorded issues.
+    pub(crate) fn finish(self) -> Vec<VrmImportError> {
+        imports()
+            .iter_mut()
+            .find(|(key, _)| *key == self.0)
+            .map(|(_, state)| std::mem::take(&mut state.issues))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for ImportGuard {
+    fn drop(&mut self) {
+        imports().retain(|(key, _)| *key != self.0);
+    }
+}
+
+/// Settings of the VRM being imported into `graph`.
+pub(crate) fn graph_settings(graph: &Graph) -> VrmLoaderSettings {
+    let key = graph_key(graph);
+    imports()
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, state)| state.settings.clone())
+        .unwrap_or_default()
+}
+
+/// Records a problem with the VRM being imported into `graph`.
+pub(crate) fn report_graph(graph: &Graph, issue: VrmImportError) {
+    let key = graph_key(graph);
+    let mut imports = imports();
+
+    if let Some((_, state)) = imports.iter_mut().find(|(k, _)| *k == key) {
+        if state.issues.contains(&issue) {
+            return;
+        }
+        state.issues.push(issue.clone());
+    }
+
+    warn!("{}", issue);
+}
+
+/// Settings of the VRM being imported.
+pub(crate) fn import_settings(context: &ImportContext) -> VrmLoaderSettings {
+    graph_settings(context.graph)
+}
+
+/// Records a problem with the VRM being imported.
+pub(crate) fn report(context: &ImportContext, issue: VrmImportError) {
+    report_graph(context.graph, issue);
 }
 
 #[derive(Debug, Error)]
 pub enum VrmError {
     #[error(transparent)]
     Gltf(#[from] GltfError),
+    #[error("Avatar license does not permit this usage: {}", format_violations(.0))]
+    PermissionDenied(Vec<PermissionViolation>),
+    #[error(transparent)]
+    Import(#[from] VrmImportError),
+}
+
+/// Problem with the contents of a VRM file.
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum VrmImportError {
+    #[error("VRM extension not found")]
+    MissingExtension,
+    #[error("Invalid VRM extension: {0}")]
+    InvalidExtension(String),
+    #[error("Invalid VRM extension data: {0}")]
+    InvalidWeight(String),
+    #[error("Missing humanoid bone: {0}")]
+    MissingHumanBone(BoneName),
+    #[error("Node not found for humanoid bone: {0}")]
+    MissingBoneNode(BoneName),
+    #[error("Material not found for material property {0}")]
+    MissingMaterial(usize),
+    #[error("MToon material not found for material property {0}")]
+    MissingMtoonMaterial(usize),
+    #[error("Invalid texture index in material property {0}")]
+    InvalidTextureIndex(usize),
+    #[error("Unsupported shader: {0}")]
+    UnsupportedShader(String),
+    #[error("Mesh not found for primitive")]
+    MissingMesh,
+    #[error("Scene {0} not found")]
+    MissingScene(usize),
+}
+
+fn format_violations(violations: &[PermissionViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl AssetLoader for VrmLoader {
     type Asset = Vrm;
-    type Settings = ();
+    type Settings = VrmLoaderSettings;
     type Error = VrmError;
 
     fn load(
@@ -49,23 +250,81 @@ impl AssetLoader for VrmLoader {
             reader
                 .read_to_end(&mut bytes)
                 .await
-                .map_err(|e| VrmError::Gltf(GltfError::Io(e)))?;
+                .map_err(GltfError::from)?;
+
+            let mut graph = Graph::default();
+            let import = ImportGuard::new(&graph, settings.clone());
 
             let is_glb = bytes.len() >= 4 && &bytes[0..4] == b"glTF";
 
-            let gltf = if is_glb {
-                let mut vec_reader = VecReader::new(bytes);
-                self.glb_loader
-                    .load(&mut vec_reader, settings, load_context)
-                    .await?
+            let mut doc = if is_glb {
+                GlbImport::<VrmExtensions>::import_slice(&mut graph, &bytes)
+                    .await
+                    .map_err(GltfError::from)?
             } else {
-                let mut vec_reader = VecReader::new(bytes);
-                self.gltf_loader
-                    .load(&mut vec_reader, settings, load_context)
-                    .await?
+                let format = GltfFormat {
+                    json: serde_json::from_slice(&bytes).map_err(GltfError::from)?,
+                    resources: HashMap::new(),
+                };
+                let resolver = BevyAssetResolver { load_context };
+                GltfImport::<VrmExtensions>::import(&mut graph, format, Some(resolver))
+                    .await
+                    .map_err(GltfError::from)?
             };
 
-            Ok(Vrm { gltf })
+            if let Some(max_size) = settings.max_texture_size {
+                limit_texture_size(&mut graph, &doc, max_size);
+            }
+
+            let mut gltf = GltfKun::new(&mut graph, &mut doc);
+
+            let mut context = ImportContext {
+                doc: &mut doc,
+                gltf: &mut gltf,
+                graph: &mut graph,
+                load_context,
+
+                materials: BevyHashMap::default(),
+                skin_matrices: BevyHashMap::default(),
+            };
+
+            import_gltf_document::<VrmExtensions>(&mut context).map_err(GltfError::from)?;
+
+            let thumbnail = thumbnail(&mut context);
+            let mut warnings = import.finish();
+
+            gltf.graph = graph;
+
+            if let Some(index) = settings.scene {
+                match gltf.scenes.get(index) {
+                    Some(scene) => gltf.default_scene = Some(scene.clone()),
+                    None => warnings.push(VrmImportError::MissingScene(index)),
+                }
+            }
+
+            if let (true, Some(issue)) = (settings.strict, warnings.first()) {
+                return Err(VrmError::Import(issue.clone()));
+            }
+
+            let meta = read_meta(&gltf.graph, &doc).unwrap_or_default();
+
+            let permission_violations = match self.policy.get().map(|p| p.evaluate(&meta)) {
+                Some(PolicyDecision::Deny(violations)) => {
+                    return Err(VrmError::PermissionDenied(violations));
+                }
+                Some(PolicyDecision::Flag(violations)) => violations,
+                Some(PolicyDecision::Allow) | None => Vec::new(),
+            };
+
+            let meta = load_context.add_labeled_asset(META_LABEL.to_string(), meta);
+
+            Ok(Vrm {
+                gltf,
+                meta,
+                thumbnail,
+                permission_violations,
+                warnings,
+            })
         })
     }
 
@@ -73,3 +332,115 @@ impl AssetLoader for VrmLoader {
         &["vrm"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::LoadState;
+
+    use super::*;
+    use crate::{
+        meta::AvatarPermission,
+        policy::{UsagePolicy, VrmUsage, sync_loader_policy},
+    };
+
+    #[test]
+    fn collect_import_issues() {
+        let graph = Graph::default();
+        let other = Graph::default();
+
+        let settings = VrmLoaderSettings {
+            strict: true,
+            ..default()
+        };
+        let import = ImportGuard::new(&graph, settings);
+        assert!(graph_settings(&graph).strict);
+        assert!(!graph_settings(&other).strict);
+
+        report_graph(&graph, VrmImportError::MissingExtension);
+        report_graph(&graph, VrmImportError::MissingExtension);
+        report_graph(&graph, VrmImportError::MissingHumanBone(BoneName::Head));
+        report_graph(&other, VrmImportError::MissingMesh);
+
+        assert_eq!(
+            import.finish(),
+            vec![
+                VrmImportError::MissingExtension,
+                VrmImportError::MissingHumanBone(BoneName::Head),
+            ]
+        );
+        assert!(!graph_settings(&graph).strict);
+    }
+
+    #[test]
+    fn policy_inserted_after_plugins() {
+        let dir = std::env::temp_dir().join(format!("bevy_vrm_policy_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let avatar = serde_json::json!({
+            "asset": { "version": "2.0" },
+            "extensions": {
+                "VRMC_vrm": {
+                    "specVersion": "1.0",
+                    "meta": {
+                        "name": "Avatar",
+                        "authors": ["Author"],
+                        "licenseUrl": "https://vrm.dev/licenses/1.0/",
+                        "avatarPermission": "onlyAuthor",
+                    },
+                    "humanoid": { "humanBones": {} },
+                },
+            },
+        });
+        std::fs::write(dir.join("avatar.vrm"), avatar.to_string()).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: dir.to_string_lossy().to_string(),
+                ..default()
+            },
+        ))
+        .init_asset::<GltfKun>()
+        .init_asset::<Vrm>()
+        .init_asset::<VrmMeta>()
+        .init_asset_loader::<VrmLoader>()
+        .add_systems(First, sync_loader_policy);
+
+        app.insert_resource(VrmLoaderPolicy::new(UsagePolicy {
+            usage: VrmUsage {
+                user: AvatarPermission::Everyone,
+                ..default()
+            },
+            deny: true,
+        }));
+        app.update();
+
+        let handle = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<Vrm>("avatar.vrm");
+
+        let mut state = LoadState::Loading;
+        for _ in 0..1000 {
+            app.update();
+
+            state = app.world().resource::<AssetServer>().load_state(&handle);
+            if matches!(state, LoadState::Loaded | LoadState::Failed(_)) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+
+        let LoadState::Failed(error) = state else {
+            panic!("Expected the load to fail, got {:?}", state);
+        };
+        assert!(
+            error.to_string().contains("Avatar license does not permit"),
+            "{}",
+            error
+        );
+    }
+}
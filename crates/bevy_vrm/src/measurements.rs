@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde_vrm::vrm0::BoneName;
+
+/// Body measurements of an avatar in its rest pose, in the avatar root's space.
+/// Inserted on the scene root when the VRM is imported.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct VrmMeasurements {
+    /// Estimated standing height, from the ground to the top of the head.
+    pub height: f32,
+    /// Point between the eyes, used for first-person cameras.
+    pub eye_position: Vec3,
+    /// Distance between the shoulder joints.
+    pub shoulder_width: f32,
+    /// Fingertip to fingertip, with arms held out straight.
+    pub arm_span: f32,
+    /// Hip joint to the ground, along the leg.
+    pub leg_length: f32,
+    /// Height of the hips bone above the ground.
+    pub hips_height: f32,
+}
+
+impl VrmMeasurements {
+    pub fn eye_height(&self) -> f32 {
+        self.eye_position.y
+    }
+
+    /// Measures from bone positions in the root's space, with the ground at `y = 0`.
+    ///
+    /// `eye_position` overrides the position found from the eye bones,
+    /// such as one from the VRM first-person offset.
+    pub fn from_bones(bones: &HashMap<BoneName, Vec3>, eye_position: Option<Vec3>) -> Self {
+        let distance = |a: BoneName, b: BoneName| match (bones.get(&a), bones.get(&b)) {
+            (Some(a), Some(b)) => a.distance(*b),
+            _ => 0.0,
+        };
+
+        let chain = |chain: &[BoneName]| {
+            chain
+                .windows(2)
+                .map(|pair| distance(pair[0], pair[1]))
+                .sum::<f32>()
+        };
+
+        let head = bones.get(&BoneName::Head).copied().unwrap_or_default();
+
+        let eye_position = eye_position.unwrap_or_else(|| {
+            let eyes = [BoneName::LeftEye, BoneName::RightEye]
+                .iter()
+                .filter_map(|bone| bones.get(bone))
+                .collect::<Vec<_>>();
+
+            if eyes.is_empty() {
+                head
+            } else {
+                let count = eyes.len() as f32;
+                eyes.into_iter().sum::<Vec3>() / count
+            }
+        });
+
+        // The eyes sit roughly halfway up the head.
+        let height = eye_position.y + (eye_position.y - head.y).max(0.0);
+
+        let shoulder_width = distance(BoneName::LeftUpperArm, BoneName::RightUpperArm);
+
+        let arm = |upper, lower, hand, finger| {
+            let length = chain(&[upper, lower, hand]);
+            length + distance(hand, finger)
+        };
+
+        let arm_span = shoulder_width
+            + arm(
+                BoneName::LeftUpperArm,
+                BoneName::LeftLowerArm,
+                BoneName::LeftHand,
+                BoneName::LeftMiddleDistal,
+            )
+            + arm(
+                BoneName::RightUpperArm,
+                BoneName::RightLowerArm,
+                BoneName::RightHand,
+                BoneName::RightMiddleDistal,
+            );
+
+        let leg = |upper, lower, foot: BoneName| {
+            chain(&[upper, lower, foot]) + bones.get(&foot).map(|f| f.y).unwrap_or_default()
+        };
+
+        let leg_length = (leg(
+            BoneName::LeftUpperLeg,
+            BoneName::LeftLowerLeg,
+            BoneName::LeftFoot,
+        ) + leg(
+            BoneName::RightUpperLeg,
+            BoneName::RightLowerLeg,
+            BoneName::RightFoot,
+        )) / 2.0;
+
+        let hips_height = bones.get(&BoneName::Hips).map(|h| h.y).unwrap_or_default();
+
+        Self {
+            height,
+            eye_position,
+            shoulder_width,
+            arm_span,
+            leg_length,
+            hips_height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_bones() {
+        let bones = HashMap::from([
+            (BoneName::Hips, Vec3::new(0.0, 1.0, 0.0)),
+            (BoneName::Head, Vec3::new(0.0, 1.5, 0.0)),
+            (BoneName::LeftEye, Vec3::new(0.03, 1.6, 0.05)),
+            (BoneName::RightEye, Vec3::new(-0.03, 1.6, 0.05)),
+            (BoneName::LeftUpperArm, Vec3::new(0.2, 1.4, 0.0)),
+            (BoneName::LeftLowerArm, Vec3::new(0.5, 1.4, 0.0)),
+            (BoneName::LeftHand, Vec3::new(0.75, 1.4, 0.0)),
+            (BoneName::RightUpperArm, Vec3::new(-0.2, 1.4, 0.0)),
+            (BoneName::RightLowerArm, Vec3::new(-0.5, 1.4, 0.0)),
+            (BoneName::RightHand, Vec3::new(-0.75, 1.4, 0.0)),
+            (BoneName::LeftUpperLeg, Vec3::new(0.1, 0.9, 0.0)),
+            (BoneName::LeftLowerLeg, Vec3::new(0.1, 0.5, 0.0)),
+            (BoneName::LeftFoot, Vec3::new(0.1, 0.1, 0.0)),
+            (BoneName::RightUpperLeg, Vec3::new(-0.1, 0.9, 0.0)),
+            (BoneName::RightLowerLeg, Vec3::new(-0.1, 0.5, 0.0)),
+            (BoneName::RightFoot, Vec3::new(-0.1, 0.1, 0.0)),
+        ]);
+
+        let measurements = VrmMeasurements::from_bones(&bones, None);
+
+        assert!(
+            measurements
+                .eye_position
+                .abs_diff_eq(Vec3::new(0.0, 1.6, 0.05), 1e-5)
+        );
+        assert!((measurements.height - 1.7).abs() < 1e-5);
+        assert!((measurements.shoulder_width - 0.4).abs() < 1e-5);
+        assert!((measurements.arm_span - 1.5).abs() < 1e-5);
+        assert!((measurements.leg_length - 0.9).abs() < 1e-5);
+        assert!((measurements.hips_height - 1.0).abs() < 1e-5);
+    }
+}
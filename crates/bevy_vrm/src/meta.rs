@@ -0,0 +1,243 @@
+use bevy::prelude::*;
+use serde_vrm::vrm0::{Allow, AllowedUserName};
+
+/// Label of the [VrmMeta] sub-asset.
+pub const META_LABEL: &str = "Meta";
+/// Label of the thumbnail [Image] sub-asset.
+pub const THUMBNAIL_LABEL: &str = "Thumbnail";
+
+/// Avatar metadata, normalized across VRM 0.x and 1.0.
+///
+/// Available as the `Meta` sub-asset of a VRM, so it can be read before
+/// the avatar is spawned, such as `asset_server.load("avatar.vrm#Meta")`.
+#[derive(Asset, Clone, Debug, Default, PartialEq, Reflect)]
+pub struct VrmMeta {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub authors: Vec<String>,
+    pub contact_information: Option<String>,
+    pub references: Vec<String>,
+    pub permissions: VrmPermissions,
+    /// Name of the license, such as `CC_BY` in VRM 0.x.
+    pub license_name: Option<String>,
+    /// URL of the license, such as the VRM Public License in VRM 1.0.
+    pub license_url: Option<String>,
+    pub other_license_url: Option<String>,
+    pub other_permission_url: Option<String>,
+}
+
+/// Usage permissions of an avatar.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+pub struct VrmPermissions {
+    pub avatar: AvatarPermission,
+    pub violent_usage: bool,
+    pub sexual_usage: bool,
+    pub commercial_usage: CommercialUsage,
+    pub political_or_religious_usage: bool,
+    pub antisocial_or_hate_usage: bool,
+    pub redistribution: bool,
+    pub modification: Modification,
+    pub credit_required: bool,
+}
+
+impl Default for VrmPermissions {
+    /// The most restrictive permissions, used for anything left unspecified.
+    fn default() -> Self {
+        Self {
+            avatar: AvatarPermission::OnlyAuthor,
+            violent_usage: false,
+            sexual_usage: false,
+            commercial_usage: CommercialUsage::PersonalNonProfit,
+            political_or_religious_usage: false,
+            antisocial_or_hate_usage: false,
+            redistribution: false,
+            modification: Modification::Prohibited,
+            credit_required: true,
+        }
+    }
+}
+
+/// Who may use the avatar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum AvatarPermission {
+    #[default]
+    OnlyAuthor,
+    OnlySeparatelyLicensedPerson,
+    Everyone,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum CommercialUsage {
+    #[default]
+    PersonalNonProfit,
+    PersonalProfit,
+    Corporation,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub enum Modification {
+    #[default]
+    Prohibited,
+    AllowModification,
+    AllowModificationRedistribution,
+}
+
+impl From<&gltf_kun_vrm::vrm0::weight::Meta> for VrmMeta {
+    fn from(meta: &gltf_kun_vrm::vrm0::weight::Meta) -> Self {
+        let allowed = |allow: &Option<Allow>| matches!(allow, Some(Allow::Allow));
+
+        // VRM 0.x licenses are Creative Commons variants, or a custom license.
+        let license = meta.license_name.as_deref().unwrap_or_default();
+        let is_cc = license.starts_with("CC");
+        let modification = if license == "CC0" || (is_cc && !license.contains("ND")) {
+            Modification::AllowModificationRedistribution
+        } else {
+            Modification::Prohibited
+        };
+
+        Self {
+            name: meta.title.clone(),
+            version: meta.version.clone(),
+            authors: meta.author.iter().cloned().collect(),
+            contact_information: meta.contact_information.clone(),
+            references: meta.reference.iter().cloned().collect(),
+            permissions: VrmPermissions {
+                avatar: match meta.allowed_user_name {
+                    Some(AllowedUserName::Everyone) => AvatarPermission::Everyone,
+                    Some(AllowedUserName::ExplicitlyLicensedPerson) => {
+                        AvatarPermission::OnlySeparatelyLicensedPerson
+                    }
+                    Some(AllowedUserName::OnlyAuthor) | None => AvatarPermission::OnlyAuthor,
+                },
+                violent_usage: allowed(&meta.violent_usage_name),
+                sexual_usage: allowed(&meta.sexual_usage_name),
+                commercial_usage: if allowed(&meta.commercial_usage_name) {
+                    CommercialUsage::Corporation
+                } else {
+                    CommercialUsage::PersonalNonProfit
+                },
+                // Not covered by VRM 0.x, left to the license.
+                political_or_religious_usage: false,
+                antisocial_or_hate_usage: false,
+                redistribution: is_cc,
+                modification,
+                credit_required: is_cc && license != "CC0",
+            },
+            license_name: meta.license_name.clone(),
+            license_url: None,
+            other_license_url: meta.other_license_url.clone(),
+            other_permission_url: meta.other_permission_url.clone(),
+        }
+    }
+}
+
+impl From<&serde_vrm::vrm1::vrmc_vrm::Meta> for VrmMeta {
+    fn from(meta: &serde_vrm::vrm1::vrmc_vrm::Meta) -> Self {
+        Self {
+            name: Some(meta.name.clone()),
+            version: meta.version.clone(),
+            authors: meta.authors.clone(),
+            contact_information: meta.contact_information.clone(),
+            references: meta.reference.clone().unwrap_or_default(),
+            permissions: VrmPermissions {
+                avatar: match meta.avatar_permission.as_str() {
+                    "everyone" => AvatarPermission::Everyone,
+                    "onlySeparatelyLicensedPerson" => {
+                        AvatarPermission::OnlySeparatelyLicensedPerson
+                    }
+                    _ => AvatarPermission::OnlyAuthor,
+                },
+                violent_usage: meta.allow_excessively_violent_usage.unwrap_or_default(),
+                sexual_usage: meta.allow_excessively_sexual_usage.unwrap_or_default(),
+                commercial_usage: match meta.commercial_usage.as_deref() {
+                    Some("corporation") => CommercialUsage::Corporation,
+                    Some("personalProfit") => CommercialUsage::PersonalProfit,
+                    _ => CommercialUsage::PersonalNonProfit,
+                },
+                political_or_religious_usage: meta
+                    .allow_political_or_religious_usage
+                    .unwrap_or_default(),
+                antisocial_or_hate_usage: meta.allow_antisocial_or_hate_usage.unwrap_or_default(),
+                redistribution: meta.allow_redistribution.unwrap_or_default(),
+                modification: match meta.modification.as_deref() {
+                    Some("allowModificationRedistribution") => {
+                        Modification::AllowModificationRedistribution
+                    }
+                    Some("allowModification") => Modification::AllowModification,
+                    _ => Modification::Prohibited,
+                },
+                credit_required: meta.credit_notation.as_deref() != Some("unnecessary"),
+            },
+            license_name: None,
+            license_url: Some(meta.license_url.clone()),
+            other_license_url: meta.other_license_url.clone(),
+            other_permission_url: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_vrm0() {
+        let meta = VrmMeta::from(&gltf_kun_vrm::vrm0::weight::Meta {
+            title: Some("Avatar".to_string()),
+            author: Some("Author".to_string()),
+            allowed_user_name: Some(AllowedUserName::Everyone),
+            commercial_usage_name: Some(Allow::Allow),
+            license_name: Some("CC_BY_ND".to_string()),
+            ..default()
+        });
+
+        assert_eq!(meta.name.as_deref(), Some("Avatar"));
+        assert_eq!(meta.authors, vec!["Author".to_string()]);
+        assert_eq!(meta.permissions.avatar, AvatarPermission::Everyone);
+        assert_eq!(
+            meta.permissions.commercial_usage,
+            CommercialUsage::Corporation
+        );
+        assert!(!meta.permissions.violent_usage);
+        assert!(meta.permissions.redistribution);
+        assert!(meta.permissions.credit_required);
+        assert_eq!(meta.permissions.modification, Modification::Prohibited);
+    }
+
+    #[test]
+    fn normalize_vrm1() {
+        let meta = VrmMeta::from(&serde_vrm::vrm1::vrmc_vrm::Meta {
+            name: "Avatar".to_string(),
+            authors: vec!["Author".to_string()],
+            license_url: "https://vrm.dev/licenses/1.0/".to_string(),
+            avatar_permission: "onlySeparatelyLicensedPerson".to_string(),
+            commercial_usage: Some("personalProfit".to_string()),
+            allow_redistribution: Some(true),
+            modification: Some("allowModification".to_string()),
+            credit_notation: Some("unnecessary".to_string()),
+            ..default()
+        });
+
+        assert_eq!(meta.name.as_deref(), Some("Avatar"));
+        assert_eq!(meta.authors, vec!["Author".to_string()]);
+        assert_eq!(
+            meta.license_url.as_deref(),
+            Some("https://vrm.dev/licenses/1.0/")
+        );
+        assert_eq!(
+            meta.permissions.avatar,
+            AvatarPermission::OnlySeparatelyLicensedPerson
+        );
+        assert_eq!(
+            meta.permissions.commercial_usage,
+            CommercialUsage::PersonalProfit
+        );
+        assert!(!meta.permissions.violent_usage);
+        assert!(meta.permissions.redistribution);
+        assert!(!meta.permissions.credit_required);
+        assert_eq!(
+            meta.permissions.modification,
+            Modification::AllowModification
+        );
+    }
+}
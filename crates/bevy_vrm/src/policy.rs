@@ -0,0 +1,302 @@
+use std::{
+    fmt::Display,
+    sync::{Arc, RwLock},
+};
+
+use bevy::prelude::*;
+
+use crate::meta::{AvatarPermission, CommercialUsage, Modification, VrmMeta, VrmPermissions};
+
+/// Decides whether an avatar may be loaded, from its metadata.
+///
+/// Consulted by the [VrmLoader](crate::loader::VrmLoader) after import.
+/// Insert a [VrmLoaderPolicy] to use one.
+pub trait VrmPolicy: Send + Sync + 'static {
+    fn evaluate(&self, meta: &VrmMeta) -> PolicyDecision;
+}
+
+/// Policy used by the [VrmLoader](crate::loader::VrmLoader).
+#[derive(Resource, Clone)]
+pub struct VrmLoaderPolicy(pub Arc<dyn VrmPolicy>);
+
+impl VrmLoaderPolicy {
+    pub fn new(policy: impl VrmPolicy) -> Self {
+        Self(Arc::new(policy))
+    }
+}
+
+/// Policy read by the [VrmLoader](crate::loader::VrmLoader) as each load finishes,
+/// synced from the [VrmLoaderPolicy] resource at the start of every frame.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct SharedPolicy(Arc<RwLock<Option<Arc<dyn VrmPolicy>>>>);
+
+impl SharedPolicy {
+    pub(crate) fn get(&self) -> Option<Arc<dyn VrmPolicy>> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub(crate) fn set(&self, policy: Option<Arc<dyn VrmPolicy>>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+}
+
+pub(crate) fn sync_loader_policy(policy: Option<Res<VrmLoaderPolicy>>, shared: Res<SharedPolicy>) {
+    match policy {
+        Some(policy) if policy.is_changed() => shared.set(Some(policy.0.clone())),
+        Some(_) => {}
+        None => {
+            if shared.get().is_some() {
+                shared.set(None);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PolicyDecision {
+    Allow,
+    /// Load the avatar, recording the violations on the [Vrm](crate::loader::Vrm).
+    Flag(Vec<PermissionViolation>),
+    /// Fail the load with [VrmError::PermissionDenied](crate::loader::VrmError::PermissionDenied).
+    Deny(Vec<PermissionViolation>),
+}
+
+/// A use of the avatar its license does not permit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+pub enum PermissionViolation {
+    /// The user is not permitted to use the avatar.
+    Avatar(AvatarPermission),
+    ViolentUsage,
+    SexualUsage,
+    CommercialUsage(CommercialUsage),
+    PoliticalOrReligiousUsage,
+    AntisocialOrHateUsage,
+    Redistribution,
+    Modification(Modification),
+}
+
+impl Display for PermissionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Avatar(permission) => write!(f, "avatar use is limited to {:?}", permission),
+            Self::ViolentUsage => write!(f, "violent usage is not allowed"),
+            Self::SexualUsage => write!(f, "sexual usage is not allowed"),
+            Self::CommercialUsage(usage) => {
+                write!(f, "commercial usage is limited to {:?}", usage)
+            }
+            Self::PoliticalOrReligiousUsage => {
+                write!(f, "political or religious usage is not allowed")
+            }
+            Self::AntisocialOrHateUsage => write!(f, "antisocial or hate usage is not allowed"),
+            Self::Redistribution => write!(f, "redistribution is not allowed"),
+            Self::Modification(modification) => {
+                write!(f, "modification is limited to {:?}", modification)
+            }
+        }
+    }
+}
+
+/// How the app uses avatars.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VrmUsage {
+    /// Who is using the avatar. [AvatarPermission::Everyone] for any user,
+    /// such as on a platform hosting user uploads.
+    pub user: AvatarPermission,
+    pub violent_usage: bool,
+    pub sexual_usage: bool,
+    pub commercial_usage: CommercialUsage,
+    pub political_or_religious_usage: bool,
+    pub antisocial_or_hate_usage: bool,
+    pub redistribution: bool,
+    pub modification: Modification,
+}
+
+impl Default for VrmUsage {
+    /// Personal, non-profit use by the avatar's author.
+    fn default() -> Self {
+        Self {
+            user: AvatarPermission::OnlyAuthor,
+            violent_usage: false,
+            sexual_usage: false,
+            commercial_usage: CommercialUsage::PersonalNonProfit,
+            political_or_religious_usage: false,
+            antisocial_or_hate_usage: false,
+            redistribution: false,
+            modification: Modification::Prohibited,
+        }
+    }
+}
+
+impl VrmUsage {
+    /// Each way this usage exceeds the given permissions.
+    pub fn violations(&self, permissions: &VrmPermissions) -> Vec<PermissionViolation> {
+        let mut violations = Vec::new();
+
+        if avatar_rank(permissions.avatar) < avatar_rank(self.user) {
+            violations.push(PermissionViolation::Avatar(permissions.avatar));
+        }
+
+        let flags = [
+            (
+                self.violent_usage,
+                permissions.violent_usage,
+                PermissionViolation::ViolentUsage,
+            ),
+            (
+                self.sexual_usage,
+                permissions.sexual_usage,
+                PermissionViolation::SexualUsage,
+            ),
+            (
+                self.political_or_religious_usage,
+                permissions.political_or_religious_usage,
+                PermissionViolation::PoliticalOrReligiousUsage,
+            ),
+            (
+                self.antisocial_or_hate_usage,
+                permissions.antisocial_or_hate_usage,
+                PermissionViolation::AntisocialOrHateUsage,
+            ),
+            (
+                self.redistribution,
+                permissions.redistribution,
+                PermissionViolation::Redistribution,
+            ),
+        ];
+
+        for (used, allowed, violation) in flags {
+            if used && !allowed {
+                violations.push(violation);
+            }
+        }
+
+        if commercial_rank(permissions.commercial_usage) < commercial_rank(self.commercial_usage) {
+            violations.push(PermissionViolation::CommercialUsage(
+                permissions.commercial_usage,
+            ));
+        }
+
+        if modification_rank(permissions.modification) < modification_rank(self.modification) {
+            violations.push(PermissionViolation::Modification(permissions.modification));
+        }
+
+        violations
+    }
+}
+
+fn avatar_rank(permission: AvatarPermission) -> u8 {
+    match permission {
+        AvatarPermission::OnlyAuthor => 0,
+        AvatarPermission::OnlySeparatelyLicensedPerson => 1,
+        AvatarPermission::Everyone => 2,
+    }
+}
+
+fn commercial_rank(usage: CommercialUsage) -> u8 {
+    match usage {
+        CommercialUsage::PersonalNonProfit => 0,
+        CommercialUsage::PersonalProfit => 1,
+        CommercialUsage::Corporation => 2,
+    }
+}
+
+fn modification_rank(modification: Modification) -> u8 {
+    match modification {
+        Modification::Prohibited => 0,
+        Modification::AllowModification => 1,
+        Modification::AllowModificationRedistribution => 2,
+    }
+}
+
+/// Checks avatars against a declared [VrmUsage].
+#[derive(Clone, Debug, Default)]
+pub struct UsagePolicy {
+    pub usage: VrmUsage,
+    /// Fail to load avatars with violations, instead of flagging them.
+    pub deny: bool,
+}
+
+impl VrmPolicy for UsagePolicy {
+    fn evaluate(&self, meta: &VrmMeta) -> PolicyDecision {
+        let violations = self.usage.violations(&meta.permissions);
+
+        if violations.is_empty() {
+            PolicyDecision::Allow
+        } else if self.deny {
+            PolicyDecision::Deny(violations)
+        } else {
+            PolicyDecision::Flag(violations)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::block_on;
+    use gltf_kun::{graph::Graph, io::format::glb::GlbImport};
+    use gltf_kun_vrm::vrm0::Vrm;
+
+    use super::*;
+    use crate::extensions::read_meta;
+
+    #[test]
+    fn usage_violations() {
+        let permissions = VrmPermissions {
+            avatar: AvatarPermission::OnlySeparatelyLicensedPerson,
+            commercial_usage: CommercialUsage::PersonalProfit,
+            ..default()
+        };
+
+        let usage = VrmUsage {
+            user: AvatarPermission::Everyone,
+            commercial_usage: CommercialUsage::Corporation,
+            ..default()
+        };
+
+        assert_eq!(
+            usage.violations(&permissions),
+            vec![
+                PermissionViolation::Avatar(AvatarPermission::OnlySeparatelyLicensedPerson),
+                PermissionViolation::CommercialUsage(CommercialUsage::PersonalProfit),
+            ]
+        );
+
+        assert!(VrmUsage::default().violations(&permissions).is_empty());
+    }
+
+    #[test]
+    fn catbot_permissions() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../assets/catbot.vrm");
+        let bytes = std::fs::read(path).unwrap();
+
+        let mut graph = Graph::default();
+        let doc = block_on(GlbImport::<Vrm>::import_slice(&mut graph, &bytes)).unwrap();
+        let meta = read_meta(&graph, &doc).unwrap();
+
+        // Catbot allows violent and sexual usage, but not commercial usage.
+        let policy = UsagePolicy {
+            usage: VrmUsage {
+                user: AvatarPermission::Everyone,
+                violent_usage: true,
+                sexual_usage: true,
+                ..default()
+            },
+            deny: true,
+        };
+        assert_eq!(policy.evaluate(&meta), PolicyDecision::Allow);
+
+        let policy = UsagePolicy {
+            usage: VrmUsage {
+                commercial_usage: CommercialUsage::Corporation,
+                ..policy.usage
+            },
+            deny: true,
+        };
+        assert_eq!(
+            policy.evaluate(&meta),
+            PolicyDecision::Deny(vec![PermissionViolation::CommercialUsage(
+                CommercialUsage::PersonalNonProfit
+            )])
+        );
+    }
+}
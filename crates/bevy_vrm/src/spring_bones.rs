@@ -3,6 +3,8 @@ use bevy::{
     prelude::*,
 };
 
+use crate::VrmSystems;
+
 #[derive(Component, Default, Reflect)]
 #[reflect(Component, MapEntities)]
 pub struct SpringBones(pub Vec<SpringBone>);
@@ -52,9 +54,10 @@ impl Plugin for SpringBonePlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<SpringBoneLogicState>()
             .register_type::<SpringBones>()
+            .add_systems(Update, remap_spring_bone_entities)
             .add_systems(
-                Update,
-                (remap_spring_bone_entities, do_springbone_logic).chain(),
+                PostUpdate,
+                do_springbone_logic.in_set(VrmSystems::SpringBones),
             );
     }
 }
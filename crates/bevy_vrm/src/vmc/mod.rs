@@ -0,0 +1,57 @@
+//! [VMC protocol](https://protocol.vmc.info/english) support, for streaming avatar motion over OSC.
+//!
+//! VMC uses Unity's left-handed coordinates, which are converted to and from the
+//! VRM 0.x glTF convention (Z negated).
+
+use bevy::prelude::*;
+use serde::{Deserialize, de::IntoDeserializer};
+use serde_vrm::vrm0::BoneName;
+
+pub mod receiver;
+pub mod sender;
+
+pub use receiver::{VmcReceiver, VmcReceiverPlugin, VmcReceiverTarget};
+pub use sender::{VmcSender, VmcSenderPlugin};
+
+/// Default port VMC performers send to.
+pub const DEFAULT_PORT: u16 = 39539;
+
+pub const ADDR_OK: &str = "/VMC/Ext/OK";
+pub const ADDR_TIME: &str = "/VMC/Ext/T";
+pub const ADDR_ROOT_POS: &str = "/VMC/Ext/Root/Pos";
+pub const ADDR_BONE_POS: &str = "/VMC/Ext/Bone/Pos";
+pub const ADDR_BLEND_VAL: &str = "/VMC/Ext/Blend/Val";
+pub const ADDR_BLEND_APPLY: &str = "/VMC/Ext/Blend/Apply";
+
+/// Parses a Unity `HumanBodyBones` name, as used by VMC.
+pub fn bone_from_vmc(name: &str) -> Option<BoneName> {
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    let camel_case = first.to_ascii_lowercase().to_string() + chars.as_str();
+
+    BoneName::deserialize(camel_case.as_str().into_deserializer())
+        .map_err(|_: serde::de::value::Error| ())
+        .ok()
+}
+
+/// Unity `HumanBodyBones` name of a bone, as used by VMC.
+pub fn bone_to_vmc(bone: BoneName) -> String {
+    // Variant names match Unity's naming.
+    format!("{:?}", bone)
+}
+
+pub fn position_from_unity(p: Vec3) -> Vec3 {
+    Vec3::new(p.x, p.y, -p.z)
+}
+
+pub fn rotation_from_unity(q: Quat) -> Quat {
+    Quat::from_xyzw(-q.x, -q.y, q.z, q.w)
+}
+
+pub fn position_to_unity(p: Vec3) -> Vec3 {
+    position_from_unity(p)
+}
+
+pub fn rotation_to_unity(q: Quat) -> Quat {
+    rotation_from_unity(q)
+}
@@ -0,0 +1,355 @@
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use bevy::prelude::*;
+use rosc::{OscMessage, OscPacket, OscType};
+use serde_vrm::vrm0::BoneName;
+
+use crate::expressions::VrmExpressions;
+
+use super::{
+    ADDR_BLEND_APPLY, ADDR_BLEND_VAL, ADDR_BONE_POS, ADDR_ROOT_POS, DEFAULT_PORT, bone_from_vmc,
+    position_from_unity, rotation_from_unity,
+};
+
+const MAX_PACKET_SIZE: usize = 65_507;
+
+/// Listens for VMC packets and applies them to each [VmcReceiverTarget].
+pub struct VmcReceiverPlugin {
+    pub address: SocketAddr,
+}
+
+impl Default for VmcReceiverPlugin {
+    fn default() -> Self {
+        Self {
+            address: SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT)),
+        }
+    }
+}
+
+impl Plugin for VmcReceiverPlugin {
+    fn build(&self, app: &mut App) {
+        match VmcReceiver::bind(self.address) {
+            Ok(receiver) => {
+                app.insert_resource(receiver);
+            }
+            Err(e) => {
+                error!("Failed to bind VMC receiver to {}: {}", self.address, e);
+                return;
+            }
+        }
+
+        app.add_systems(Update, (receive_vmc, apply_vmc_pose).chain());
+    }
+}
+
+/// Marks an avatar to be driven by the [VmcReceiver].
+#[derive(Component, Clone, Debug)]
+pub struct VmcReceiverTarget {
+    /// Apply `/VMC/Ext/Root/Pos` to the avatar's [Transform].
+    pub apply_root: bool,
+}
+
+impl Default for VmcReceiverTarget {
+    fn default() -> Self {
+        Self { apply_root: true }
+    }
+}
+
+/// Latest pose received over VMC.
+#[derive(Clone, Debug, Default)]
+pub struct VmcPose {
+    pub root: Option<Transform>,
+    /// Local bone transforms.
+    pub bones: HashMap<BoneName, Transform>,
+    /// Blend shape weights, updated on each `/VMC/Ext/Blend/Apply`.
+    pub blend_shapes: HashMap<String, f32>,
+    pending_blend_shapes: HashMap<String, f32>,
+}
+
+impl VmcPose {
+    pub fn handle_packet(&mut self, packet: OscPacket) {
+        match packet {
+            OscPacket::Message(message) => self.handle_message(message),
+            OscPacket::Bundle(bundle) => {
+                for packet in bundle.content {
+                    self.handle_packet(packet);
+                }
+            }
+        }
+    }
+
+    fn handle_message(&mut self, message: OscMessage) {
+        match message.addr.as_str() {
+            ADDR_ROOT_POS => {
+                if let Some((_, transform)) = read_transform(&message.args) {
+                    self.root = Some(transform);
+                }
+            }
+            ADDR_BONE_POS => {
+                let Some((name, transform)) = read_transform(&message.args) else {
+                    return;
+                };
+                if let Some(bone) = bone_from_vmc(name) {
+                    self.bones.insert(bone, transform);
+                }
+            }
+            ADDR_BLEND_VAL => {
+                if let [OscType::String(name), OscType::Float(value)] = message.args.as_slice() {
+                    self.pending_blend_shapes.insert(name.clone(), *value);
+                }
+            }
+            ADDR_BLEND_APPLY => {
+                self.blend_shapes.extend(self.pending_blend_shapes.drain());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads a `(name, p.x, p.y, p.z, q.x, q.y, q.z, q.w)` message,
+/// converting from Unity coordinates.
+fn read_transform(args: &[OscType]) -> Option<(&str, Transform)> {
+    let [OscType::String(name), rest @ ..] = args else {
+        return None;
+    };
+
+    let mut values = [0.0; 7];
+    for (value, arg) in values.iter_mut().zip(rest) {
+        *value = arg.clone().float()?;
+    }
+    if rest.len() < values.len() {
+        return None;
+    }
+
+    let [px, py, pz, qx, qy, qz, qw] = values;
+
+    Some((
+        name,
+        Transform {
+            translation: position_from_unity(Vec3::new(px, py, pz)),
+            rotation: rotation_from_unity(Quat::from_xyzw(qx, qy, qz, qw)).normalize(),
+            ..default()
+        },
+    ))
+}
+
+#[derive(Resource)]
+pub struct VmcReceiver {
+    socket: UdpSocket,
+    buf: Vec<u8>,
+    pub pose: VmcPose,
+}
+
+impl VmcReceiver {
+    pub fn bind(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            buf: vec![0; MAX_PACKET_SIZE],
+            pose: VmcPose::default(),
+        })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Reads all pending packets without blocking, returning the number read.
+    pub fn receive(&mut self) -> usize {
+        let mut count = 0;
+
+        loop {
+            let len = match self.socket.recv(&mut self.buf) {
+                Ok(len) => len,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    warn!("Failed to receive VMC packet: {}", e);
+                    break;
+                }
+            };
+
+            match rosc::decoder::decode_udp(&self.buf[..len]) {
+                Ok((_, packet)) => {
+                    self.pose.handle_packet(packet);
+                    count += 1;
+                }
+                Err(e) => {
+                    warn!("Failed to decode VMC packet: {:?}", e);
+                }
+            }
+        }
+
+        count
+    }
+}
+
+fn receive_vmc(mut receiver: ResMut<VmcReceiver>) {
+    if receiver.bypass_change_detection().receive() > 0 {
+        receiver.set_changed();
+    }
+}
+
+fn apply_vmc_pose(
+    bones: Query<&BoneName>,
+    children: Query<&Children>,
+    mut expressions: Query<&mut VrmExpressions>,
+    mut targets: Query<(Entity, &VmcReceiverTarget)>,
+    mut transforms: Query<&mut Transform>,
+    receiver: Res<VmcReceiver>,
+) {
+    if !receiver.is_changed() {
+        return;
+    }
+
+    let pose = &receiver.pose;
+
+    for (entity, target) in targets.iter_mut() {
+        if target.apply_root
+            && let (Some(root), Ok(mut transform)) = (pose.root, transforms.get_mut(entity))
+        {
+            transform.translation = root.translation;
+            transform.rotation = root.rotation;
+        }
+
+        for child in children.iter_descendants(entity) {
+            if let Ok(bone) = bones.get(child)
+                && let (Some(bone_pose), Ok(mut transform)) =
+                    (pose.bones.get(bone), transforms.get_mut(child))
+            {
+                transform.rotation = bone_pose.rotation;
+
+                if *bone == BoneName::Hips {
+                    transform.translation = bone_pose.translation;
+                }
+            }
+
+            if let Ok(mut expressions) = expressions.get_mut(child) {
+                for (name, value) in pose.blend_shapes.iter() {
+                    let changed = expressions
+                        .get(name)
+                        .is_some_and(|expression| expression.weight != *value);
+
+                    if changed {
+                        expressions.set_weight(name, *value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use rosc::{OscBundle, OscTime};
+
+    use super::*;
+
+    fn message(addr: &str, args: Vec<OscType>) -> OscPacket {
+        OscPacket::Message(OscMessage {
+            addr: addr.to_string(),
+            args,
+        })
+    }
+
+    #[test]
+    fn receive_udp() {
+        let mut receiver = VmcReceiver::bind("127.0.0.1:0").unwrap();
+        let address = receiver.local_addr().unwrap();
+
+        let rotation = Quat::from_rotation_y(0.5);
+
+        let packet = OscPacket::Bundle(OscBundle {
+            timetag: OscTime::from((0, 1)),
+            content: vec![
+                message(
+                    ADDR_ROOT_POS,
+                    vec![
+                        OscType::String("root".to_string()),
+                        OscType::Float(1.0),
+                        OscType::Float(2.0),
+                        OscType::Float(3.0),
+                        OscType::Float(0.0),
+                        OscType::Float(0.0),
+                        OscType::Float(0.0),
+                        OscType::Float(1.0),
+                    ],
+                ),
+                message(
+                    ADDR_BONE_POS,
+                    vec![
+                        OscType::String("LeftUpperArm".to_string()),
+                        OscType::Float(0.0),
+                        OscType::Float(0.0),
+                        OscType::Float(0.0),
+                        OscType::Float(rotation.x),
+                        OscType::Float(rotation.y),
+                        OscType::Float(rotation.z),
+                        OscType::Float(rotation.w),
+                    ],
+                ),
+                message(
+                    ADDR_BLEND_VAL,
+                    vec![OscType::String("Blink".to_string()), OscType::Float(0.75)],
+                ),
+            ],
+        });
+
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender
+            .send_to(&rosc::encoder::encode(&packet).unwrap(), address)
+            .unwrap();
+
+        for _ in 0..100 {
+            if receiver.receive() > 0 {
+                break;
+            }
+            sleep(Duration::from_millis(10));
+        }
+
+        let root = receiver.pose.root.unwrap();
+        assert_eq!(root.translation, Vec3::new(1.0, 2.0, -3.0));
+
+        let arm = receiver.pose.bones[&BoneName::LeftUpperArm];
+        assert!(
+            arm.rotation
+                .abs_diff_eq(rotation_from_unity(rotation), 1e-6)
+        );
+
+        // Blend shapes wait for an apply message.
+        assert!(receiver.pose.blend_shapes.is_empty());
+
+        sender
+            .send_to(
+                &rosc::encoder::encode(&message(ADDR_BLEND_APPLY, Vec::new())).unwrap(),
+                address,
+            )
+            .unwrap();
+
+        for _ in 0..100 {
+            if receiver.receive() > 0 {
+                break;
+            }
+            sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(receiver.pose.blend_shapes["Blink"], 0.75);
+    }
+
+    #[test]
+    fn bone_names() {
+        assert_eq!(bone_from_vmc("Hips"), Some(BoneName::Hips));
+        assert_eq!(
+            bone_from_vmc("RightLittleDistal"),
+            Some(BoneName::RightLittleDistal)
+        );
+        assert_eq!(bone_from_vmc("LastBone"), None);
+    }
+}
@@ -0,0 +1,208 @@
+use std::net::{SocketAddr, UdpSocket};
+
+use bevy::prelude::*;
+use rosc::{OscBundle, OscMessage, OscPacket, OscTime, OscType};
+use serde_vrm::vrm0::BoneName;
+
+use crate::expressions::VrmExpressions;
+
+use super::{
+    ADDR_BLEND_APPLY, ADDR_BLEND_VAL, ADDR_BONE_POS, ADDR_OK, ADDR_ROOT_POS, ADDR_TIME,
+    DEFAULT_PORT, bone_to_vmc, position_to_unity, rotation_to_unity,
+};
+
+/// Broadcasts the pose of each [VmcSender] avatar over VMC.
+#[derive(Default)]
+pub struct VmcSenderPlugin;
+
+impl Plugin for VmcSenderPlugin {
+    fn build(&self, app: &mut App) {
+        match UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], 0))) {
+            Ok(socket) => {
+                app.insert_resource(VmcSenderSocket(socket));
+            }
+            Err(e) => {
+                error!("Failed to bind VMC sender socket: {}", e);
+                return;
+            }
+        }
+
+        // Run last, so the pose includes animation and any other bone updates.
+        app.add_systems(Last, send_vmc);
+    }
+}
+
+#[derive(Resource)]
+pub struct VmcSenderSocket(pub UdpSocket);
+
+/// Streams this avatar's pose and expressions over VMC.
+#[derive(Component, Clone, Debug)]
+pub struct VmcSender {
+    pub target: SocketAddr,
+    /// Packets sent per second.
+    pub send_rate: f32,
+    elapsed: f32,
+}
+
+impl VmcSender {
+    pub fn new(target: SocketAddr, send_rate: f32) -> Self {
+        Self {
+            target,
+            send_rate,
+            elapsed: f32::INFINITY,
+        }
+    }
+}
+
+impl Default for VmcSender {
+    fn default() -> Self {
+        Self::new(SocketAddr::from(([127, 0, 0, 1], DEFAULT_PORT)), 60.0)
+    }
+}
+
+fn send_vmc(
+    bones: Query<(&BoneName, &Transform)>,
+    children: Query<&Children>,
+    expressions: Query<&VrmExpressions>,
+    mut senders: Query<(Entity, &Transform, &mut VmcSender)>,
+    socket: Res<VmcSenderSocket>,
+    time: Res<Time>,
+) {
+    for (entity, root, mut sender) in senders.iter_mut() {
+        sender.elapsed += time.delta_secs();
+
+        if sender.elapsed < 1.0 / sender.send_rate {
+            continue;
+        }
+        sender.elapsed = 0.0;
+
+        let bone_transforms = children
+            .iter_descendants(entity)
+            .filter_map(|child| bones.get(child).ok())
+            .map(|(bone, transform)| (*bone, *transform));
+
+        let expression_weights = children
+            .iter_descendants(entity)
+            .filter_map(|child| expressions.get(child).ok())
+            .flat_map(|expressions| expressions.0.iter())
+            .map(|expression| (expression.name.clone(), expression.weight));
+
+        let packet = build_packet(
+            time.elapsed_secs(),
+            root,
+            bone_transforms,
+            expression_weights,
+        );
+
+        let bytes = match rosc::encoder::encode(&packet) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to encode VMC packet: {:?}", e);
+                continue;
+            }
+        };
+
+        if let Err(e) = socket.0.send_to(&bytes, sender.target) {
+            warn!("Failed to send VMC packet to {}: {}", sender.target, e);
+        }
+    }
+}
+
+/// Builds a VMC bundle for a single frame, converting to Unity coordinates.
+pub fn build_packet(
+    time: f32,
+    root: &Transform,
+    bones: impl Iterator<Item = (BoneName, Transform)>,
+    expressions: impl Iterator<Item = (String, f32)>,
+) -> OscPacket {
+    let mut content = vec![
+        message(ADDR_OK, vec![OscType::Int(1)]),
+        message(ADDR_TIME, vec![OscType::Float(time)]),
+        transform_message(ADDR_ROOT_POS, "root".to_string(), root),
+    ];
+
+    for (bone, transform) in bones {
+        content.push(transform_message(
+            ADDR_BONE_POS,
+            bone_to_vmc(bone),
+            &transform,
+        ));
+    }
+
+    for (name, weight) in expressions {
+        content.push(message(
+            ADDR_BLEND_VAL,
+            vec![OscType::String(name), OscType::Float(weight)],
+        ));
+    }
+
+    content.push(message(ADDR_BLEND_APPLY, Vec::new()));
+
+    OscPacket::Bundle(OscBundle {
+        timetag: OscTime::from((0, 1)),
+        content,
+    })
+}
+
+fn message(addr: &str, args: Vec<OscType>) -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: addr.to_string(),
+        args,
+    })
+}
+
+fn transform_message(addr: &str, name: String, transform: &Transform) -> OscPacket {
+    let p = position_to_unity(transform.translation);
+    let q = rotation_to_unity(transform.rotation);
+
+    message(
+        addr,
+        vec![
+            OscType::String(name),
+            OscType::Float(p.x),
+            OscType::Float(p.y),
+            OscType::Float(p.z),
+            OscType::Float(q.x),
+            OscType::Float(q.y),
+            OscType::Float(q.z),
+            OscType::Float(q.w),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vmc::receiver::VmcPose;
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let root = Transform::from_xyz(1.0, 0.0, 2.0).with_rotation(Quat::from_rotation_y(1.0));
+        let head = Transform::from_rotation(Quat::from_rotation_x(0.3));
+
+        let packet = build_packet(
+            0.0,
+            &root,
+            [(BoneName::Head, head)].into_iter(),
+            [("Joy".to_string(), 0.5)].into_iter(),
+        );
+
+        let mut pose = VmcPose::default();
+        pose.handle_packet(packet);
+
+        let received_root = pose.root.unwrap();
+        assert!(
+            received_root
+                .translation
+                .abs_diff_eq(root.translation, 1e-6)
+        );
+        assert!(received_root.rotation.abs_diff_eq(root.rotation, 1e-6));
+        assert!(
+            pose.bones[&BoneName::Head]
+                .rotation
+                .abs_diff_eq(head.rotation, 1e-6)
+        );
+        assert_eq!(pose.blend_shapes["Joy"], 0.5);
+    }
+}
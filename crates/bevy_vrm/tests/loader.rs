@@ -0,0 +1,275 @@
+use std::path::{Path, PathBuf};
+
+use bevy::{asset::LoadState, prelude::*, render::mesh::skinning::SkinnedMeshInverseBindposes};
+use bevy_gltf_kun::import::gltf::{GltfKun, mesh::GltfMesh, node::GltfNode, scene::GltfScene};
+use bevy_vrm::{
+    loader::{Vrm, VrmImportError, VrmLoader},
+    meta::{AvatarPermission, VrmMeta},
+    mtoon::{MtoonMaterial, VrmOutlineMode},
+};
+use serde_json::{Value, json};
+
+/// Packs a glTF document and its binary buffer into a GLB.
+fn glb(json: &Value, bin: &[u8]) -> Vec<u8> {
+    let mut json = serde_json::to_vec(json).unwrap();
+    while !json.len().is_multiple_of(4) {
+        json.push(b' ');
+    }
+
+    let mut bin = bin.to_vec();
+    while !bin.len().is_multiple_of(4) {
+        bin.push(0);
+    }
+
+    let length = 12 + 8 + json.len() + 8 + bin.len();
+
+    let mut bytes = Vec::with_capacity(length);
+    bytes.extend_from_slice(b"glTF");
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&(length as u32).to_le_bytes());
+    bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"JSON");
+    bytes.extend_from_slice(&json);
+    bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(b"BIN\0");
+    bytes.extend_from_slice(&bin);
+    bytes
+}
+
+/// A single triangle using material 0, with the given materials, textures, and extensions.
+fn triangle(materials: Value, textures: Value, extensions: Value) -> Vec<u8> {
+    let positions = [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+    let bin = positions
+        .iter()
+        .flatten()
+        .flat_map(|v| v.to_le_bytes())
+        .collect::<Vec<_>>();
+
+    let json = json!({
+        "asset": { "version": "2.0" },
+        "buffers": [{ "byteLength": bin.len() }],
+        "bufferViews": [{ "buffer": 0, "byteLength": bin.len() }],
+        "accessors": [{
+            "bufferView": 0,
+            "componentType": 5126,
+            "count": 3,
+            "type": "VEC3",
+            "min": [0.0, 0.0, 0.0],
+            "max": [1.0, 1.0, 0.0],
+        }],
+        "materials": materials,
+        "textures": textures,
+        "meshes": [{
+            "primitives": [{ "attributes": { "POSITION": 0 }, "material": 0 }],
+        }],
+        "nodes": [{ "name": "Body", "mesh": 0 }],
+        "scenes": [{ "nodes": [0] }],
+        "scene": 0,
+        "extensions": extensions,
+    });
+
+    glb(&json, &bin)
+}
+
+fn app(dir: &Path) -> App {
+    let mut app = App::new();
+
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: dir.to_string_lossy().to_string(),
+            ..default()
+        },
+    ))
+    .init_asset::<AnimationClip>()
+    .init_asset::<GltfKun>()
+    .init_asset::<GltfMesh>()
+    .init_asset::<GltfNode>()
+    .init_asset::<GltfScene>()
+    .init_asset::<Image>()
+    .init_asset::<Mesh>()
+    .init_asset::<MtoonMaterial>()
+    .init_asset::<Scene>()
+    .init_asset::<SkinnedMeshInverseBindposes>()
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Vrm>()
+    .init_asset::<VrmMeta>();
+
+    app
+}
+
+/// Writes the file to a fresh asset directory.
+fn asset_dir(name: &str, bytes: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bevy_vrm_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("avatar.vrm"), bytes).unwrap();
+    dir
+}
+
+fn load(app: &mut App) -> Handle<Vrm> {
+    app.init_asset_loader::<VrmLoader>();
+
+    let handle = app.world().resource::<AssetServer>().load("avatar.vrm");
+
+    for _ in 0..1000 {
+        app.update();
+
+        match app.world().resource::<AssetServer>().load_state(&handle) {
+            LoadState::Loaded | LoadState::Failed(_) => break,
+            _ => std::thread::sleep(std::time::Duration::from_millis(1)),
+        }
+    }
+
+    handle
+}
+
+#[test]
+fn vrm1_mtoon_material() {
+    let materials = json!([{
+        "pbrMetallicRoughness": { "baseColorFactor": [1.0, 0.0, 0.0, 1.0] },
+        "extensions": {
+            "VRMC_materials_mtoon": {
+                "specVersion": "1.0",
+                "shadeColorFactor": [0.5, 0.25, 0.0],
+                "outlineWidthMode": "worldCoordinates",
+                "outlineWidthFactor": 0.01,
+                "outlineWidthMultiplyTexture": { "index": 1 },
+                "uvAnimationMaskTexture": { "index": 0 },
+                "matcapTexture": { "index": 5 },
+            },
+        },
+    }]);
+
+    // Textures without an image keep their slot, without decoding anything.
+    let textures = json!([{}, {}]);
+
+    let dir = asset_dir("mtoon", &triangle(materials, textures, json!({})));
+    let mut app = app(&dir);
+    let handle = load(&mut app);
+
+    let world = app.world();
+    let server = world.resource::<AssetServer>();
+
+    let vrm = world
+        .resource::<Assets<Vrm>>()
+        .get(&handle)
+        .expect("VRM should load");
+
+    assert!(
+        vrm.warnings
+            .contains(&VrmImportError::InvalidTextureIndex(0))
+    );
+
+    let (_, mtoon) = world
+        .resource::<Assets<MtoonMaterial>>()
+        .iter()
+        .next()
+        .expect("MToon material should be imported");
+
+    assert_eq!(mtoon.base_color, LinearRgba::rgb(1.0, 0.0, 0.0).into());
+    assert_eq!(mtoon.shade_factor, LinearRgba::rgb(0.5, 0.25, 0.0).into());
+    assert_eq!(mtoon.outline_mode, VrmOutlineMode::World);
+    assert_eq!(mtoon.outline_width, 0.01);
+
+    let path = |handle: &Option<Handle<Image>>| {
+        handle
+            .as_ref()
+            .and_then(|handle| server.get_path(handle))
+            .and_then(|path| path.label().map(str::to_string))
+    };
+
+    assert_eq!(
+        path(&mtoon.uv_animation_mask_texture).as_deref(),
+        Some("Texture0")
+    );
+    assert_eq!(
+        path(&mtoon.outline_width_multiply_texture).as_deref(),
+        Some("Texture1")
+    );
+    assert_eq!(mtoon.matcap_texture, None);
+
+    // The primitive uses the MToon material instead of its glTF material.
+    let gltf_scene = world
+        .resource::<Assets<GltfScene>>()
+        .get(vrm.gltf.default_scene.as_ref().unwrap())
+        .unwrap();
+    let scene = world
+        .resource::<Assets<Scene>>()
+        .get(&gltf_scene.scene)
+        .unwrap();
+    let mut query = scene
+        .world
+        .try_query::<&MeshMaterial3d<MtoonMaterial>>()
+        .unwrap();
+    assert_eq!(query.iter(&scene.world).count(), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn vrm1_meta() {
+    let extensions = json!({
+        "VRMC_vrm": {
+            "specVersion": "1.0",
+            "meta": {
+                "name": "Avatar",
+                "authors": ["Author"],
+                "licenseUrl": "https://vrm.dev/licenses/1.0/",
+                "avatarPermission": "everyone",
+            },
+            "humanoid": { "humanBones": {} },
+        },
+    });
+
+    let dir = asset_dir("meta", &triangle(json!([{}]), json!([]), extensions));
+    let mut app = app(&dir);
+    let handle = load(&mut app);
+
+    let world = app.world();
+    let vrm = world
+        .resource::<Assets<Vrm>>()
+        .get(&handle)
+        .expect("VRM should load");
+
+    // VRM 1.0 files are not missing the VRM 0.x extension.
+    assert!(vrm.warnings.is_empty(), "{:?}", vrm.warnings);
+
+    let meta = world.resource::<Assets<VrmMeta>>().get(&vrm.meta).unwrap();
+    assert_eq!(meta.name.as_deref(), Some("Avatar"));
+    assert_eq!(meta.authors, vec!["Author".to_string()]);
+    assert_eq!(meta.permissions.avatar, AvatarPermission::Everyone);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn vrm0_thumbnail() {
+    let bytes = std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../assets/catbot.vrm"
+    ))
+    .unwrap();
+
+    let dir = asset_dir("thumbnail", &bytes);
+    let mut app = app(&dir);
+    let handle = load(&mut app);
+
+    let world = app.world();
+    let server = world.resource::<AssetServer>();
+
+    let vrm = world
+        .resource::<Assets<Vrm>>()
+        .get(&handle)
+        .expect("VRM should load");
+
+    let thumbnail = server.load::<Image>("avatar.vrm#Thumbnail");
+    assert_eq!(vrm.thumbnail.as_ref(), Some(&thumbnail));
+
+    let image = world
+        .resource::<Assets<Image>>()
+        .get(&thumbnail)
+        .expect("Thumbnail should load");
+    assert!(image.width() > 0 && image.height() > 0);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
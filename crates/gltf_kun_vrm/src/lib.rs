@@ -2,3 +2,4 @@
 
 pub mod vrm0;
 pub mod vrm1;
+pub mod weight;
@@ -1,12 +1,15 @@
 use std::fmt::Display;
 
-use gltf_kun::graph::{ByteNode, Graph, NodeIndex, OtherEdgeHelpers, Weight, gltf::Primitive};
+use gltf_kun::graph::{ByteNode, Graph, NodeIndex, OtherEdgeHelpers, Weight, gltf::Mesh};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::weight::from_bytes;
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BindEdges {
-    #[serde(rename = "VRM/Bind/Primitive")]
-    Primitive,
+    #[serde(rename = "VRM/Bind/Mesh")]
+    Mesh,
 }
 
 impl Display for BindEdges {
@@ -19,15 +22,19 @@ impl Display for BindEdges {
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct BindWeight {
+    /// Morph target index within the mesh.
+    pub index: Option<u32>,
     pub weight: Option<f32>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for BindWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
@@ -61,10 +68,10 @@ impl Bind {
         Self(graph.add_node(Weight::Bytes(weight.into())))
     }
 
-    pub fn primitive(&self, graph: &Graph) -> Option<Primitive> {
-        self.find_property(graph, &BindEdges::Primitive.to_string())
+    pub fn mesh(&self, graph: &Graph) -> Option<Mesh> {
+        self.find_property(graph, &BindEdges::Mesh.to_string())
     }
-    pub fn set_primitive(&self, graph: &mut Graph, primitive: Option<Primitive>) {
-        self.set_property(graph, BindEdges::Primitive.to_string(), primitive);
+    pub fn set_mesh(&self, graph: &mut Graph, mesh: Option<Mesh>) {
+        self.set_property(graph, BindEdges::Mesh.to_string(), mesh);
     }
 }
@@ -2,9 +2,11 @@ use std::fmt::Display;
 
 use gltf_kun::graph::{ByteNode, Graph, NodeIndex, OtherEdgeHelpers, Weight};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_vrm::vrm0::{MaterialBind, PresetName};
 
 use super::bind::Bind;
+use crate::weight::from_bytes;
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BlendShapeGroupEdges {
@@ -26,14 +28,16 @@ pub struct BlendShapeGroupWeight {
     pub material_values: Vec<MaterialBind>,
     pub name: Option<String>,
     pub preset_name: Option<PresetName>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for BlendShapeGroupWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
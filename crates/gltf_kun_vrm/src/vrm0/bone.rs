@@ -2,8 +2,11 @@ use std::fmt::Display;
 
 use gltf_kun::graph::{ByteNode, Graph, NodeIndex, OtherEdgeHelpers, Weight, gltf::Node};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_vrm::vrm0::BoneName;
 
+use crate::weight::from_bytes;
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BoneEdges {
     #[serde(rename = "VRM/Bone/Node")]
@@ -22,14 +25,16 @@ impl Display for BoneEdges {
 pub struct BoneWeight {
     pub name: Option<BoneName>,
     pub use_default_values: Option<bool>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for BoneWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
@@ -2,9 +2,11 @@ use std::fmt::Display;
 
 use gltf_kun::graph::{ByteNode, Graph, NodeIndex, OtherEdgeHelpers, Weight, gltf::Node};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_vrm::vrm0::Vec3;
 
 use super::collider_group::ColliderGroup;
+use crate::weight::from_bytes;
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BoneGroupEdges {
@@ -31,14 +33,16 @@ pub struct BoneGroupWeight {
     pub drag_force: Option<f32>,
     pub center: Option<f32>,
     pub hit_radius: Option<f32>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for BoneGroupWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
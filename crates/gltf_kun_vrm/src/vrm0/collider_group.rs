@@ -2,8 +2,11 @@ use std::fmt::Display;
 
 use gltf_kun::graph::{ByteNode, Graph, NodeIndex, OtherEdgeHelpers, Weight, gltf::Node};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_vrm::vrm0::Collider;
 
+use crate::weight::from_bytes;
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ColliderGroupEdges {
     #[serde(rename = "VRM/ColliderGroup/Node")]
@@ -21,14 +24,16 @@ impl Display for ColliderGroupEdges {
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct ColliderGroupWeight {
     pub colliders: Vec<Collider>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for ColliderGroupWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
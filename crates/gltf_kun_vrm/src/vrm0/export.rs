@@ -0,0 +1,278 @@
+use gltf_kun::{
+    extensions::ExtensionExport,
+    graph::{
+        ByteNode, Extensions, Graph,
+        gltf::{GltfDocument, Node, Texture},
+    },
+    io::format::gltf::GltfFormat,
+};
+use serde_vrm::vrm0::{
+    BlendShapeGroup, BlendShapeMaster, Bone, BoneGroup, ColliderGroup, FirstPerson, Humanoid,
+    MaterialProperty, MeshAnnotation, Meta, SecondaryAnimation, TextureProperties,
+};
+
+use super::{EXTENSION_NAME, Vrm};
+
+impl ExtensionExport<GltfDocument, GltfFormat> for Vrm {
+    fn export(
+        graph: &mut Graph,
+        doc: &GltfDocument,
+        format: &mut GltfFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let vrm = match doc.get_extension::<Vrm>(graph) {
+            Some(vrm) => vrm,
+            None => return Ok(()),
+        };
+
+        let graph = &*graph;
+        let weight = vrm.read(graph);
+
+        let node_idx = |node: Node| doc.node_index(graph, node).map(|i| i as u32);
+        let texture_idx = |texture: Option<Texture>| {
+            texture.and_then(|t| doc.texture_index(graph, t).map(|i| i as u32))
+        };
+
+        let human_bones = vrm
+            .human_bones(graph)
+            .into_iter()
+            .map(|bone| {
+                let bone_weight = bone.read(graph);
+                Bone {
+                    bone: bone_weight.name,
+                    node: bone.node(graph).and_then(node_idx),
+                    use_default_values: bone_weight.use_default_values,
+                    others: bone_weight.others,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let first_person_bone = vrm
+            .first_person_bone(graph)
+            .and_then(|bone| bone.node(graph))
+            .and_then(node_idx);
+
+        let mesh_annotations = vrm
+            .mesh_annotations(graph)
+            .into_iter()
+            .map(|annotation| {
+                let annotation_weight = annotation.read(graph);
+                MeshAnnotation {
+                    mesh: annotation
+                        .mesh(graph)
+                        .and_then(|mesh| doc.mesh_index(graph, mesh))
+                        .map(|i| i as u32),
+                    first_person_flag: annotation_weight.first_person_flag,
+                    others: annotation_weight.others,
+                }
+            })
+            .collect();
+
+        let blend_shape_groups = vrm
+            .blend_shape_groups(graph)
+            .into_iter()
+            .map(|group| {
+                let group_weight = group.read(graph);
+                let binds = group
+                    .binds(graph)
+                    .into_iter()
+                    .map(|bind| {
+                        let bind_weight = bind.read(graph);
+                        serde_vrm::vrm0::Bind {
+                            mesh: bind
+                                .mesh(graph)
+                                .and_then(|mesh| doc.mesh_index(graph, mesh))
+                                .map(|i| i as u32),
+                            index: bind_weight.index,
+                            weight: bind_weight.weight,
+                            others: bind_weight.others,
+                        }
+                    })
+                    .collect();
+
+                BlendShapeGroup {
+                    name: group_weight.name,
+                    preset_name: group_weight.preset_name,
+                    binds: Some(binds),
+                    material_values: Some(group_weight.material_values),
+                    is_binary: group_weight.is_binary,
+                    others: group_weight.others,
+                }
+            })
+            .collect();
+
+        let mut collider_groups = Vec::new();
+
+        let bone_groups = vrm
+            .bone_groups(graph)
+            .into_iter()
+            .map(|group| {
+                let group_weight = group.read(graph);
+
+                let group_collider_groups = group
+                    .collider_groups(graph)
+                    .into_iter()
+                    .map(|collider_group| {
+                        let idx = match collider_groups.iter().position(|g| *g == collider_group) {
+                            Some(idx) => idx,
+                            None => {
+                                collider_groups.push(collider_group);
+                                collider_groups.len() - 1
+                            }
+                        };
+                        idx as u32
+                    })
+                    .collect();
+
+                BoneGroup {
+                    comment: group_weight.comment,
+                    stiffiness: group_weight.stiffiness,
+                    gravity_power: group_weight.gravity_power,
+                    gravity_dir: Some(group_weight.gravity_dir),
+                    drag_force: group_weight.drag_force,
+                    center: group_weight.center,
+                    hit_radius: group_weight.hit_radius,
+                    bones: Some(
+                        group
+                            .bones(graph)
+                            .into_iter()
+                            .filter_map(node_idx)
+                            .collect(),
+                    ),
+                    collider_groups: Some(group_collider_groups),
+                    others: group_weight.others,
+                }
+            })
+            .collect();
+
+        let collider_groups = collider_groups
+            .into_iter()
+            .map(|group| {
+                let group_weight = group.read(graph);
+                ColliderGroup {
+                    node: group.node(graph).and_then(node_idx),
+                    colliders: Some(group_weight.colliders),
+                    others: group_weight.others,
+                }
+            })
+            .collect();
+
+        // Material properties are matched to materials by index.
+        let mut material_properties = vrm
+            .material_properties(graph)
+            .into_iter()
+            .filter_map(|property| {
+                let material = property.material(graph)?;
+                let idx = doc.material_index(graph, material)?;
+                Some((idx, property))
+            })
+            .collect::<Vec<_>>();
+        material_properties.sort_by_key(|(idx, _)| *idx);
+
+        let material_properties = material_properties
+            .into_iter()
+            .map(|(_, property)| {
+                let property_weight = property.read(graph);
+                MaterialProperty {
+                    name: property_weight.name,
+                    render_queue: property_weight.render_queue,
+                    shader: property_weight.shader,
+                    float: Some(property_weight.float),
+                    vector: Some(property_weight.vector),
+                    texture: Some(TextureProperties {
+                        base_color: texture_idx(property.main_texture(graph)),
+                        shade: texture_idx(property.shade_texture(graph)),
+                        normal: texture_idx(property.bump_map(graph)),
+                        additive: texture_idx(property.sphere_add(graph)),
+                        emissive: texture_idx(property.emission_map(graph)),
+                        rim_multiply: texture_idx(property.rim_texture(graph)),
+                        outline_width_multiply_texture: texture_idx(
+                            property.outline_width_texture(graph),
+                        ),
+                        uv_animation_mask_texture: texture_idx(
+                            property.uv_anim_mask_texture(graph),
+                        ),
+                        others: property_weight.texture_others,
+                    }),
+                    keyword_map: Some(property_weight.keyword_map),
+                    tag_map: Some(property_weight.tag_map),
+                    others: property_weight.others,
+                }
+            })
+            .collect();
+
+        let meta = weight.meta;
+        let humanoid = weight.humanoid;
+        let first_person = weight.first_person;
+
+        let ext = serde_vrm::vrm0::Vrm {
+            exporter_version: Some(weight.exporter_version),
+            spec_version: Some("0.0".to_string()),
+            meta: Some(Meta {
+                title: meta.title,
+                version: meta.version,
+                author: meta.author,
+                contact_information: meta.contact_information,
+                reference: meta.reference,
+                texture: texture_idx(vrm.thumbnail(graph)),
+                allowed_user_name: meta.allowed_user_name,
+                violent_usage_name: meta.violent_usage_name,
+                sexual_usage_name: meta.sexual_usage_name,
+                commercial_usage_name: meta.commercial_usage_name,
+                other_permission_url: meta.other_permission_url,
+                license_name: meta.license_name,
+                other_license_url: meta.other_license_url,
+                others: meta.others,
+            }),
+            humanoid: Some(Humanoid {
+                human_bones: Some(human_bones),
+                arm_stretch: humanoid.arm_stretch,
+                leg_stretch: humanoid.leg_stretch,
+                upper_arm_twist: humanoid.upper_arm_twist,
+                lower_arm_twist: humanoid.lower_arm_twist,
+                upper_leg_twist: humanoid.upper_leg_twist,
+                lower_leg_twist: humanoid.lower_leg_twist,
+                feet_spacing: humanoid.feet_spacing,
+                has_translation_dof: humanoid.has_translation_dof,
+                others: humanoid.others,
+            }),
+            first_person: Some(FirstPerson {
+                first_person_bone,
+                first_person_bone_offset: Some(first_person.first_person_bone_offset),
+                mesh_annotations: Some(mesh_annotations),
+                look_at_type_name: first_person.look_at_type_name,
+                look_at_horizontal_inner: first_person.look_at_horizontal_inner,
+                look_at_horizontal_outer: first_person.look_at_horizontal_outer,
+                look_at_vertical_down: first_person.look_at_vertical_down,
+                look_at_vertical_up: first_person.look_at_vertical_up,
+                others: first_person.others,
+            }),
+            blend_shape_master: Some(BlendShapeMaster {
+                blend_shape_groups: Some(blend_shape_groups),
+                others: weight.blend_shape_master_others,
+            }),
+            secondary_animation: Some(SecondaryAnimation {
+                bone_groups: Some(bone_groups),
+                collider_groups: Some(collider_groups),
+                others: weight.secondary_animation_others,
+            }),
+            material_properties: Some(material_properties),
+            others: weight.others,
+        };
+
+        let extensions = format.json.extensions.get_or_insert_with(Default::default);
+        extensions
+            .others
+            .insert(EXTENSION_NAME.to_string(), serde_json::to_value(ext)?);
+
+        if !format
+            .json
+            .extensions_used
+            .iter()
+            .any(|e| e == EXTENSION_NAME)
+        {
+            format.json.extensions_used.push(EXTENSION_NAME.to_string());
+        }
+
+        Ok(())
+    }
+}
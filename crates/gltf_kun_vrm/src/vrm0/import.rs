@@ -3,6 +3,7 @@ use gltf_kun::{
     graph::{ByteNode, Extensions, Graph, gltf::GltfDocument},
     io::format::gltf::GltfFormat,
 };
+use serde_json::Map;
 use thiserror::Error;
 
 use super::{
@@ -71,7 +72,11 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
 
             material_property.set_material(graph, Some(material));
 
+            let mut texture_others = Map::new();
+
             if let Some(texture_properties) = material_property_json.texture {
+                texture_others = texture_properties.others;
+
                 if let Some(idx) = texture_properties.base_color {
                     doc.textures(graph)
                         .get(idx as usize)
@@ -116,6 +121,33 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                         })
                         .ok_or_else(|| Box::new(VrmImportError::TextureNotFound(idx as usize)))?;
                 }
+
+                if let Some(idx) = texture_properties.rim_multiply {
+                    doc.textures(graph)
+                        .get(idx as usize)
+                        .map(|texture| {
+                            material_property.set_rim_texture(graph, Some(*texture));
+                        })
+                        .ok_or_else(|| Box::new(VrmImportError::TextureNotFound(idx as usize)))?;
+                }
+
+                if let Some(idx) = texture_properties.outline_width_multiply_texture {
+                    doc.textures(graph)
+                        .get(idx as usize)
+                        .map(|texture| {
+                            material_property.set_outline_width_texture(graph, Some(*texture));
+                        })
+                        .ok_or_else(|| Box::new(VrmImportError::TextureNotFound(idx as usize)))?;
+                }
+
+                if let Some(idx) = texture_properties.uv_animation_mask_texture {
+                    doc.textures(graph)
+                        .get(idx as usize)
+                        .map(|texture| {
+                            material_property.set_uv_anim_mask_texture(graph, Some(*texture));
+                        })
+                        .ok_or_else(|| Box::new(VrmImportError::TextureNotFound(idx as usize)))?;
+                }
             }
 
             let weight = MaterialPropertyWeight {
@@ -126,6 +158,8 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                 tag_map: material_property_json.tag_map.unwrap_or_default(),
                 keyword_map: material_property_json.keyword_map.unwrap_or_default(),
                 render_queue: material_property_json.render_queue,
+                others: material_property_json.others,
+                texture_others,
             };
 
             material_property.write(graph, &weight);
@@ -154,6 +188,7 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                 contact_information: meta.contact_information,
                 other_permission_url: meta.other_permission_url,
                 commercial_usage_name: meta.commercial_usage_name,
+                others: meta.others,
             }
         } else {
             Meta::default()
@@ -181,6 +216,7 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                 let weight = BoneWeight {
                     name: bone_json.bone,
                     use_default_values: bone_json.use_default_values,
+                    others: bone_json.others,
                 };
 
                 bone.write(graph, &weight)
@@ -195,6 +231,7 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                 upper_leg_twist: humanoid.upper_leg_twist,
                 lower_leg_twist: humanoid.lower_leg_twist,
                 has_translation_dof: humanoid.has_translation_dof,
+                others: humanoid.others,
             }
         } else {
             Humanoid::default()
@@ -223,7 +260,13 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                 if let Some(mesh) = doc.meshes(graph).get(mesh_idx as usize) {
                     let ma = MeshAnnotation::new(graph);
                     ma.set_mesh(graph, Some(*mesh));
-                    ma.write(graph, &MeshAnnotationWeight { first_person_flag });
+                    ma.write(
+                        graph,
+                        &MeshAnnotationWeight {
+                            first_person_flag,
+                            others: annotation.others,
+                        },
+                    );
                     vrm.add_mesh_annotation(graph, ma);
                 }
             }
@@ -235,34 +278,48 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                 first_person_bone_offset: first_person.first_person_bone_offset.unwrap_or_default(),
                 look_at_horizontal_inner: first_person.look_at_horizontal_inner,
                 look_at_horizontal_outer: first_person.look_at_horizontal_outer,
+                others: first_person.others,
             }
         } else {
             FirstPerson::default()
         };
 
+        let mut blend_shape_master_others = Map::new();
+
         if let Some(blend_shape_master) = ext.blend_shape_master {
+            blend_shape_master_others = blend_shape_master.others;
+
             let blend_shape_groups = blend_shape_master.blend_shape_groups.unwrap_or_default();
 
             for group_json in blend_shape_groups {
                 let group = BlendShapeGroup::new(graph);
+                vrm.add_blend_shape_group(graph, group);
 
                 let binds = group_json.binds.unwrap_or_default();
 
                 for bind_json in binds {
-                    let bind = Bind::new(graph);
-
-                    if let Some(mesh_idx) = bind_json.mesh {
-                        if let Some(mesh) = doc.meshes(graph).get(mesh_idx as usize) {
-                            let index = bind_json.index.unwrap_or_default();
-
-                            if let Some(primitive) = mesh.primitives(graph).get(index as usize) {
-                                bind.set_primitive(graph, Some(*primitive));
+                    let mesh = match bind_json.mesh {
+                        Some(mesh_idx) => match doc.meshes(graph).get(mesh_idx as usize) {
+                            Some(mesh) => Some(*mesh),
+                            None => {
+                                tracing::warn!(
+                                    "Skipping blend shape bind with missing mesh: {}",
+                                    mesh_idx
+                                );
+                                continue;
                             }
-                        }
-                    }
+                        },
+                        None => None,
+                    };
+
+                    let bind = Bind::new(graph);
+                    group.add_bind(graph, bind);
+                    bind.set_mesh(graph, mesh);
 
                     let weight = BindWeight {
+                        index: bind_json.index,
                         weight: bind_json.weight,
+                        others: bind_json.others,
                     };
 
                     bind.write(graph, &weight);
@@ -273,13 +330,18 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                     preset_name: group_json.preset_name,
                     is_binary: group_json.is_binary,
                     material_values: group_json.material_values.unwrap_or_default(),
+                    others: group_json.others,
                 };
 
                 group.write(graph, &weight);
             }
         }
 
+        let mut secondary_animation_others = Map::new();
+
         if let Some(secondary_animation) = ext.secondary_animation {
+            secondary_animation_others = secondary_animation.others;
+
             let collider_groups = secondary_animation.collider_groups.unwrap_or_default();
 
             let mut graph_collider_groups = Vec::new();
@@ -299,6 +361,7 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
 
                 let weight = ColliderGroupWeight {
                     colliders: collider_group_json.colliders.unwrap_or_default(),
+                    others: collider_group_json.others,
                 };
 
                 collider_group.write(graph, &weight);
@@ -344,6 +407,7 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
                     hit_radius: bone_group_json.hit_radius,
                     gravity_dir: bone_group_json.gravity_dir.unwrap_or_default(),
                     gravity_power: bone_group_json.gravity_power,
+                    others: bone_group_json.others,
                 };
 
                 bone_group.write(graph, &weight);
@@ -355,6 +419,9 @@ impl ExtensionImport<GltfDocument, GltfFormat> for Vrm {
             humanoid,
             first_person,
             exporter_version: ext.exporter_version.unwrap_or_default(),
+            others: ext.others,
+            blend_shape_master_others,
+            secondary_animation_others,
         };
 
         vrm.write(graph, &weight);
@@ -5,8 +5,11 @@ use gltf_kun::graph::{
     gltf::{Material, Texture},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_vrm::vrm0::{FloatProperties, KeywordMap, Shader, TagMap, VectorProperties};
 
+use crate::weight::from_bytes;
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum MaterialPropertyEdges {
     #[serde(rename = "VRM/MaterialProperty/Material")]
@@ -21,6 +24,12 @@ pub enum MaterialPropertyEdges {
     SphereAdd,
     #[serde(rename = "VRM/MaterialProperty/EmissionMap")]
     EmissionMap,
+    #[serde(rename = "VRM/MaterialProperty/RimTexture")]
+    RimTexture,
+    #[serde(rename = "VRM/MaterialProperty/OutlineWidthTexture")]
+    OutlineWidthTexture,
+    #[serde(rename = "VRM/MaterialProperty/UvAnimMaskTexture")]
+    UvAnimMaskTexture,
 }
 
 impl Display for MaterialPropertyEdges {
@@ -40,14 +49,19 @@ pub struct MaterialPropertyWeight {
     pub vector: VectorProperties,
     pub keyword_map: KeywordMap,
     pub tag_map: TagMap,
+    #[serde(default)]
+    pub others: Map<String, Value>,
+    /// Texture properties other than the ones stored as edges.
+    #[serde(default)]
+    pub texture_others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for MaterialPropertyWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
@@ -135,4 +149,40 @@ impl MaterialProperty {
             texture,
         );
     }
+
+    pub fn rim_texture(&self, graph: &Graph) -> Option<Texture> {
+        self.find_property(graph, &MaterialPropertyEdges::RimTexture.to_string())
+    }
+    pub fn set_rim_texture(&self, graph: &mut Graph, texture: Option<Texture>) {
+        self.set_property(
+            graph,
+            MaterialPropertyEdges::RimTexture.to_string(),
+            texture,
+        );
+    }
+
+    pub fn outline_width_texture(&self, graph: &Graph) -> Option<Texture> {
+        self.find_property(
+            graph,
+            &MaterialPropertyEdges::OutlineWidthTexture.to_string(),
+        )
+    }
+    pub fn set_outline_width_texture(&self, graph: &mut Graph, texture: Option<Texture>) {
+        self.set_property(
+            graph,
+            MaterialPropertyEdges::OutlineWidthTexture.to_string(),
+            texture,
+        );
+    }
+
+    pub fn uv_anim_mask_texture(&self, graph: &Graph) -> Option<Texture> {
+        self.find_property(graph, &MaterialPropertyEdges::UvAnimMaskTexture.to_string())
+    }
+    pub fn set_uv_anim_mask_texture(&self, graph: &mut Graph, texture: Option<Texture>) {
+        self.set_property(
+            graph,
+            MaterialPropertyEdges::UvAnimMaskTexture.to_string(),
+            texture,
+        );
+    }
 }
@@ -2,8 +2,11 @@ use std::fmt::Display;
 
 use gltf_kun::graph::{ByteNode, Graph, NodeIndex, OtherEdgeHelpers, Weight, gltf::Mesh};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_vrm::vrm0::FirstPersonFlag;
 
+use crate::weight::from_bytes;
+
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum MeshAnnotationEdges {
     #[serde(rename = "VRM/MeshAnnotation/Mesh")]
@@ -21,14 +24,16 @@ impl Display for MeshAnnotationEdges {
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct MeshAnnotationWeight {
     pub first_person_flag: FirstPersonFlag,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for MeshAnnotationWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
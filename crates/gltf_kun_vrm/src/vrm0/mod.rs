@@ -10,15 +10,18 @@ use self::{
     blend_shape_group::BlendShapeGroup, bone::Bone, bone_group::BoneGroup,
     material_property::MaterialProperty, mesh_annotation::MeshAnnotation, weight::VrmWeight,
 };
+use crate::weight::{TryByteNode, WeightError};
 
 pub mod bind;
 pub mod blend_shape_group;
 pub mod bone;
 pub mod bone_group;
 pub mod collider_group;
+pub mod export;
 pub mod import;
 pub mod material_property;
 pub mod mesh_annotation;
+pub mod validate;
 pub mod weight;
 
 pub const EXTENSION_NAME: &str = "VRM";
@@ -137,11 +140,49 @@ impl Vrm {
     pub fn set_thumbnail(&self, graph: &mut Graph, texture: Option<Texture>) {
         self.set_property(graph, VrmEdge::Thumbnail.to_string(), texture);
     }
+
+    /// Reads the weight of the extension and of each of its properties,
+    /// failing on the first one with malformed bytes.
+    pub fn check_weights(&self, graph: &Graph) -> Result<(), WeightError> {
+        self.try_read(graph)?;
+
+        for group in self.blend_shape_groups(graph) {
+            group.try_read(graph)?;
+            for bind in group.binds(graph) {
+                bind.try_read(graph)?;
+            }
+        }
+
+        for group in self.bone_groups(graph) {
+            group.try_read(graph)?;
+            for collider_group in group.collider_groups(graph) {
+                collider_group.try_read(graph)?;
+            }
+        }
+
+        for bone in self
+            .human_bones(graph)
+            .into_iter()
+            .chain(self.first_person_bone(graph))
+        {
+            bone.try_read(graph)?;
+        }
+
+        for property in self.material_properties(graph) {
+            property.try_read(graph)?;
+        }
+
+        for annotation in self.mesh_annotations(graph) {
+            annotation.try_read(graph)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use gltf_kun::graph::{GraphNodeWeight, gltf::Texture};
+    use gltf_kun::graph::{GraphNodeWeight, Weight, gltf::Texture};
 
     use super::*;
 
@@ -163,6 +204,26 @@ mod tests {
         assert_eq!(vrm.blend_shape_groups(&graph), vec![group_2]);
     }
 
+    #[test]
+    fn check_weights() {
+        let mut graph = Graph::new();
+
+        let vrm = Vrm::new(&mut graph);
+        let group = BlendShapeGroup::new(&mut graph);
+        vrm.add_blend_shape_group(&mut graph, group);
+
+        let bind = bind::Bind::new(&mut graph);
+        group.add_bind(&mut graph, bind);
+        bind.write(&mut graph, &bind::BindWeight::default());
+        assert!(vrm.check_weights(&graph).is_ok());
+
+        // Malformed bytes read as the default weight, but fail the check.
+        graph[bind.0] = Weight::Bytes(b"{\"index\": \"one\"}".to_vec());
+        assert_eq!(bind.read(&graph), bind::BindWeight::default());
+        assert!(bind.try_read(&graph).is_err());
+        assert!(vrm.check_weights(&graph).is_err());
+    }
+
     #[test]
     fn bone_groups() {
         let mut graph = Graph::new();
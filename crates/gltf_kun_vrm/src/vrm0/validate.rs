@@ -0,0 +1,636 @@
+//! Structured checks for VRM 0.x avatars.
+
+use std::collections::HashMap;
+
+use gltf_kun::{
+    graph::{ByteNode, Extensions, Graph, GraphNodeWeight, gltf::GltfDocument},
+    io::format::gltf::GltfFormat,
+};
+use serde_vrm::vrm0::{BoneName, Shader};
+use thiserror::Error;
+
+use super::{EXTENSION_NAME, Vrm};
+
+/// Human bones every VRM 0.x avatar must have.
+pub const REQUIRED_BONES: [BoneName; 17] = [
+    BoneName::Hips,
+    BoneName::Spine,
+    BoneName::Chest,
+    BoneName::Neck,
+    BoneName::Head,
+    BoneName::LeftUpperLeg,
+    BoneName::LeftLowerLeg,
+    BoneName::LeftFoot,
+    BoneName::RightUpperLeg,
+    BoneName::RightLowerLeg,
+    BoneName::RightFoot,
+    BoneName::LeftUpperArm,
+    BoneName::LeftLowerArm,
+    BoneName::LeftHand,
+    BoneName::RightUpperArm,
+    BoneName::RightLowerArm,
+    BoneName::RightHand,
+];
+
+/// Each human bone, with a bone that must be one of its ancestors.
+const HIERARCHY: [(BoneName, BoneName); 24] = [
+    (BoneName::Spine, BoneName::Hips),
+    (BoneName::Chest, BoneName::Spine),
+    (BoneName::UpperChest, BoneName::Chest),
+    (BoneName::Neck, BoneName::Chest),
+    (BoneName::Head, BoneName::Neck),
+    (BoneName::LeftEye, BoneName::Head),
+    (BoneName::RightEye, BoneName::Head),
+    (BoneName::Jaw, BoneName::Head),
+    (BoneName::LeftUpperLeg, BoneName::Hips),
+    (BoneName::LeftLowerLeg, BoneName::LeftUpperLeg),
+    (BoneName::LeftFoot, BoneName::LeftLowerLeg),
+    (BoneName::LeftToes, BoneName::LeftFoot),
+    (BoneName::RightUpperLeg, BoneName::Hips),
+    (BoneName::RightLowerLeg, BoneName::RightUpperLeg),
+    (BoneName::RightFoot, BoneName::RightLowerLeg),
+    (BoneName::RightToes, BoneName::RightFoot),
+    (BoneName::LeftShoulder, BoneName::Chest),
+    (BoneName::LeftUpperArm, BoneName::Chest),
+    (BoneName::LeftLowerArm, BoneName::LeftUpperArm),
+    (BoneName::LeftHand, BoneName::LeftLowerArm),
+    (BoneName::RightShoulder, BoneName::Chest),
+    (BoneName::RightUpperArm, BoneName::Chest),
+    (BoneName::RightLowerArm, BoneName::RightUpperArm),
+    (BoneName::RightHand, BoneName::RightLowerArm),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The avatar may still load, but not as intended.
+    Warning,
+    /// The avatar is invalid.
+    Error,
+}
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum Diagnostic {
+    #[error("Missing {EXTENSION_NAME} extension")]
+    MissingExtension,
+    #[error("{path} references index {index}, but there are only {count}")]
+    IndexOutOfRange {
+        /// JSON path of the reference, such as `firstPerson.meshAnnotations[0].mesh`.
+        path: String,
+        index: u32,
+        count: usize,
+    },
+    #[error("Missing required human bone: {0:?}")]
+    MissingHumanBone(BoneName),
+    #[error("Human bone {0:?} has no node")]
+    HumanBoneMissingNode(BoneName),
+    #[error("Human bone {0:?} is assigned more than once")]
+    DuplicateHumanBone(BoneName),
+    #[error("Node {node} is assigned to both {first:?} and {second:?}")]
+    SharedBoneNode {
+        node: usize,
+        first: BoneName,
+        second: BoneName,
+    },
+    #[error("Human bone {bone:?} is not a descendant of {ancestor:?}")]
+    BoneHierarchy { bone: BoneName, ancestor: BoneName },
+    #[error("Spring bone group {group} has no bones")]
+    EmptyBoneGroup { group: usize },
+    #[error("Spring bone group {group} references a node outside the document")]
+    SpringBoneNotInDocument { group: usize },
+    #[error("Spring bone group {group} moves human bone {bone:?}")]
+    SpringBoneOnHumanBone { group: usize, bone: BoneName },
+    #[error("Collider group of spring bone group {group} has no node")]
+    ColliderGroupMissingNode { group: usize },
+    #[error("Mesh annotation {0} has no mesh")]
+    MeshAnnotationMissingMesh(usize),
+    #[error("Material property {material} uses unsupported shader {shader}")]
+    UnsupportedShader { material: usize, shader: String },
+    #[error("Texture {texture} is {width}x{height}, larger than {max}")]
+    TextureTooLarge {
+        texture: usize,
+        width: u32,
+        height: u32,
+        max: u32,
+    },
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::MissingExtension
+            | Self::IndexOutOfRange { .. }
+            | Self::MissingHumanBone(_)
+            | Self::HumanBoneMissingNode(_)
+            | Self::DuplicateHumanBone(_)
+            | Self::SharedBoneNode { .. }
+            | Self::BoneHierarchy { .. }
+            | Self::SpringBoneNotInDocument { .. } => Severity::Error,
+            Self::EmptyBoneGroup { .. }
+            | Self::SpringBoneOnHumanBone { .. }
+            | Self::ColliderGroupMissingNode { .. }
+            | Self::MeshAnnotationMissingMesh(_)
+            | Self::UnsupportedShader { .. }
+            | Self::TextureTooLarge { .. } => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationOptions {
+    /// Largest allowed texture width or height, in pixels.
+    pub max_texture_size: Option<u32>,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        Self {
+            max_texture_size: Some(4096),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Whether the report has no errors. Warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.with_severity(Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.with_severity(Severity::Warning)
+    }
+
+    fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(move |d| d.severity() == severity)
+    }
+
+    pub fn append(&mut self, other: ValidationReport) {
+        self.diagnostics.extend(other.diagnostics);
+    }
+
+    fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+}
+
+/// Checks the indices of the extension JSON against the glTF.
+///
+/// References out of range fail the [import](super::import), so this can
+/// report them before the format is imported into a graph.
+pub fn validate_format(format: &GltfFormat) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let Some(ext) = format
+        .json
+        .extensions
+        .as_ref()
+        .and_then(|e| e.others.get(EXTENSION_NAME))
+    else {
+        report.push(Diagnostic::MissingExtension);
+        return report;
+    };
+
+    // Malformed JSON fails the import with its own error.
+    let Ok(ext) = serde_json::from_value::<serde_vrm::vrm0::Vrm>(ext.clone()) else {
+        return report;
+    };
+
+    let nodes = format.json.nodes.len();
+    let meshes = format.json.meshes.len();
+    let textures = format.json.textures.len();
+    let materials = format.json.materials.len();
+
+    let mut check = |path: String, index: Option<u32>, count: usize| {
+        if let Some(index) = index
+            && index as usize >= count
+        {
+            report.push(Diagnostic::IndexOutOfRange { path, index, count });
+        }
+    };
+
+    if let Some(meta) = &ext.meta {
+        check("meta.texture".to_string(), meta.texture, textures);
+    }
+
+    for (i, bone) in ext
+        .humanoid
+        .iter()
+        .flat_map(|h| h.human_bones.iter().flatten())
+        .enumerate()
+    {
+        check(format!("humanoid.humanBones[{i}].node"), bone.node, nodes);
+    }
+
+    if let Some(first_person) = &ext.first_person {
+        check(
+            "firstPerson.firstPersonBone".to_string(),
+            first_person.first_person_bone,
+            nodes,
+        );
+
+        for (i, annotation) in first_person.mesh_annotations.iter().flatten().enumerate() {
+            check(
+                format!("firstPerson.meshAnnotations[{i}].mesh"),
+                annotation.mesh,
+                meshes,
+            );
+        }
+    }
+
+    for (i, group) in ext
+        .blend_shape_master
+        .iter()
+        .flat_map(|m| m.blend_shape_groups.iter().flatten())
+        .enumerate()
+    {
+        for (j, bind) in group.binds.iter().flatten().enumerate() {
+            check(
+                format!("blendShapeMaster.blendShapeGroups[{i}].binds[{j}].mesh"),
+                bind.mesh,
+                meshes,
+            );
+        }
+    }
+
+    if let Some(secondary) = &ext.secondary_animation {
+        let collider_groups = secondary.collider_groups.as_deref().unwrap_or_default();
+
+        for (i, group) in collider_groups.iter().enumerate() {
+            check(
+                format!("secondaryAnimation.colliderGroups[{i}].node"),
+                group.node,
+                nodes,
+            );
+        }
+
+        for (i, group) in secondary.bone_groups.iter().flatten().enumerate() {
+            for (j, bone) in group.bones.iter().flatten().enumerate() {
+                check(
+                    format!("secondaryAnimation.boneGroups[{i}].bones[{j}]"),
+                    Some(*bone),
+                    nodes,
+                );
+            }
+
+            for (j, idx) in group.collider_groups.iter().flatten().enumerate() {
+                check(
+                    format!("secondaryAnimation.boneGroups[{i}].colliderGroups[{j}]"),
+                    Some(*idx),
+                    collider_groups.len(),
+                );
+            }
+        }
+    }
+
+    if let Some(properties) = &ext.material_properties {
+        if properties.len() > materials {
+            check(
+                "materialProperties".to_string(),
+                Some(properties.len() as u32 - 1),
+                materials,
+            );
+        }
+
+        for (i, property) in properties.iter().enumerate() {
+            let Some(texture) = &property.texture else {
+                continue;
+            };
+
+            for (name, idx) in [
+                ("_MainTex", texture.base_color),
+                ("_ShadeTexture", texture.shade),
+                ("_SphereAdd", texture.additive),
+                ("_BumpMap", texture.normal),
+                ("_EmissionMap", texture.emissive),
+                ("_RimTexture", texture.rim_multiply),
+                (
+                    "_OutlineWidthTexture",
+                    texture.outline_width_multiply_texture,
+                ),
+                ("_UvAnimMaskTexture", texture.uv_animation_mask_texture),
+            ] {
+                check(
+                    format!("materialProperties[{i}].textureProperties.{name}"),
+                    idx,
+                    textures,
+                );
+            }
+        }
+    }
+
+    report
+}
+
+/// Checks an imported avatar.
+pub fn validate(graph: &Graph, doc: GltfDocument, options: &ValidationOptions) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    let Some(vrm) = doc.get_extension::<Vrm>(graph) else {
+        report.push(Diagnostic::MissingExtension);
+        return report;
+    };
+
+    validate_humanoid(graph, doc, vrm, &mut report);
+    validate_spring_bones(graph, doc, vrm, &mut report);
+
+    for (i, annotation) in vrm.mesh_annotations(graph).iter().enumerate() {
+        if annotation.mesh(graph).is_none() {
+            report.push(Diagnostic::MeshAnnotationMissingMesh(i));
+        }
+    }
+
+    for (i, property) in vrm.material_properties(graph).iter().enumerate() {
+        match property.read(graph).shader {
+            Some(Shader::MToon | Shader::Gltf) | None => {}
+            Some(shader) => report.push(Diagnostic::UnsupportedShader {
+                material: i,
+                shader: shader_name(&shader),
+            }),
+        }
+    }
+
+    if let Some(max) = options.max_texture_size {
+        for (i, texture) in doc.textures(graph).iter().enumerate() {
+            let Some(image) = texture.image(graph) else {
+                continue;
+            };
+
+            let Some((width, height)) = image_size(&image.get(graph).data) else {
+                continue;
+            };
+
+            if width > max || height > max {
+                report.push(Diagnostic::TextureTooLarge {
+                    texture: i,
+                    width,
+                    height,
+                    max,
+                });
+            }
+        }
+    }
+
+    report
+}
+
+fn validate_humanoid(graph: &Graph, doc: GltfDocument, vrm: Vrm, report: &mut ValidationReport) {
+    let mut bones = HashMap::new();
+    let mut nodes = HashMap::new();
+
+    for bone in vrm.human_bones(graph) {
+        let Some(name) = bone.read(graph).name else {
+            continue;
+        };
+
+        let Some(node) = bone.node(graph) else {
+            report.push(Diagnostic::HumanBoneMissingNode(name));
+            continue;
+        };
+
+        if bones.insert(name, node).is_some() {
+            report.push(Diagnostic::DuplicateHumanBone(name));
+        }
+
+        if let Some(first) = nodes.insert(node, name)
+            && first != name
+        {
+            report.push(Diagnostic::SharedBoneNode {
+                node: doc.node_index(graph, node).unwrap_or_default(),
+                first,
+                second: name,
+            });
+        }
+    }
+
+    for name in REQUIRED_BONES {
+        if !bones.contains_key(&name) {
+            report.push(Diagnostic::MissingHumanBone(name));
+        }
+    }
+
+    for (bone, ancestor) in HIERARCHY {
+        let (Some(node), Some(ancestor_node)) = (bones.get(&bone), bones.get(&ancestor)) else {
+            continue;
+        };
+
+        let mut current = node.parents(graph).first().copied();
+        let mut found = false;
+
+        while let Some(parent) = current {
+            if parent == *ancestor_node {
+                found = true;
+                break;
+            }
+            current = parent.parents(graph).first().copied();
+        }
+
+        if !found {
+            report.push(Diagnostic::BoneHierarchy { bone, ancestor });
+        }
+    }
+}
+
+fn validate_spring_bones(
+    graph: &Graph,
+    doc: GltfDocument,
+    vrm: Vrm,
+    report: &mut ValidationReport,
+) {
+    let human_nodes = vrm
+        .human_bones(graph)
+        .iter()
+        .filter_map(|bone| Some((bone.node(graph)?, bone.read(graph).name?)))
+        .collect::<HashMap<_, _>>();
+
+    for (group, bone_group) in vrm.bone_groups(graph).iter().enumerate() {
+        let bones = bone_group.bones(graph);
+
+        if bones.is_empty() {
+            report.push(Diagnostic::EmptyBoneGroup { group });
+        }
+
+        for node in bones {
+            if doc.node_index(graph, node).is_none() {
+                report.push(Diagnostic::SpringBoneNotInDocument { group });
+            }
+
+            if let Some(bone) = human_nodes.get(&node) {
+                report.push(Diagnostic::SpringBoneOnHumanBone { group, bone: *bone });
+            }
+        }
+
+        for collider_group in bone_group.collider_groups(graph) {
+            if collider_group.node(graph).is_none() {
+                report.push(Diagnostic::ColliderGroupMissingNode { group });
+            }
+        }
+    }
+}
+
+fn shader_name(shader: &Shader) -> String {
+    match shader {
+        Shader::Other(name) => name.clone(),
+        shader => serde_json::to_value(shader)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_else(|| format!("{:?}", shader)),
+    }
+}
+
+/// Reads the dimensions of a PNG or JPEG image from its header.
+fn image_size(data: &[u8]) -> Option<(u32, u32)> {
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    if data.starts_with(&PNG_SIGNATURE) {
+        let width = u32::from_be_bytes(data.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(data.get(20..24)?.try_into().ok()?);
+        return Some((width, height));
+    }
+
+    if data.starts_with(&[0xFF, 0xD8]) {
+        let mut i = 2;
+
+        while i + 9 < data.len() {
+            if data[i] != 0xFF {
+                return None;
+            }
+
+            let marker = data[i + 1];
+            let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+
+            // Start of frame markers, excluding DHT, JPG, and DAC.
+            if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                let height = u16::from_be_bytes([data[i + 5], data[i + 6]]) as u32;
+                let width = u16::from_be_bytes([data[i + 7], data[i + 8]]) as u32;
+                return Some((width, height));
+            }
+
+            i += 2 + len;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use gltf_kun::{extensions::Extension, graph::gltf::Node};
+
+    use super::{
+        super::bone::{Bone, BoneWeight},
+        *,
+    };
+
+    fn add_bone(graph: &mut Graph, vrm: Vrm, name: BoneName, node: Node) {
+        let bone = Bone::new(graph);
+        bone.set_node(graph, Some(node));
+        bone.write(
+            graph,
+            &BoneWeight {
+                name: Some(name),
+                ..Default::default()
+            },
+        );
+        vrm.add_human_bone(graph, bone);
+    }
+
+    /// A valid humanoid with the required bones, returning their nodes.
+    fn humanoid(graph: &mut Graph) -> (GltfDocument, Vrm, [Node; 17]) {
+        let doc = GltfDocument::new(graph);
+        let vrm = Vrm::new(graph);
+        doc.add_extension(graph, vrm);
+
+        let nodes = REQUIRED_BONES.map(|_| doc.create_node(graph));
+        for (name, node) in REQUIRED_BONES.iter().zip(nodes) {
+            add_bone(graph, vrm, *name, node);
+        }
+
+        // Chain every bone to its expected ancestor.
+        for (bone, ancestor) in HIERARCHY {
+            let child = REQUIRED_BONES.iter().position(|b| *b == bone);
+            let parent = REQUIRED_BONES.iter().position(|b| *b == ancestor);
+            if let (Some(child), Some(parent)) = (child, parent) {
+                nodes[parent].add_child(graph, &nodes[child]);
+            }
+        }
+
+        (doc, vrm, nodes)
+    }
+
+    #[test]
+    fn humanoid_diagnostics() {
+        let mut graph = Graph::new();
+        let (doc, vrm, nodes) = humanoid(&mut graph);
+
+        let report = validate(&graph, doc, &ValidationOptions::default());
+        assert!(report.is_valid(), "{:?}", report.diagnostics);
+
+        // Move the left hand under the right arm, and reuse the head node for the jaw.
+        let hand = nodes[13];
+        nodes[12].remove_child(&mut graph, &hand);
+        nodes[16].add_child(&mut graph, &hand);
+        add_bone(&mut graph, vrm, BoneName::Jaw, nodes[4]);
+
+        let report = validate(&graph, doc, &ValidationOptions::default());
+        assert_eq!(
+            report.diagnostics,
+            vec![
+                Diagnostic::SharedBoneNode {
+                    node: 4,
+                    first: BoneName::Head,
+                    second: BoneName::Jaw,
+                },
+                Diagnostic::BoneHierarchy {
+                    bone: BoneName::Jaw,
+                    ancestor: BoneName::Head,
+                },
+                Diagnostic::BoneHierarchy {
+                    bone: BoneName::LeftHand,
+                    ancestor: BoneName::LeftLowerArm,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn right_hand_hierarchy() {
+        let mut graph = Graph::new();
+        let (doc, _, nodes) = humanoid(&mut graph);
+
+        // Attach the right hand directly to the right upper arm.
+        let hand = nodes[16];
+        nodes[15].remove_child(&mut graph, &hand);
+        nodes[14].add_child(&mut graph, &hand);
+
+        let report = validate(&graph, doc, &ValidationOptions::default());
+        assert_eq!(
+            report.diagnostics,
+            vec![Diagnostic::BoneHierarchy {
+                bone: BoneName::RightHand,
+                ancestor: BoneName::RightLowerArm,
+            }]
+        );
+    }
+
+    #[test]
+    fn png_size() {
+        let mut data = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        data.extend([0, 0, 0, 13]);
+        data.extend(b"IHDR");
+        data.extend(8192u32.to_be_bytes());
+        data.extend(512u32.to_be_bytes());
+
+        assert_eq!(image_size(&data), Some((8192, 512)));
+        assert_eq!(image_size(&[0; 8]), None);
+    }
+}
@@ -1,20 +1,31 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use serde_vrm::vrm0::{Allow, AllowedUserName, LookAtCurve, Vec3};
 
+use crate::weight::from_bytes;
+
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct VrmWeight {
     pub exporter_version: String,
     pub meta: Meta,
     pub humanoid: Humanoid,
     pub first_person: FirstPerson,
+    #[serde(default)]
+    pub others: Map<String, Value>,
+    /// Unknown properties of the `blendShapeMaster` object, whose groups are graph nodes.
+    #[serde(default)]
+    pub blend_shape_master_others: Map<String, Value>,
+    /// Unknown properties of the `secondaryAnimation` object, whose groups are graph nodes.
+    #[serde(default)]
+    pub secondary_animation_others: Map<String, Value>,
 }
 
 impl From<&Vec<u8>> for VrmWeight {
     fn from(bytes: &Vec<u8>) -> Self {
-        if bytes.is_empty() {
-            return Self::default();
-        }
-        serde_json::from_slice(bytes).expect("Failed to deserialize weight")
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
     }
 }
 
@@ -38,6 +49,8 @@ pub struct Meta {
     pub other_permission_url: Option<String>,
     pub license_name: Option<String>,
     pub other_license_url: Option<String>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
@@ -50,6 +63,8 @@ pub struct Humanoid {
     pub lower_leg_twist: Option<f32>,
     pub feet_spacing: Option<f32>,
     pub has_translation_dof: Option<bool>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
@@ -60,4 +75,6 @@ pub struct FirstPerson {
     pub look_at_horizontal_outer: Option<LookAtCurve>,
     pub look_at_vertical_down: Option<LookAtCurve>,
     pub look_at_vertical_up: Option<LookAtCurve>,
+    #[serde(default)]
+    pub others: Map<String, Value>,
 }
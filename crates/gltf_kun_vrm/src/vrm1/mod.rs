@@ -1,2 +1,3 @@
 pub mod vrmc_materials_mtoon;
+pub mod vrmc_spring_bone;
 pub mod vrmc_vrm;
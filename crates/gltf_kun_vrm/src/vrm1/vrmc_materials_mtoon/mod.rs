@@ -1,7 +1,15 @@
-use gltf_kun::{extensions::Extension, graph::NodeIndex};
+use gltf_kun::{
+    extensions::{Extension, ExtensionImport},
+    graph::{ByteNode, Extensions, Graph, NodeIndex, gltf::GltfDocument},
+    io::format::gltf::GltfFormat,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::weight::from_bytes;
 
 pub const EXTENSION_NAME: &str = "VRMC_materials_mtoon";
 
+/// `VRMC_materials_mtoon` extension of a material.
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct VrmcMaterialsMtoon(pub NodeIndex);
 
@@ -17,8 +25,56 @@ impl From<VrmcMaterialsMtoon> for NodeIndex {
     }
 }
 
+/// Texture slots keep their glTF texture indices.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MtoonWeight(pub serde_vrm::vrm1::vrmc_materials_mtoon::VrmcMaterialsMtoon);
+
+impl From<&Vec<u8>> for MtoonWeight {
+    fn from(bytes: &Vec<u8>) -> Self {
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
+    }
+}
+
+impl From<&MtoonWeight> for Vec<u8> {
+    fn from(value: &MtoonWeight) -> Self {
+        serde_json::to_vec(value).expect("Failed to serialize weight")
+    }
+}
+
+impl ByteNode<MtoonWeight> for VrmcMaterialsMtoon {}
+
 impl Extension for VrmcMaterialsMtoon {
     fn name() -> &'static str {
         EXTENSION_NAME
     }
 }
+
+impl ExtensionImport<GltfDocument, GltfFormat> for VrmcMaterialsMtoon {
+    fn import(
+        graph: &mut Graph,
+        format: &mut GltfFormat,
+        doc: &GltfDocument,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let materials = doc.materials(graph);
+
+        for (material_json, material) in format.json.materials.iter().zip(materials) {
+            let Some(ext) = material_json
+                .extensions
+                .as_ref()
+                .and_then(|extensions| extensions.others.get(EXTENSION_NAME))
+            else {
+                continue;
+            };
+
+            let weight = MtoonWeight(serde_json::from_value(ext.clone())?);
+
+            let mtoon = material.create_extension::<Self>(graph);
+            mtoon.write(graph, &weight);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use gltf_kun::{extensions::Extension, graph::NodeIndex};
+
+pub const EXTENSION_NAME: &str = "VRMC_springBone";
+
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct VrmcSpringBone(pub NodeIndex);
+
+impl From<NodeIndex> for VrmcSpringBone {
+    fn from(index: NodeIndex) -> Self {
+        Self(index)
+    }
+}
+
+impl From<VrmcSpringBone> for NodeIndex {
+    fn from(spring_bone: VrmcSpringBone) -> Self {
+        spring_bone.0
+    }
+}
+
+impl Extension for VrmcSpringBone {
+    fn name() -> &'static str {
+        EXTENSION_NAME
+    }
+}
@@ -1,4 +1,11 @@
-use gltf_kun::{extensions::Extension, graph::NodeIndex};
+use gltf_kun::{
+    extensions::{Extension, ExtensionImport},
+    graph::{ByteNode, Extensions, Graph, NodeIndex, gltf::GltfDocument},
+    io::format::gltf::GltfFormat,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::weight::from_bytes;
 
 pub const EXTENSION_NAME: &str = "VRMC_vrm";
 
@@ -17,8 +24,53 @@ impl From<VrmcVrm> for NodeIndex {
     }
 }
 
+/// Nodes, images, and other properties keep their glTF indices.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct VrmcVrmWeight(pub serde_vrm::vrm1::vrmc_vrm::VrmcVrm);
+
+impl From<&Vec<u8>> for VrmcVrmWeight {
+    fn from(bytes: &Vec<u8>) -> Self {
+        from_bytes(bytes).unwrap_or_else(|e| {
+            tracing::error!("{}", e);
+            Self::default()
+        })
+    }
+}
+
+impl From<&VrmcVrmWeight> for Vec<u8> {
+    fn from(value: &VrmcVrmWeight) -> Self {
+        serde_json::to_vec(value).expect("Failed to serialize weight")
+    }
+}
+
+impl ByteNode<VrmcVrmWeight> for VrmcVrm {}
+
 impl Extension for VrmcVrm {
     fn name() -> &'static str {
         EXTENSION_NAME
     }
 }
+
+impl ExtensionImport<GltfDocument, GltfFormat> for VrmcVrm {
+    fn import(
+        graph: &mut Graph,
+        format: &mut GltfFormat,
+        doc: &GltfDocument,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(ext) = format
+            .json
+            .extensions
+            .as_ref()
+            .and_then(|extensions| extensions.others.get(EXTENSION_NAME))
+        else {
+            return Ok(());
+        };
+
+        let weight = VrmcVrmWeight(serde_json::from_value(ext.clone())?);
+
+        let vrm = doc.create_extension::<Self>(graph);
+        vrm.write(graph, &weight);
+
+        Ok(())
+    }
+}
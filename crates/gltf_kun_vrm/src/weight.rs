@@ -0,0 +1,43 @@
+//! Fallible reading of extension weights, which are stored as JSON bytes.
+
+use gltf_kun::graph::{ByteNode, Graph, Weight};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Failed to deserialize weight: {0}")]
+pub struct WeightError(#[from] pub serde_json::Error);
+
+/// Deserializes a weight, with empty bytes as the default weight.
+pub fn from_bytes<T: DeserializeOwned + Default>(bytes: &[u8]) -> Result<T, WeightError> {
+    if bytes.is_empty() {
+        return Ok(T::default());
+    }
+
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+/// Reads a weight without falling back to the default when its bytes are malformed,
+/// as [ByteNode::read] does.
+pub trait TryByteNode<T>: ByteNode<T>
+where
+    for<'a> T: From<&'a Vec<u8>>,
+    for<'a> &'a T: Into<Vec<u8>>,
+    T: DeserializeOwned + Default,
+{
+    fn try_read(self, graph: &Graph) -> Result<T, WeightError> {
+        match &graph[self.into()] {
+            Weight::Bytes(bytes) => from_bytes(bytes),
+            _ => panic!("Incorrect weight type"),
+        }
+    }
+}
+
+impl<N, T> TryByteNode<T> for N
+where
+    N: ByteNode<T>,
+    for<'a> T: From<&'a Vec<u8>>,
+    for<'a> &'a T: Into<Vec<u8>>,
+    T: DeserializeOwned + Default,
+{
+}
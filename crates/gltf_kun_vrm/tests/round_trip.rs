@@ -0,0 +1,157 @@
+//! Imports VRM 0.x avatars into a graph and exports them back out.
+//!
+//! Exported extensions are compared against golden files in `tests/golden`.
+//! Run with `UPDATE_GOLDEN=1` to regenerate them after an intended change.
+
+use std::{fs, path::Path};
+
+use futures_lite::future::block_on;
+use gltf_kun::{
+    graph::{Graph, gltf::GltfDocument},
+    io::format::{
+        glb::{GlbExport, GlbImport},
+        gltf::{GltfExport, GltfFormat, GltfImport},
+    },
+    io::resolver::DataUriResolver,
+};
+use gltf_kun_vrm::vrm0::{EXTENSION_NAME, Vrm};
+use serde_json::Value;
+
+#[test]
+fn synthetic() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/synthetic.gltf");
+    let mut graph = Graph::default();
+    let doc = block_on(GltfImport::<Vrm>::import_file(&mut graph, &path)).unwrap();
+    let original = serde_json::from_slice::<Value>(&fs::read(&path).unwrap()).unwrap();
+
+    round_trip("synthetic", graph, doc, &original);
+}
+
+#[test]
+fn missing_bind_mesh() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/synthetic.gltf");
+    let mut json = serde_json::from_slice::<Value>(&fs::read(&path).unwrap()).unwrap();
+    json["extensions"][EXTENSION_NAME]["blendShapeMaster"]["blendShapeGroups"][0]["binds"][0]["mesh"] =
+        99.into();
+
+    let format = GltfFormat {
+        json: serde_json::from_value(json).unwrap(),
+        ..Default::default()
+    };
+    let mut graph = Graph::default();
+    let doc = block_on(GltfImport::<Vrm>::import(
+        &mut graph,
+        format,
+        Some(DataUriResolver),
+    ))
+    .unwrap();
+
+    // The bad bind is dropped, the rest of the extension still imports.
+    let exported = export(&mut graph, doc);
+    let vrm: serde_vrm::vrm0::Vrm = serde_json::from_value(exported).unwrap();
+    let groups = vrm.blend_shape_master.unwrap().blend_shape_groups.unwrap();
+    assert!(groups[0].binds.clone().unwrap_or_default().is_empty());
+    assert!(!vrm.humanoid.unwrap().human_bones.unwrap().is_empty());
+}
+
+#[test]
+fn catbot() {
+    asset("catbot");
+}
+
+#[test]
+fn cool_loops() {
+    asset("cool_loops");
+}
+
+fn asset(name: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../assets")
+        .join(name)
+        .with_extension("vrm");
+    let bytes = fs::read(path).unwrap();
+
+    let mut graph = Graph::default();
+    let doc = block_on(GlbImport::<Vrm>::import_slice(&mut graph, &bytes)).unwrap();
+
+    let len = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+    let original = serde_json::from_slice::<Value>(&bytes[20..20 + len]).unwrap();
+
+    round_trip(name, graph, doc, &original);
+}
+
+fn round_trip(name: &str, mut graph: Graph, doc: GltfDocument, original: &Value) {
+    let original_json = &original["extensions"][EXTENSION_NAME];
+    let original: serde_vrm::vrm0::Vrm = serde_json::from_value(original_json.clone()).unwrap();
+
+    let exported = export(&mut graph, doc);
+    if let Some(path) = invented_key(original_json, &exported, "") {
+        panic!("{name} export wrote {path}, which is not in the original");
+    }
+
+    let vrm: serde_vrm::vrm0::Vrm = serde_json::from_value(exported.clone()).unwrap();
+    assert_eq!(vrm, original, "{name} changed going through the graph");
+
+    // Write a GLB and read it back, which must not change anything either.
+    let glb = GlbExport::<Vrm>::export(&mut graph, &doc).unwrap();
+    let mut graph = Graph::default();
+    let doc = block_on(GlbImport::<Vrm>::import_slice(&mut graph, &glb.0)).unwrap();
+    assert_eq!(
+        export(&mut graph, doc),
+        exported,
+        "{name} changed going through a GLB"
+    );
+
+    assert_golden(name, &exported);
+}
+
+fn export(graph: &mut Graph, doc: GltfDocument) -> Value {
+    let format: GltfFormat = GltfExport::<Vrm>::export(graph, &doc).unwrap();
+    format.json.extensions.unwrap().others[EXTENSION_NAME].clone()
+}
+
+/// Finds the first key in `exported` that `original` does not have at the same path,
+/// such as a `null` written for a missing property or a misspelled key.
+fn invented_key(original: &Value, exported: &Value, path: &str) -> Option<String> {
+    match (original, exported) {
+        (Value::Object(a), Value::Object(b)) => b.iter().find_map(|(key, value)| {
+            let path = format!("{path}.{key}");
+            match a.get(key) {
+                Some(original) => invented_key(original, value, &path),
+                None => Some(path),
+            }
+        }),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .enumerate()
+            .find_map(|(i, (a, b))| invented_key(a, b, &format!("{path}[{i}]"))),
+        _ => None,
+    }
+}
+
+fn assert_golden(name: &str, value: &Value) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name)
+        .with_extension("json");
+    let actual = serde_json::to_string_pretty(value).unwrap() + "\n";
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing {}, run with UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+
+    assert!(
+        expected == actual,
+        "{name} does not match {}, run with UPDATE_GOLDEN=1 if the change is intended",
+        path.display()
+    );
+}
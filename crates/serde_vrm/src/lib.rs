@@ -1,4 +1,9 @@
 //! [Serde](https://github.com/serde-rs/serde) types for [VRM](https://vrm.dev/en/).
+//!
+//! Object types keep any properties they don't model, such as `extras` or
+//! exporter-specific keys, in an `others` map, so they are written back out unchanged.
+//! Missing optional properties are left out rather than written as `null`,
+//! which glTF does not allow.
 
 pub mod vrm0;
 pub mod vrm1;
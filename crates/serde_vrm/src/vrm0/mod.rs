@@ -3,36 +3,66 @@
 use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Vrm {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub exporter_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub spec_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub meta: Option<Meta>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub humanoid: Option<Humanoid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub first_person: Option<FirstPerson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub blend_shape_master: Option<BlendShapeMaster>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub secondary_animation: Option<SecondaryAnimation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub material_properties: Option<Vec<MaterialProperty>>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Meta {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contact_information: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reference: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub texture: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allowed_user_name: Option<AllowedUserName>,
+    /// UniVRM writes the usage keys misspelled, as `violentUssageName`.
+    #[serde(rename = "violentUssageName", alias = "violentUsageName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub violent_usage_name: Option<Allow>,
+    #[serde(rename = "sexualUssageName", alias = "sexualUsageName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sexual_usage_name: Option<Allow>,
+    #[serde(rename = "commercialUssageName", alias = "commercialUsageName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub commercial_usage_name: Option<Allow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub other_permission_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub license_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub other_license_url: Option<String>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -51,23 +81,40 @@ pub enum Allow {
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Humanoid {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub human_bones: Option<Vec<Bone>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub arm_stretch: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub leg_stretch: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub upper_arm_twist: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lower_arm_twist: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub upper_leg_twist: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lower_leg_twist: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub feet_spacing: Option<f32>,
+    #[serde(alias = "hasTranslationDof", rename = "hasTranslationDoF")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub has_translation_dof: Option<bool>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Bone {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bone: Option<BoneName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub node: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub use_default_values: Option<bool>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[cfg(feature = "bevy")]
@@ -201,21 +248,34 @@ impl Display for BoneName {
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FirstPerson {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub first_person_bone: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub first_person_bone_offset: Option<Vec3>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mesh_annotations: Option<Vec<MeshAnnotation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub look_at_type_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub look_at_horizontal_inner: Option<LookAtCurve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub look_at_horizontal_outer: Option<LookAtCurve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub look_at_vertical_down: Option<LookAtCurve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub look_at_vertical_up: Option<LookAtCurve>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MeshAnnotation {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mesh: Option<u32>,
     pub first_person_flag: FirstPersonFlag,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[cfg_attr(
@@ -224,41 +284,55 @@ pub struct MeshAnnotation {
     reflect(Component)
 )]
 #[derive(Copy, Clone, Debug, Default, Hash, Deserialize, PartialEq, Eq, Serialize)]
-#[serde(rename_all = "camelCase")]
 pub enum FirstPersonFlag {
     #[default]
-    #[serde(alias = "Auto")]
+    #[serde(alias = "auto")]
     Auto,
-    #[serde(alias = "Both")]
+    #[serde(alias = "both")]
     Both,
-    #[serde(alias = "FirstPersonOnly")]
+    #[serde(alias = "firstPersonOnly")]
     FirstPersonOnly,
-    #[serde(alias = "ThirdPersonOnly")]
+    #[serde(alias = "thirdPersonOnly")]
     ThirdPersonOnly,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LookAtCurve {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub curve: Option<[f32; 8]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub x_range: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub y_range: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BlendShapeMaster {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub blend_shape_groups: Option<Vec<BlendShapeGroup>>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BlendShapeGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub preset_name: Option<PresetName>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub binds: Option<Vec<Bind>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub material_values: Option<Vec<MaterialBind>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_binary: Option<bool>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -304,49 +378,82 @@ pub enum PresetName {
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MaterialBind {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub material_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub property_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub target_value: Option<Vec<f32>>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Bind {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mesh: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub weight: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SecondaryAnimation {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bone_groups: Option<Vec<BoneGroup>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub collider_groups: Option<Vec<ColliderGroup>>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BoneGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stiffiness: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gravity_power: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gravity_dir: Option<Vec3>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub drag_force: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub center: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub hit_radius: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bones: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub collider_groups: Option<Vec<u32>>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct ColliderGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub node: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub colliders: Option<Vec<Collider>>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Collider {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub offset: Option<Vec3>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub radius: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
@@ -358,20 +465,30 @@ pub struct Vec3 {
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct MaterialProperty {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     #[serde(rename = "renderQueue")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub render_queue: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shader: Option<Shader>,
     #[serde(rename = "floatProperties")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub float: Option<FloatProperties>,
     #[serde(rename = "vectorProperties")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub vector: Option<VectorProperties>,
     #[serde(rename = "textureProperties")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub texture: Option<TextureProperties>,
     #[serde(rename = "keywordMap")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub keyword_map: Option<KeywordMap>,
     #[serde(rename = "tagMap")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tag_map: Option<TagMap>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -394,79 +511,124 @@ pub enum Shader {
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct FloatProperties {
     #[serde(rename = "_ShadeShift")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shade_shift: Option<f32>,
     #[serde(rename = "_ShadeToony")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shade_toony: Option<f32>,
+    #[serde(rename = "_BlendMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blend_mode: Option<f32>,
     #[serde(rename = "_Cutoff")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cutoff: Option<f32>,
     #[serde(rename = "_IndirectLightIntensity")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gi_intensity_factor: Option<f32>,
     #[serde(rename = "_BumpScale")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub normal_scale: Option<f32>,
     #[serde(rename = "_CullMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub double_sided: Option<f32>,
     #[serde(rename = "_ReceiveShadowRate")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shade_receive_multiply_factor: Option<f32>,
     #[serde(rename = "_RimLightingMix")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rim_lighting_mix_factor: Option<f32>,
     #[serde(rename = "_RimFresnelPower")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rim_fresnel_power_factor: Option<f32>,
     #[serde(rename = "_RimLift")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rim_lift_factor: Option<f32>,
     #[serde(rename = "_OutlineWidth")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_factor: Option<f32>,
     #[serde(rename = "_OutlineWidthMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_width_mode: Option<f32>,
     #[serde(rename = "_OutlineScaledMaxDistance")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_scaled_max_distance_factor: Option<f32>,
+    #[serde(rename = "_OutlineColorMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline_color_mode: Option<f32>,
     #[serde(rename = "_OutlineLightingMix")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_lighting_mix_factor: Option<f32>,
     #[serde(rename = "_UvAnimScrollX")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uv_animation_scroll_x_speed_factor: Option<f32>,
     #[serde(rename = "_UvAnimScrollY")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uv_animation_scroll_y_speed_factor: Option<f32>,
     #[serde(rename = "_UvAnimRotation")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uv_animation_rotation_speed_factor: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct TextureProperties {
     #[serde(rename = "_MainTex")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub base_color: Option<u32>,
     #[serde(rename = "_ShadeTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shade: Option<u32>,
     #[serde(rename = "_BumpMap")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub normal: Option<u32>,
     #[serde(rename = "_SphereAdd")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub additive: Option<u32>,
     #[serde(rename = "_EmissionMap")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub emissive: Option<u32>,
     #[serde(rename = "_RimTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rim_multiply: Option<u32>,
     #[serde(rename = "_OutlineWidthTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_width_multiply_texture: Option<u32>,
     #[serde(rename = "_UvAnimMaskTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uv_animation_mask_texture: Option<u32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct VectorProperties {
     #[serde(rename = "_Color")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<[f32; 4]>,
     #[serde(rename = "_EmissionColor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub emissive_factor: Option<[f32; 4]>,
     #[serde(rename = "_OutlineColor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_color: Option<[f32; 4]>,
     #[serde(rename = "_ShadeColor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shade_color: Option<[f32; 4]>,
     #[serde(rename = "_RimColor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rim_factor: Option<[f32; 4]>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct TagMap {
     #[serde(rename = "RenderType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub render_type: Option<RenderType>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -479,15 +641,23 @@ pub enum RenderType {
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct KeywordMap {
     #[serde(rename = "_ALPHABLEND_ON")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alpha_blend: Option<bool>,
     #[serde(rename = "_ALPHATEST_ON")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alpha_test: Option<bool>,
     #[serde(rename = "_NORMALMAP")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub normal_map: Option<bool>,
     #[serde(rename = "MTOON_OUTLINE_COLOR_FIXED")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_color_fixed: Option<bool>,
     #[serde(rename = "MTOON_OUTLINE_COLOR_MIXED")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_color_mixed: Option<bool>,
     #[serde(rename = "MTOON_OUTLINE_WIDTH_WORLD")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub outline_width_world: Option<bool>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
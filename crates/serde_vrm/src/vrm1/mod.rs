@@ -1,4 +1,5 @@
 //! VRM 1.0 types.
 
 pub mod vrmc_materials_mtoon;
+pub mod vrmc_spring_bone;
 pub mod vrmc_vrm;
@@ -1 +1,91 @@
-pub struct VrmcMaterialsMtoon;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub const SPEC_VERSION: &str = "1.0";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VrmcMaterialsMtoon {
+    pub spec_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transparent_with_z_write: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub render_queue_offset_number: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shade_color_factor: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shade_multiply_texture: Option<TextureInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shading_shift_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shading_shift_texture: Option<ShadingShiftTexture>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shading_toony_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gi_equalization_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matcap_factor: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matcap_texture: Option<TextureInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parametric_rim_color_factor: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rim_multiply_texture: Option<TextureInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rim_lighting_mix_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parametric_rim_fresnel_power_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parametric_rim_lift_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline_width_mode: Option<OutlineWidthMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline_width_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline_width_multiply_texture: Option<TextureInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline_color_factor: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline_lighting_mix_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_animation_mask_texture: Option<TextureInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_animation_scroll_x_speed_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_animation_scroll_y_speed_factor: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_animation_rotation_speed_factor: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureInfo {
+    pub index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tex_coord: Option<u32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadingShiftTexture {
+    pub index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tex_coord: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutlineWidthMode {
+    #[default]
+    None,
+    WorldCoordinates,
+    ScreenCoordinates,
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub const SPEC_VERSION: &str = "1.0";
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VrmcSpringBone {
+    pub spec_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colliders: Option<Vec<Collider>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collider_groups: Option<Vec<ColliderGroup>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub springs: Option<Vec<Spring>>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Collider {
+    pub node: u32,
+    pub shape: Shape,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Shape {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sphere: Option<Sphere>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capsule: Option<Capsule>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Sphere {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub radius: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Capsule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub radius: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tail: Option<[f32; 3]>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ColliderGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Indices into the colliders.
+    pub colliders: Vec<u32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Spring {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Chain of joints, from the root to the tip.
+    pub joints: Vec<Joint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collider_groups: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub center: Option<u32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Joint {
+    pub node: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_radius: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stiffness: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gravity_power: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gravity_dir: Option<[f32; 3]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drag_force: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
@@ -1,4 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub const SPEC_VERSION: &str = "1.0";
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct VrmcVrm {
@@ -7,57 +12,209 @@ pub struct VrmcVrm {
     pub meta: Meta,
     pub humanoid: Humanoid,
     #[serde(rename = "firstPerson")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub first_person: Option<FirstPerson>,
     #[serde(rename = "lookAt")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub look_at: Option<LookAt>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub expressions: Option<Expressions>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Meta {
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
     pub authors: Vec<String>,
     #[serde(rename = "copyrightInformation")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub copy_right_information: Option<String>,
     #[serde(rename = "contactInformation")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contact_information: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reference: Option<Vec<String>>,
     #[serde(rename = "thirdPartyLicenses")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub third_party_licenses: Option<String>,
     #[serde(rename = "thumbnailImage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub thumbnail_image: Option<u32>,
     #[serde(rename = "licenseUrl")]
     pub license_url: String,
     #[serde(rename = "avatarPermission")]
     pub avatar_permission: String,
     #[serde(rename = "allowExcessivelyViolentUsage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_excessively_violent_usage: Option<bool>,
     #[serde(rename = "allowExcessivelySexualUsage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_excessively_sexual_usage: Option<bool>,
     #[serde(rename = "commercialUsage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub commercial_usage: Option<String>,
     #[serde(rename = "allowPoliticalOrReligiousUsage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_political_or_religious_usage: Option<bool>,
     #[serde(rename = "allowAntisocialOrHateUsage")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_antisocial_or_hate_usage: Option<bool>,
     #[serde(rename = "creditNotation")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub credit_notation: Option<String>,
     #[serde(rename = "allowRedistribution")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_redistribution: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub modification: Option<String>,
     #[serde(rename = "otherLicenseUrl")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub other_license_url: Option<String>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Humanoid {
+    /// Human bones by name, such as `hips` or `leftThumbMetacarpal`.
+    #[serde(default, rename = "humanBones")]
+    pub human_bones: BTreeMap<String, HumanBone>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct HumanBone {
+    pub node: u32,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
-pub struct Humanoid {}
+pub struct FirstPerson {
+    #[serde(default, rename = "meshAnnotations")]
+    pub mesh_annotations: Vec<MeshAnnotation>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
-pub struct FirstPerson {}
+pub struct MeshAnnotation {
+    pub node: u32,
+    /// One of `auto`, `both`, `thirdPersonOnly`, or `firstPersonOnly`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
-pub struct LookAt {}
+pub struct LookAt {
+    #[serde(rename = "offsetFromHeadBone")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset_from_head_bone: Option<[f32; 3]>,
+    /// Either `bone` or `expression`.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    #[serde(rename = "rangeMapHorizontalInner")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range_map_horizontal_inner: Option<RangeMap>,
+    #[serde(rename = "rangeMapHorizontalOuter")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range_map_horizontal_outer: Option<RangeMap>,
+    #[serde(rename = "rangeMapVerticalDown")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range_map_vertical_down: Option<RangeMap>,
+    #[serde(rename = "rangeMapVerticalUp")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range_map_vertical_up: Option<RangeMap>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RangeMap {
+    #[serde(rename = "inputMaxValue")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_max_value: Option<f32>,
+    #[serde(rename = "outputScale")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_scale: Option<f32>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
 
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
-pub struct Expressions {}
+pub struct Expressions {
+    /// Preset expressions by name, such as `happy` or `blinkLeft`.
+    #[serde(default)]
+    pub preset: BTreeMap<String, Expression>,
+    #[serde(default)]
+    pub custom: BTreeMap<String, Expression>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Expression {
+    #[serde(rename = "morphTargetBinds")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub morph_target_binds: Option<Vec<MorphTargetBind>>,
+    #[serde(rename = "materialColorBinds")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub material_color_binds: Option<Vec<MaterialColorBind>>,
+    #[serde(rename = "textureTransformBinds")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub texture_transform_binds: Option<Vec<TextureTransformBind>>,
+    #[serde(rename = "isBinary")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_binary: Option<bool>,
+    /// One of `none`, `block`, or `blend`.
+    #[serde(rename = "overrideBlink")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_blink: Option<String>,
+    #[serde(rename = "overrideLookAt")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_look_at: Option<String>,
+    #[serde(rename = "overrideMouth")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_mouth: Option<String>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MorphTargetBind {
+    pub node: u32,
+    pub index: u32,
+    pub weight: f32,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MaterialColorBind {
+    pub material: u32,
+    /// One of `color`, `emissionColor`, `shadeColor`, `matcapColor`, `rimColor`, or `outlineColor`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(rename = "targetValue")]
+    pub target_value: [f32; 4],
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TextureTransformBind {
+    pub material: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<[f32; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<[f32; 2]>,
+    #[serde(flatten)]
+    pub others: Map<String, Value>,
+}
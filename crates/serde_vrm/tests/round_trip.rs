@@ -0,0 +1,153 @@
+//! Round-trips VRM extension JSON through the serde types.
+
+use std::{fs, path::Path};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use serde_vrm::{
+    vrm0::{Allow, Meta, Vrm},
+    vrm1::{
+        vrmc_materials_mtoon::VrmcMaterialsMtoon, vrmc_spring_bone::VrmcSpringBone,
+        vrmc_vrm::VrmcVrm,
+    },
+};
+
+const ASSETS: &[&str] = &["catbot.vrm", "cool_loops.vrm"];
+
+#[test]
+fn vrm0_fixture() {
+    round_trip_exact::<Vrm>(&fixture("vrm0.json"));
+}
+
+#[test]
+fn vrmc_vrm_fixture() {
+    round_trip_exact::<VrmcVrm>(&fixture("vrmc_vrm.json"));
+}
+
+#[test]
+fn vrmc_spring_bone_fixture() {
+    round_trip_exact::<VrmcSpringBone>(&fixture("vrmc_spring_bone.json"));
+}
+
+#[test]
+fn vrmc_materials_mtoon_fixture() {
+    round_trip_exact::<VrmcMaterialsMtoon>(&fixture("vrmc_materials_mtoon.json"));
+}
+
+#[test]
+fn assets() {
+    for name in ASSETS {
+        let json = asset_extension(name, "VRM");
+        let vrm: Vrm = serde_json::from_value(json.clone()).unwrap();
+        let out = serde_json::to_value(&vrm).unwrap();
+
+        if let Some(path) = mismatch(&json, &out, name) {
+            panic!("round trip changed {path}");
+        }
+
+        let again: Vrm = serde_json::from_value(out).unwrap();
+        assert_eq!(vrm, again, "{name}");
+    }
+}
+
+#[test]
+fn catbot_meta() {
+    let json = asset_extension("catbot.vrm", "VRM");
+    let meta = serde_json::from_value::<Vrm>(json).unwrap().meta.unwrap();
+
+    assert_eq!(meta.violent_usage_name, Some(Allow::Allow));
+    assert_eq!(meta.sexual_usage_name, Some(Allow::Allow));
+    assert_eq!(meta.commercial_usage_name, Some(Allow::Disallow));
+    assert!(meta.others.is_empty(), "{:?}", meta.others);
+}
+
+#[test]
+fn usage_name_spelling() {
+    let meta = serde_json::from_value::<Meta>(serde_json::json!({
+        "violentUsageName": "Allow",
+        "sexualUsageName": "Allow",
+        "commercialUsageName": "Allow",
+    }))
+    .unwrap();
+
+    assert_eq!(meta.violent_usage_name, Some(Allow::Allow));
+    assert_eq!(meta.sexual_usage_name, Some(Allow::Allow));
+    assert_eq!(meta.commercial_usage_name, Some(Allow::Allow));
+
+    // Written the way UniVRM reads them.
+    let out = serde_json::to_value(&meta).unwrap();
+    assert_eq!(out["violentUssageName"], "Allow");
+    assert_eq!(out["sexualUssageName"], "Allow");
+    assert_eq!(out["commercialUssageName"], "Allow");
+}
+
+fn round_trip_exact<T: DeserializeOwned + Serialize + PartialEq + std::fmt::Debug>(json: &Value) {
+    let value: T = serde_json::from_value(json.clone()).unwrap();
+    let out = serde_json::to_value(&value).unwrap();
+
+    if let Some(path) = mismatch(json, &out, "") {
+        panic!("round trip changed {path}");
+    }
+
+    let again: T = serde_json::from_value(out).unwrap();
+    assert_eq!(value, again);
+}
+
+/// Finds the first path where `out` differs from `original`.
+/// Numbers are compared at f32 precision.
+fn mismatch(original: &Value, out: &Value, path: &str) -> Option<String> {
+    match (original, out) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, value) in a {
+                let path = format!("{path}.{key}");
+                let Some(out) = b.get(key) else {
+                    return Some(path);
+                };
+
+                if let Some(path) = mismatch(value, out, &path) {
+                    return Some(path);
+                }
+            }
+
+            b.keys()
+                .find(|key| !a.contains_key(*key))
+                .map(|key| format!("{path}.{key}"))
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                return Some(path.to_string());
+            }
+
+            a.iter()
+                .zip(b)
+                .enumerate()
+                .find_map(|(i, (a, b))| mismatch(a, b, &format!("{path}[{i}]")))
+        }
+        (Value::Number(a), Value::Number(b)) => {
+            let a = a.as_f64().map(|n| n as f32);
+            let b = b.as_f64().map(|n| n as f32);
+            (a != b).then(|| path.to_string())
+        }
+        (a, b) => (a != b).then(|| path.to_string()),
+    }
+}
+
+fn fixture(name: &str) -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    serde_json::from_slice(&fs::read(path).unwrap()).unwrap()
+}
+
+/// Reads an extension from the JSON chunk of a bundled GLB asset.
+fn asset_extension(name: &str, extension: &str) -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../assets")
+        .join(name);
+    let bytes = fs::read(path).unwrap();
+
+    let len = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+    let json: Value = serde_json::from_slice(&bytes[20..20 + len]).unwrap();
+
+    json["extensions"][extension].clone()
+}
@@ -0,0 +1,1005 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    f32::consts::TAU,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, ensure};
+use gltf_kun::{
+    extensions::ExtensionExport,
+    graph::{
+        ByteNode, Extensions, Graph, GraphNodeWeight,
+        gltf::{
+            Accessor, GltfDocument, Node, Texture,
+            accessor::{ComponentType, Type},
+            animation::TargetPath,
+            node::Quat,
+            primitive::Semantic,
+        },
+    },
+    io::format::gltf::GltfFormat,
+};
+use gltf_kun_vrm::{
+    vrm0::{Vrm, material_property::MaterialProperty},
+    vrm1::{vrmc_materials_mtoon, vrmc_spring_bone, vrmc_vrm},
+};
+use serde_vrm::{
+    vrm0::{Allow, AllowedUserName, BoneName, FirstPersonFlag, LookAtCurve, PresetName, Shader},
+    vrm1::{
+        vrmc_materials_mtoon::{OutlineWidthMode, TextureInfo, VrmcMaterialsMtoon},
+        vrmc_spring_bone::{Collider, ColliderGroup, Joint, Shape, Sphere, Spring, VrmcSpringBone},
+        vrmc_vrm::{
+            Expression, Expressions, FirstPerson, HumanBone, Humanoid, LookAt, MaterialColorBind,
+            MeshAnnotation, Meta, MorphTargetBind, RangeMap, TextureTransformBind, VrmcVrm,
+        },
+    },
+};
+
+use crate::io::{self, VrmVersion, json_name};
+
+const LICENSE_URL: &str = "https://vrm.dev/licenses/1.0/";
+const KHR_MATERIALS_UNLIT: &str = "KHR_materials_unlit";
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the VRM 0.x avatar.
+    input: PathBuf,
+    /// Where to write the VRM 1.0 avatar.
+    output: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let mut avatar = io::load(&args.input)?;
+
+    ensure!(
+        avatar.version() == Some(VrmVersion::V0),
+        "convert only supports VRM 0.x avatars"
+    );
+
+    let graph = &mut avatar.graph;
+    let doc = avatar.doc;
+
+    doc.get_extension::<Vrm>(graph)
+        .context("failed to import the VRM extension, run `vrm validate` for details")?;
+
+    rotate(graph, doc);
+
+    io::save::<Vrm1>(graph, doc, &args.output)?;
+
+    println!("wrote {}", args.output.display());
+    Ok(())
+}
+
+/// VRM 0.x avatars face -Z, while VRM 1.0 avatars face +Z.
+/// Turns the avatar half a revolution around Y by negating X and Z in every
+/// node transform and in the vertex and animation data.
+fn rotate(graph: &mut Graph, doc: GltfDocument) {
+    let mut accessors = HashSet::new();
+
+    for mesh in doc.meshes(graph) {
+        for primitive in mesh.primitives(graph) {
+            accessors.extend(
+                primitive
+                    .attributes(graph)
+                    .into_iter()
+                    .filter(|(semantic, _)| is_directional(semantic))
+                    .map(|(_, a)| a),
+            );
+
+            for target in primitive.morph_targets(graph) {
+                accessors.extend(
+                    target
+                        .attributes(graph)
+                        .into_iter()
+                        .filter(|(semantic, _)| is_directional(semantic))
+                        .map(|(_, a)| a),
+                );
+            }
+        }
+    }
+
+    for skin in doc.skins(graph) {
+        accessors.extend(skin.inverse_bind_matrices(graph));
+    }
+
+    for animation in doc.animations(graph) {
+        for channel in animation.channels(graph) {
+            let path = channel.get(graph).path;
+            if !matches!(path, TargetPath::Translation | TargetPath::Rotation) {
+                continue;
+            }
+
+            if let Some(sampler) = channel.sampler(graph) {
+                accessors.extend(sampler.output(graph));
+            }
+        }
+    }
+
+    for accessor in accessors {
+        rotate_accessor(graph, accessor);
+    }
+
+    for mut node in doc.nodes(graph) {
+        let weight = node.get_mut(graph);
+        weight.translation.x = -weight.translation.x;
+        weight.translation.z = -weight.translation.z;
+
+        let r = weight.rotation;
+        weight.rotation = Quat::from_xyzw(-r.x, r.y, -r.z, r.w);
+    }
+}
+
+fn is_directional(semantic: &Semantic) -> bool {
+    matches!(
+        semantic,
+        Semantic::Positions | Semantic::Normals | Semantic::Tangents
+    )
+}
+
+/// Sign of each element of a column-major matrix conjugated by the rotation.
+const MAT4_SIGNS: [f32; 16] = [
+    1.0, -1.0, 1.0, -1.0, //
+    -1.0, 1.0, -1.0, 1.0, //
+    1.0, -1.0, 1.0, -1.0, //
+    -1.0, 1.0, -1.0, 1.0, //
+];
+
+/// Rotates vectors, tangents or quaternions, and matrices, based on the element type.
+fn rotate_accessor(graph: &mut Graph, mut accessor: Accessor) {
+    let weight = accessor.get_mut(graph);
+
+    if weight.component_type != ComponentType::F32 {
+        eprintln!(
+            "warning: skipping accessor {:?} with {:?} components",
+            weight.name.as_deref().unwrap_or_default(),
+            weight.component_type
+        );
+        return;
+    }
+
+    let signs: &[f32] = match weight.element_type {
+        Type::Vec3 => &[-1.0, 1.0, -1.0],
+        Type::Vec4 => &[-1.0, 1.0, -1.0, 1.0],
+        Type::Mat4 => &MAT4_SIGNS,
+        _ => return,
+    };
+
+    for (i, bytes) in weight.data.chunks_exact_mut(4).enumerate() {
+        if signs[i % signs.len()] < 0.0 {
+            let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            bytes.copy_from_slice(&(-value).to_le_bytes());
+        }
+    }
+}
+
+fn rotate_vec3(v: serde_vrm::vrm0::Vec3) -> [f32; 3] {
+    [-v.x, v.y, -v.z]
+}
+
+/// Writes the VRM 0.x extension of a document as VRM 1.0 extensions.
+struct Vrm1;
+
+impl ExtensionExport<GltfDocument, GltfFormat> for Vrm1 {
+    fn export(
+        graph: &mut Graph,
+        doc: &GltfDocument,
+        format: &mut GltfFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(vrm) = doc.get_extension::<Vrm>(graph) else {
+            return Ok(());
+        };
+
+        let graph = &*graph;
+        let doc = *doc;
+
+        let ext = VrmcVrm {
+            spec_version: serde_vrm::vrm1::vrmc_vrm::SPEC_VERSION.to_string(),
+            meta: convert_meta(graph, doc, vrm),
+            humanoid: convert_humanoid(graph, doc, vrm),
+            first_person: Some(convert_first_person(graph, doc, vrm)),
+            look_at: Some(convert_look_at(graph, vrm)),
+            expressions: Some(convert_expressions(graph, doc, vrm)),
+            ..Default::default()
+        };
+        let spring_bone = convert_spring_bone(graph, doc, vrm);
+
+        let extensions = format.json.extensions.get_or_insert_with(Default::default);
+        extensions.others.insert(
+            vrmc_vrm::EXTENSION_NAME.to_string(),
+            serde_json::to_value(ext)?,
+        );
+        extensions.others.insert(
+            vrmc_spring_bone::EXTENSION_NAME.to_string(),
+            serde_json::to_value(spring_bone)?,
+        );
+
+        let mut used = vec![vrmc_vrm::EXTENSION_NAME, vrmc_spring_bone::EXTENSION_NAME];
+        used.extend(convert_materials(graph, doc, vrm, format)?);
+
+        for name in used {
+            if !format.json.extensions_used.iter().any(|e| e == name) {
+                format.json.extensions_used.push(name.to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn convert_meta(graph: &Graph, doc: GltfDocument, vrm: Vrm) -> Meta {
+    let meta = vrm.read(graph).meta;
+
+    let allowed = |value: Option<Allow>| Some(value == Some(Allow::Allow));
+
+    let license = meta.license_name.as_deref().unwrap_or_default();
+    let is_cc = license.starts_with("CC");
+    let is_cc0 = license == "CC0";
+
+    Meta {
+        name: meta.title.unwrap_or_default(),
+        version: meta.version,
+        authors: meta.author.into_iter().collect(),
+        contact_information: meta.contact_information,
+        reference: meta.reference.map(|r| vec![r]),
+        thumbnail_image: vrm
+            .thumbnail(graph)
+            .and_then(|t| t.image(graph))
+            .and_then(|i| doc.image_index(graph, i))
+            .map(|i| i as u32),
+        license_url: LICENSE_URL.to_string(),
+        avatar_permission: match meta.allowed_user_name {
+            Some(AllowedUserName::Everyone) => "everyone",
+            Some(AllowedUserName::ExplicitlyLicensedPerson) => "onlySeparatelyLicensedPerson",
+            _ => "onlyAuthor",
+        }
+        .to_string(),
+        allow_excessively_violent_usage: allowed(meta.violent_usage_name),
+        allow_excessively_sexual_usage: allowed(meta.sexual_usage_name),
+        commercial_usage: Some(
+            match meta.commercial_usage_name {
+                Some(Allow::Allow) => "corporation",
+                _ => "personalNonProfit",
+            }
+            .to_string(),
+        ),
+        allow_political_or_religious_usage: Some(false),
+        allow_antisocial_or_hate_usage: Some(false),
+        credit_notation: Some(
+            if is_cc && !is_cc0 {
+                "required"
+            } else {
+                "unnecessary"
+            }
+            .to_string(),
+        ),
+        allow_redistribution: Some(is_cc),
+        modification: Some(
+            if is_cc0 || (is_cc && !license.contains("ND")) {
+                "allowModificationRedistribution"
+            } else {
+                "prohibited"
+            }
+            .to_string(),
+        ),
+        other_license_url: meta.other_license_url,
+        ..Default::default()
+    }
+}
+
+fn convert_humanoid(graph: &Graph, doc: GltfDocument, vrm: Vrm) -> Humanoid {
+    let human_bones = vrm
+        .human_bones(graph)
+        .into_iter()
+        .filter_map(|bone| {
+            let name = bone.read(graph).name?;
+            let node = doc.node_index(graph, bone.node(graph)?)?;
+            Some((
+                bone_name(name),
+                HumanBone {
+                    node: node as u32,
+                    ..Default::default()
+                },
+            ))
+        })
+        .collect();
+
+    Humanoid {
+        human_bones,
+        ..Default::default()
+    }
+}
+
+/// VRM 1.0 added a metacarpal at the base of the thumb, shifting the names of
+/// the thumb bones down by one.
+fn bone_name(name: BoneName) -> String {
+    let name = json_name(&name);
+
+    if let Some(side) = name.strip_suffix("ThumbProximal") {
+        format!("{side}ThumbMetacarpal")
+    } else if let Some(side) = name.strip_suffix("ThumbIntermediate") {
+        format!("{side}ThumbProximal")
+    } else {
+        name
+    }
+}
+
+/// VRM 0.x annotates meshes, while VRM 1.0 annotates the nodes using them.
+fn convert_first_person(graph: &Graph, doc: GltfDocument, vrm: Vrm) -> FirstPerson {
+    let mut mesh_annotations = Vec::new();
+
+    for annotation in vrm.mesh_annotations(graph) {
+        let type_ = match annotation.read(graph).first_person_flag {
+            FirstPersonFlag::Auto => "auto",
+            FirstPersonFlag::Both => "both",
+            FirstPersonFlag::FirstPersonOnly => "firstPersonOnly",
+            FirstPersonFlag::ThirdPersonOnly => "thirdPersonOnly",
+        };
+
+        let Some(mesh) = annotation.mesh(graph) else {
+            continue;
+        };
+
+        mesh_annotations.extend(
+            mesh.nodes(graph)
+                .into_iter()
+                .filter_map(|node| doc.node_index(graph, node))
+                .map(|node| MeshAnnotation {
+                    node: node as u32,
+                    type_: type_.to_string(),
+                    ..Default::default()
+                }),
+        );
+    }
+
+    FirstPerson {
+        mesh_annotations,
+        ..Default::default()
+    }
+}
+
+fn convert_look_at(graph: &Graph, vrm: Vrm) -> LookAt {
+    let first_person = vrm.read(graph).first_person;
+
+    let range_map = |curve: Option<LookAtCurve>| {
+        curve.map(|curve| RangeMap {
+            input_max_value: curve.x_range,
+            output_scale: curve.y_range,
+            ..Default::default()
+        })
+    };
+
+    LookAt {
+        offset_from_head_bone: Some(rotate_vec3(first_person.first_person_bone_offset)),
+        type_: Some(
+            match first_person.look_at_type_name.as_deref() {
+                Some("BlendShape") => "expression",
+                _ => "bone",
+            }
+            .to_string(),
+        ),
+        range_map_horizontal_inner: range_map(first_person.look_at_horizontal_inner),
+        range_map_horizontal_outer: range_map(first_person.look_at_horizontal_outer),
+        range_map_vertical_down: range_map(first_person.look_at_vertical_down),
+        range_map_vertical_up: range_map(first_person.look_at_vertical_up),
+        ..Default::default()
+    }
+}
+
+fn convert_expressions(graph: &Graph, doc: GltfDocument, vrm: Vrm) -> Expressions {
+    // Material values refer to materials by name, preferring the VRM
+    // material property name over the glTF one.
+    let mut materials = HashMap::new();
+    for (i, material) in doc.materials(graph).into_iter().enumerate() {
+        if let Some(name) = material.get(graph).name.clone() {
+            materials.insert(name, i as u32);
+        }
+    }
+    for property in vrm.material_properties(graph) {
+        if let Some(name) = property.read(graph).name
+            && let Some(i) = property
+                .material(graph)
+                .and_then(|m| doc.material_index(graph, m))
+        {
+            materials.insert(name, i as u32);
+        }
+    }
+
+    let mut expressions = Expressions::default();
+
+    for (i, group) in vrm.blend_shape_groups(graph).into_iter().enumerate() {
+        let weight = group.read(graph);
+
+        let mut morph_target_binds = Vec::new();
+        for bind in group.binds(graph) {
+            let bind_weight = bind.read(graph);
+            let (Some(mesh), Some(index)) = (bind.mesh(graph), bind_weight.index) else {
+                continue;
+            };
+
+            morph_target_binds.extend(
+                mesh.nodes(graph)
+                    .into_iter()
+                    .filter_map(|node| doc.node_index(graph, node))
+                    .map(|node| MorphTargetBind {
+                        node: node as u32,
+                        index,
+                        weight: bind_weight.weight.unwrap_or(100.0) / 100.0,
+                        ..Default::default()
+                    }),
+            );
+        }
+
+        let mut material_color_binds = Vec::new();
+        let mut texture_transform_binds = Vec::new();
+        for value in weight.material_values {
+            let Some(&material) = value.material_name.as_ref().and_then(|n| materials.get(n))
+            else {
+                continue;
+            };
+
+            let mut target = [0.0; 4];
+            for (t, v) in target
+                .iter_mut()
+                .zip(value.target_value.unwrap_or_default())
+            {
+                *t = v;
+            }
+
+            match value.property_name.as_deref() {
+                Some("_MainTex_ST") => {
+                    // Unity's V axis points up, so the offset is measured from the other edge.
+                    let [scale_x, scale_y, offset_x, offset_y] = target;
+                    texture_transform_binds.push(TextureTransformBind {
+                        material,
+                        scale: Some([scale_x, scale_y]),
+                        offset: Some([offset_x, 1.0 - scale_y - offset_y]),
+                        ..Default::default()
+                    });
+                }
+                Some(property) => {
+                    let type_ = match property {
+                        "_Color" => "color",
+                        "_EmissionColor" => "emissionColor",
+                        "_ShadeColor" => "shadeColor",
+                        "_RimColor" => "rimColor",
+                        "_OutlineColor" => "outlineColor",
+                        _ => continue,
+                    };
+                    material_color_binds.push(MaterialColorBind {
+                        material,
+                        type_: type_.to_string(),
+                        target_value: target,
+                        ..Default::default()
+                    });
+                }
+                None => {}
+            }
+        }
+
+        let expression = Expression {
+            morph_target_binds: Some(morph_target_binds),
+            material_color_binds: Some(material_color_binds),
+            texture_transform_binds: Some(texture_transform_binds),
+            is_binary: weight.is_binary,
+            ..Default::default()
+        };
+
+        match weight.preset_name.and_then(preset_name) {
+            Some(preset) if !expressions.preset.contains_key(preset) => {
+                expressions.preset.insert(preset.to_string(), expression);
+            }
+            _ => {
+                let mut name = weight.name.unwrap_or_else(|| format!("expression{i}"));
+                while expressions.custom.contains_key(&name) {
+                    name.push('_');
+                }
+                expressions.custom.insert(name, expression);
+            }
+        }
+    }
+
+    expressions
+}
+
+fn preset_name(preset: PresetName) -> Option<&'static str> {
+    Some(match preset {
+        PresetName::Neutral => "neutral",
+        PresetName::A => "aa",
+        PresetName::I => "ih",
+        PresetName::U => "ou",
+        PresetName::E => "ee",
+        PresetName::O => "oh",
+        PresetName::Blink => "blink",
+        PresetName::BlinkLeft => "blinkLeft",
+        PresetName::BlinkRight => "blinkRight",
+        PresetName::Joy => "happy",
+        PresetName::Angry => "angry",
+        PresetName::Sorrow => "sad",
+        PresetName::Fun => "relaxed",
+        PresetName::LookUp => "lookUp",
+        PresetName::LookDown => "lookDown",
+        PresetName::LookLeft => "lookLeft",
+        PresetName::LookRight => "lookRight",
+        PresetName::Unknown => return None,
+    })
+}
+
+fn convert_spring_bone(graph: &Graph, doc: GltfDocument, vrm: Vrm) -> VrmcSpringBone {
+    let node_idx = |node: Node| doc.node_index(graph, node).map(|i| i as u32);
+    let node_count = doc.nodes(graph).len();
+
+    let mut colliders = Vec::new();
+    let mut collider_groups = Vec::new();
+    let mut converted = Vec::new();
+    let mut springs = Vec::new();
+
+    for bone_group in vrm.bone_groups(graph) {
+        let weight = bone_group.read(graph);
+
+        let mut groups = Vec::new();
+        for collider_group in bone_group.collider_groups(graph) {
+            if let Some(i) = converted.iter().position(|g| *g == collider_group) {
+                groups.push(i as u32);
+                continue;
+            }
+
+            let Some(node) = collider_group.node(graph).and_then(node_idx) else {
+                continue;
+            };
+
+            let start = colliders.len();
+            colliders.extend(
+                collider_group
+                    .read(graph)
+                    .colliders
+                    .into_iter()
+                    .map(|collider| Collider {
+                        node,
+                        shape: Shape {
+                            sphere: Some(Sphere {
+                                offset: collider.offset.map(rotate_vec3),
+                                radius: collider.radius,
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }),
+            );
+
+            groups.push(collider_groups.len() as u32);
+            converted.push(collider_group);
+            collider_groups.push(ColliderGroup {
+                colliders: (start..colliders.len()).map(|i| i as u32).collect(),
+                ..Default::default()
+            });
+        }
+
+        let center = weight
+            .center
+            .filter(|c| *c >= 0.0 && (*c as usize) < node_count)
+            .map(|c| c as u32);
+
+        for root in bone_group.bones(graph) {
+            for chain in chains(graph, root) {
+                let joints = chain
+                    .into_iter()
+                    .filter_map(node_idx)
+                    .map(|node| Joint {
+                        node,
+                        hit_radius: weight.hit_radius,
+                        stiffness: weight.stiffiness,
+                        gravity_power: weight.gravity_power,
+                        gravity_dir: Some(rotate_vec3(weight.gravity_dir.clone())),
+                        drag_force: weight.drag_force,
+                        ..Default::default()
+                    })
+                    .collect();
+
+                springs.push(Spring {
+                    name: weight.comment.clone(),
+                    joints,
+                    collider_groups: Some(groups.clone()),
+                    center,
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    VrmcSpringBone {
+        spec_version: serde_vrm::vrm1::vrmc_spring_bone::SPEC_VERSION.to_string(),
+        colliders: Some(colliders),
+        collider_groups: Some(collider_groups),
+        springs: Some(springs),
+        ..Default::default()
+    }
+}
+
+/// Splits the tree under a VRM 0.x spring root into VRM 1.0 chains.
+/// Each chain follows the first child, with other children starting new chains.
+fn chains(graph: &Graph, root: Node) -> Vec<Vec<Node>> {
+    let mut chains = Vec::new();
+    let mut starts = vec![root];
+
+    while let Some(start) = starts.pop() {
+        let mut chain = vec![start];
+        let mut current = start;
+
+        loop {
+            let children = current.children(graph);
+            let Some((first, rest)) = children.split_first() else {
+                break;
+            };
+
+            starts.extend(rest.iter().rev());
+            chain.push(*first);
+            current = *first;
+        }
+
+        chains.push(chain);
+    }
+
+    chains
+}
+
+/// Adds material extensions to the glTF JSON, returning the extensions used.
+fn convert_materials(
+    graph: &Graph,
+    doc: GltfDocument,
+    vrm: Vrm,
+    format: &mut GltfFormat,
+) -> Result<BTreeSet<&'static str>, serde_json::Error> {
+    let mut used = BTreeSet::new();
+
+    for property in vrm.material_properties(graph) {
+        let Some(idx) = property
+            .material(graph)
+            .and_then(|m| doc.material_index(graph, m))
+        else {
+            continue;
+        };
+
+        let Some(material) = format.json.materials.get_mut(idx) else {
+            continue;
+        };
+
+        let (name, value) = match property.read(graph).shader {
+            Some(Shader::MToon) => (
+                vrmc_materials_mtoon::EXTENSION_NAME,
+                serde_json::to_value(convert_mtoon(graph, doc, property))?,
+            ),
+            Some(
+                Shader::UnlitCutout
+                | Shader::UnlitTexture
+                | Shader::UnlitTransparent
+                | Shader::UnlitTransparentZWrite,
+            ) => (KHR_MATERIALS_UNLIT, serde_json::json!({})),
+            _ => continue,
+        };
+
+        material
+            .extensions
+            .get_or_insert_with(Default::default)
+            .others
+            .insert(name.to_string(), value);
+        used.insert(name);
+    }
+
+    Ok(used)
+}
+
+fn convert_mtoon(
+    graph: &Graph,
+    doc: GltfDocument,
+    property: MaterialProperty,
+) -> VrmcMaterialsMtoon {
+    let weight = property.read(graph);
+    let float = weight.float;
+    let vector = weight.vector;
+
+    let texture = |texture: Option<Texture>| {
+        texture
+            .and_then(|t| doc.texture_index(graph, t))
+            .map(|index| TextureInfo {
+                index: index as u32,
+                ..Default::default()
+            })
+    };
+    let rgb = |color: Option<[f32; 4]>| color.map(|[r, g, b, _]| [r, g, b]);
+
+    // MToon 0.x shades over smoothstep(shift, shift + 1 - toony, n·l), while
+    // MToon 1.0 shades over linearstep(-1 + toony, 1 - toony, n·l + shift).
+    // Matching the edges of both ranges gives the 1.0 factors.
+    let shade_shift = float.shade_shift.unwrap_or(0.0);
+    let shade_toony = float.shade_toony.unwrap_or(0.9);
+    let range_min = shade_shift;
+    let range_max = shade_shift + 1.0 - shade_toony;
+
+    let outline_width_mode = match float.outline_width_mode.map(|m| m as u32) {
+        Some(1) => OutlineWidthMode::WorldCoordinates,
+        Some(2) => OutlineWidthMode::ScreenCoordinates,
+        _ => OutlineWidthMode::None,
+    };
+    // Outline widths were in centimeters, or hundredths of the clip space
+    // height, which spans two units.
+    let outline_width_factor = float.outline_factor.map(|w| match outline_width_mode {
+        OutlineWidthMode::WorldCoordinates => w * 0.01,
+        OutlineWidthMode::ScreenCoordinates => w * 0.005,
+        OutlineWidthMode::None => 0.0,
+    });
+
+    VrmcMaterialsMtoon {
+        spec_version: serde_vrm::vrm1::vrmc_materials_mtoon::SPEC_VERSION.to_string(),
+        shade_color_factor: rgb(vector.shade_color),
+        shade_multiply_texture: texture(property.shade_texture(graph)),
+        shading_shift_factor: Some((-(range_max + range_min) * 0.5).clamp(-1.0, 1.0)),
+        shading_toony_factor: Some(((2.0 - (range_max - range_min)) * 0.5).clamp(0.0, 1.0)),
+        gi_equalization_factor: float.gi_intensity_factor.map(|f| 1.0 - f),
+        matcap_texture: texture(property.sphere_add(graph)),
+        parametric_rim_color_factor: rgb(vector.rim_factor),
+        rim_multiply_texture: texture(property.rim_texture(graph)),
+        rim_lighting_mix_factor: float.rim_lighting_mix_factor,
+        parametric_rim_fresnel_power_factor: float.rim_fresnel_power_factor,
+        parametric_rim_lift_factor: float.rim_lift_factor,
+        outline_width_mode: Some(outline_width_mode),
+        outline_width_factor,
+        outline_width_multiply_texture: texture(property.outline_width_texture(graph)),
+        outline_color_factor: rgb(vector.outline_color),
+        outline_lighting_mix_factor: float.outline_lighting_mix_factor,
+        uv_animation_mask_texture: texture(property.uv_anim_mask_texture(graph)),
+        // Unity's V axis points up, mirroring vertical scrolling and rotation.
+        uv_animation_scroll_x_speed_factor: float.uv_animation_scroll_x_speed_factor,
+        uv_animation_scroll_y_speed_factor: float.uv_animation_scroll_y_speed_factor.map(|s| -s),
+        uv_animation_rotation_speed_factor: float
+            .uv_animation_rotation_speed_factor
+            .map(|s| -s * TAU),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gltf_kun::graph::gltf::node::Vec3;
+    use serde_json::json;
+
+    use super::*;
+    use crate::io::tests::triangle;
+
+    fn has_null(value: &serde_json::Value) -> bool {
+        match value {
+            serde_json::Value::Null => true,
+            serde_json::Value::Array(values) => values.iter().any(has_null),
+            serde_json::Value::Object(map) => map.values().any(has_null),
+            _ => false,
+        }
+    }
+
+    fn f32s(data: &[u8]) -> Vec<f32> {
+        data.chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+
+    fn accessor(graph: &mut Graph, element_type: Type, values: &[f32]) -> Accessor {
+        let doc = GltfDocument::new(graph);
+        let mut accessor = doc.create_accessor(graph);
+
+        let weight = accessor.get_mut(graph);
+        weight.element_type = element_type;
+        weight.data = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+
+        accessor
+    }
+
+    #[test]
+    fn rotate_vectors() {
+        let mut graph = Graph::default();
+
+        let vec3 = accessor(&mut graph, Type::Vec3, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        rotate_accessor(&mut graph, vec3);
+        assert_eq!(
+            f32s(&vec3.get(&graph).data),
+            vec![-1.0, 2.0, -3.0, -4.0, 5.0, -6.0]
+        );
+
+        let vec4 = accessor(&mut graph, Type::Vec4, &[1.0, 2.0, 3.0, 4.0]);
+        rotate_accessor(&mut graph, vec4);
+        assert_eq!(f32s(&vec4.get(&graph).data), vec![-1.0, 2.0, -3.0, 4.0]);
+
+        let scalar = accessor(&mut graph, Type::Scalar, &[1.0, 2.0]);
+        rotate_accessor(&mut graph, scalar);
+        assert_eq!(f32s(&scalar.get(&graph).data), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn rotate_matrices() {
+        // R * M * R, where R negates X and Z, for a translation of (1, 2, 3)
+        // and a quarter turn around X.
+        let matrix = [
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            0.0, -1.0, 0.0, 0.0, //
+            1.0, 2.0, 3.0, 1.0, //
+        ];
+        let rotated = [
+            1.0, 0.0, 0.0, 0.0, //
+            0.0, 0.0, -1.0, 0.0, //
+            0.0, 1.0, 0.0, 0.0, //
+            -1.0, 2.0, -3.0, 1.0, //
+        ];
+
+        let mut graph = Graph::default();
+        let mat4 = accessor(&mut graph, Type::Mat4, &matrix);
+        rotate_accessor(&mut graph, mat4);
+
+        assert_eq!(f32s(&mat4.get(&graph).data), rotated);
+    }
+
+    #[test]
+    fn rotate_nodes() {
+        let mut graph = Graph::default();
+        let doc = GltfDocument::new(&mut graph);
+
+        let mut node = doc.create_node(&mut graph);
+        let weight = node.get_mut(&mut graph);
+        weight.translation = Vec3::new(1.0, 2.0, 3.0);
+        weight.rotation = Quat::from_xyzw(0.1, 0.2, 0.3, 0.9);
+
+        rotate(&mut graph, doc);
+
+        let weight = node.get(&graph);
+        assert_eq!(weight.translation, Vec3::new(-1.0, 2.0, -3.0));
+        assert_eq!(weight.rotation, Quat::from_xyzw(-0.1, 0.2, -0.3, 0.9));
+    }
+
+    #[test]
+    fn thumb_bone_names() {
+        assert_eq!(
+            bone_name(BoneName::LeftThumbProximal),
+            "leftThumbMetacarpal"
+        );
+        assert_eq!(
+            bone_name(BoneName::RightThumbIntermediate),
+            "rightThumbProximal"
+        );
+        assert_eq!(bone_name(BoneName::LeftThumbDistal), "leftThumbDistal");
+        assert_eq!(bone_name(BoneName::LeftIndexProximal), "leftIndexProximal");
+    }
+
+    #[test]
+    fn spring_chains() {
+        let mut graph = Graph::default();
+        let doc = GltfDocument::new(&mut graph);
+
+        let [root, a, b, c] = [(); 4].map(|_| doc.create_node(&mut graph));
+        root.add_child(&mut graph, &a);
+        root.add_child(&mut graph, &b);
+        a.add_child(&mut graph, &c);
+
+        assert_eq!(root.children(&graph), vec![a, b]);
+        assert_eq!(chains(&graph, root), vec![vec![root, a, c], vec![b]]);
+    }
+
+    #[test]
+    fn expressions() {
+        let mut avatar = triangle(json!({
+            "materialProperties": [{ "name": "FaceMtoon" }],
+            "blendShapeMaster": {
+                "blendShapeGroups": [{
+                    "name": "Joy",
+                    "presetName": "joy",
+                    "binds": [{ "mesh": 0, "index": 1, "weight": 50 }],
+                    "materialValues": [
+                        {
+                            "materialName": "FaceMtoon",
+                            "propertyName": "_MainTex_ST",
+                            "targetValue": [1.0, 0.5, 0.0, 0.25],
+                        },
+                        {
+                            "materialName": "Face",
+                            "propertyName": "_Color",
+                            "targetValue": [1.0, 0.0, 0.0, 1.0],
+                        },
+                        {
+                            "materialName": "Missing",
+                            "propertyName": "_Color",
+                            "targetValue": [1.0, 0.0, 0.0, 1.0],
+                        },
+                    ],
+                }],
+            },
+        }));
+
+        let graph = &mut avatar.graph;
+        let doc = avatar.doc;
+        let vrm = doc.get_extension::<Vrm>(graph).unwrap();
+
+        let expressions = convert_expressions(graph, doc, vrm);
+        assert!(expressions.custom.is_empty());
+
+        let happy = &expressions.preset["happy"];
+
+        let binds = happy.morph_target_binds.as_ref().unwrap();
+        assert_eq!(binds.len(), 1);
+        assert_eq!((binds[0].node, binds[0].index), (0, 1));
+        assert_eq!(binds[0].weight, 0.5);
+
+        // The V offset is measured from the bottom of the texture.
+        let transforms = happy.texture_transform_binds.as_ref().unwrap();
+        assert_eq!(transforms.len(), 1);
+        assert_eq!(transforms[0].material, 0);
+        assert_eq!(transforms[0].scale, Some([1.0, 0.5]));
+        assert_eq!(transforms[0].offset, Some([0.0, 0.25]));
+
+        let colors = happy.material_color_binds.as_ref().unwrap();
+        assert_eq!(colors.len(), 1);
+        assert_eq!(colors[0].type_, "color");
+        assert_eq!(colors[0].target_value, [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    /// Converts a bundled asset, loading the result.
+    fn convert_asset(name: &str) -> io::Avatar {
+        let input = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../assets")
+            .join(name)
+            .with_extension("vrm");
+        let output = std::env::temp_dir().join(format!("{name}_{}.vrm", std::process::id()));
+
+        run(Args {
+            input,
+            output: output.clone(),
+        })
+        .unwrap();
+
+        let avatar = io::load(&output).unwrap();
+        std::fs::remove_file(output).unwrap();
+
+        assert_eq!(avatar.version(), Some(VrmVersion::V1));
+        avatar
+    }
+
+    fn converted_meta(name: &str) -> Meta {
+        let avatar = convert_asset(name);
+        let vrm = avatar.extension(vrmc_vrm::EXTENSION_NAME).unwrap();
+        serde_json::from_value::<VrmcVrm>(vrm.clone()).unwrap().meta
+    }
+
+    #[test]
+    fn convert_permissions() {
+        // Catbot allows violent and sexual usage, but not commercial usage.
+        let meta = converted_meta("catbot");
+        assert_eq!(meta.avatar_permission, "everyone");
+        assert_eq!(meta.allow_excessively_violent_usage, Some(true));
+        assert_eq!(meta.allow_excessively_sexual_usage, Some(true));
+        assert_eq!(meta.commercial_usage.as_deref(), Some("personalNonProfit"));
+
+        let meta = converted_meta("cool_loops");
+        assert_eq!(meta.allow_excessively_violent_usage, Some(true));
+        assert_eq!(meta.allow_excessively_sexual_usage, Some(true));
+        assert_eq!(meta.commercial_usage.as_deref(), Some("corporation"));
+    }
+
+    #[test]
+    fn convert_catbot() {
+        let avatar = convert_asset("catbot");
+
+        // glTF does not allow null for optional properties.
+        let json = serde_json::to_value(&avatar.format.json).unwrap();
+        assert!(!has_null(&json["extensions"]));
+        assert!(!has_null(&json["materials"]));
+
+        let vrm = avatar.extension(vrmc_vrm::EXTENSION_NAME).unwrap();
+        let vrm = serde_json::from_value::<VrmcVrm>(vrm.clone()).unwrap();
+        assert!(vrm.humanoid.human_bones.contains_key("hips"));
+        assert!(!vrm.meta.name.is_empty());
+
+        let spring_bone = avatar.extension(vrmc_spring_bone::EXTENSION_NAME).unwrap();
+        let spring_bone = serde_json::from_value::<VrmcSpringBone>(spring_bone.clone()).unwrap();
+
+        let node_count = avatar.format.json.nodes.len() as u32;
+        for spring in spring_bone.springs.unwrap_or_default() {
+            assert!(spring.joints.iter().all(|joint| joint.node < node_count));
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use gltf_kun_vrm::{
+    vrm0,
+    vrm1::{vrmc_materials_mtoon, vrmc_spring_bone, vrmc_vrm},
+};
+use serde_vrm::{
+    vrm0::Shader,
+    vrm1::{vrmc_spring_bone::VrmcSpringBone, vrmc_vrm::VrmcVrm},
+};
+
+use crate::io::{self, Avatar, VrmVersion, json_name};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the avatar.
+    input: PathBuf,
+    /// Print the raw VRM extension JSON instead.
+    #[arg(long)]
+    json: bool,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let avatar = io::load(&args.input)?;
+
+    let Some(version) = avatar.version() else {
+        bail!("{} is not a VRM", args.input.display());
+    };
+
+    if args.json {
+        let names: &[&str] = match version {
+            VrmVersion::V0 => &[vrm0::EXTENSION_NAME],
+            VrmVersion::V1 => &[vrmc_vrm::EXTENSION_NAME, vrmc_spring_bone::EXTENSION_NAME],
+        };
+
+        let exts = names
+            .iter()
+            .filter_map(|name| Some((name.to_string(), avatar.extension(name)?.clone())))
+            .collect::<serde_json::Map<_, _>>();
+
+        println!("{}", serde_json::to_string_pretty(&exts)?);
+        return Ok(());
+    }
+
+    match version {
+        VrmVersion::V0 => inspect_vrm0(&avatar),
+        VrmVersion::V1 => inspect_vrm1(&avatar),
+    }
+}
+
+fn inspect_vrm0(avatar: &Avatar) -> Result<()> {
+    let ext = avatar
+        .extension(vrm0::EXTENSION_NAME)
+        .cloned()
+        .context("missing VRM extension")?;
+    let ext: serde_vrm::vrm0::Vrm = serde_json::from_value(ext)?;
+
+    println!(
+        "VRM {} ({})",
+        ext.spec_version.as_deref().unwrap_or("0.0"),
+        ext.exporter_version
+            .as_deref()
+            .unwrap_or("unknown exporter")
+    );
+
+    let meta = ext.meta.unwrap_or_default();
+    println!("\nMeta");
+    field("title", &meta.title);
+    field("version", &meta.version);
+    field("author", &meta.author);
+    field("contact", &meta.contact_information);
+    field("reference", &meta.reference);
+    field("license", &meta.license_name);
+    field("other license", &meta.other_license_url);
+    debug_field("allowed users", &meta.allowed_user_name);
+    debug_field("violent usage", &meta.violent_usage_name);
+    debug_field("sexual usage", &meta.sexual_usage_name);
+    debug_field("commercial usage", &meta.commercial_usage_name);
+
+    let bones = ext.humanoid.and_then(|h| h.human_bones).unwrap_or_default();
+    println!("\nBones ({})", bones.len());
+    for bone in bones {
+        let name = bone.bone.map(|b| json_name(&b)).unwrap_or_default();
+        let node = bone.node.map(|n| avatar.node_name(n)).unwrap_or_default();
+        println!("  {name}: node {node}");
+    }
+
+    let groups = ext
+        .blend_shape_master
+        .and_then(|m| m.blend_shape_groups)
+        .unwrap_or_default();
+    println!("\nExpressions ({})", groups.len());
+    for group in groups {
+        let name = group.name.unwrap_or_default();
+        let preset = group
+            .preset_name
+            .map(|p| format!(" [{}]", json_name(&p)))
+            .unwrap_or_default();
+        let binds = group.binds.unwrap_or_default();
+        let meshes = binds
+            .iter()
+            .filter_map(|b| b.mesh)
+            .map(|m| avatar.mesh_name(m))
+            .collect::<Vec<_>>();
+        println!(
+            "  {name}{preset}: {} binds, {} material values{}",
+            binds.len(),
+            group.material_values.unwrap_or_default().len(),
+            if meshes.is_empty() {
+                String::new()
+            } else {
+                format!(" (meshes {})", meshes.join(", "))
+            }
+        );
+    }
+
+    let secondary = ext.secondary_animation.unwrap_or_default();
+    let bone_groups = secondary.bone_groups.unwrap_or_default();
+    let collider_groups = secondary.collider_groups.unwrap_or_default();
+    println!(
+        "\nSprings ({} groups, {} collider groups)",
+        bone_groups.len(),
+        collider_groups.len()
+    );
+    for (i, group) in bone_groups.iter().enumerate() {
+        let roots = group
+            .bones
+            .iter()
+            .flatten()
+            .map(|n| avatar.node_name(*n))
+            .collect::<Vec<_>>();
+        println!(
+            "  {i}{}: stiffness {}, gravity {}, drag {}, radius {}, colliders {:?}",
+            group
+                .comment
+                .as_ref()
+                .map(|c| format!(" \"{c}\""))
+                .unwrap_or_default(),
+            number(group.stiffiness),
+            number(group.gravity_power),
+            number(group.drag_force),
+            number(group.hit_radius),
+            group.collider_groups.clone().unwrap_or_default(),
+        );
+        println!("    roots: {}", roots.join(", "));
+    }
+
+    let materials = ext.material_properties.unwrap_or_default();
+    println!("\nMaterials ({})", materials.len());
+    for (i, material) in materials.iter().enumerate() {
+        let shader = match &material.shader {
+            Some(Shader::Other(name)) => name.clone(),
+            Some(shader) => json_name(shader),
+            None => "none".to_string(),
+        };
+        println!(
+            "  {i} \"{}\": {shader}",
+            material.name.as_deref().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+fn inspect_vrm1(avatar: &Avatar) -> Result<()> {
+    let ext = avatar
+        .extension(vrmc_vrm::EXTENSION_NAME)
+        .cloned()
+        .context("missing VRMC_vrm extension")?;
+    let ext: VrmcVrm = serde_json::from_value(ext)?;
+
+    println!("VRM {}", ext.spec_version);
+
+    let meta = ext.meta;
+    println!("\nMeta");
+    field("name", &Some(meta.name));
+    field("version", &meta.version);
+    field("authors", &Some(meta.authors.join(", ")));
+    field("contact", &meta.contact_information);
+    field("references", &meta.reference.map(|r| r.join(", ")));
+    field("license", &Some(meta.license_url));
+    field("other license", &meta.other_license_url);
+    field("avatar permission", &Some(meta.avatar_permission));
+    debug_field("violent usage", &meta.allow_excessively_violent_usage);
+    debug_field("sexual usage", &meta.allow_excessively_sexual_usage);
+    field("commercial usage", &meta.commercial_usage);
+    field("credit", &meta.credit_notation);
+    debug_field("redistribution", &meta.allow_redistribution);
+    field("modification", &meta.modification);
+
+    let bones = ext.humanoid.human_bones;
+    println!("\nBones ({})", bones.len());
+    for (name, bone) in bones {
+        println!("  {name}: node {}", avatar.node_name(bone.node));
+    }
+
+    let expressions = ext.expressions.unwrap_or_default();
+    println!(
+        "\nExpressions ({})",
+        expressions.preset.len() + expressions.custom.len()
+    );
+    let all = expressions
+        .preset
+        .iter()
+        .map(|(name, e)| (format!("{name} [preset]"), e))
+        .chain(expressions.custom.iter().map(|(name, e)| (name.clone(), e)));
+    for (name, expression) in all {
+        println!(
+            "  {name}: {} morph binds, {} color binds, {} texture binds",
+            expression.morph_target_binds.iter().flatten().count(),
+            expression.material_color_binds.iter().flatten().count(),
+            expression.texture_transform_binds.iter().flatten().count(),
+        );
+    }
+
+    let springs = match avatar.extension(vrmc_spring_bone::EXTENSION_NAME) {
+        Some(ext) => serde_json::from_value::<VrmcSpringBone>(ext.clone())?,
+        None => VrmcSpringBone::default(),
+    };
+    let spring_list = springs.springs.unwrap_or_default();
+    println!(
+        "\nSprings ({} springs, {} colliders)",
+        spring_list.len(),
+        springs.colliders.unwrap_or_default().len()
+    );
+    for (i, spring) in spring_list.iter().enumerate() {
+        let joints = spring
+            .joints
+            .iter()
+            .map(|j| avatar.node_name(j.node))
+            .collect::<Vec<_>>();
+        println!(
+            "  {i}{}: {}",
+            spring
+                .name
+                .as_ref()
+                .map(|n| format!(" \"{n}\""))
+                .unwrap_or_default(),
+            joints.join(" -> ")
+        );
+    }
+
+    let materials = &avatar.format.json.materials;
+    println!("\nMaterials ({})", materials.len());
+    for (i, material) in materials.iter().enumerate() {
+        let others = material.extensions.as_ref().map(|e| &e.others);
+        let shader = if others.is_some_and(|o| o.contains_key(vrmc_materials_mtoon::EXTENSION_NAME))
+        {
+            "MToon"
+        } else if others.is_some_and(|o| o.contains_key("KHR_materials_unlit")) {
+            "unlit"
+        } else {
+            "pbr"
+        };
+        println!(
+            "  {i} \"{}\": {shader}",
+            material.name.as_deref().unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+fn field(name: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        println!("  {name}: {value}");
+    }
+}
+
+fn debug_field<T: std::fmt::Debug>(name: &str, value: &Option<T>) {
+    if let Some(value) = value {
+        println!("  {name}: {value:?}");
+    }
+}
+
+fn number(value: Option<f32>) -> String {
+    value.map(|v| v.to_string()).unwrap_or("-".to_string())
+}
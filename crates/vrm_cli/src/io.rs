@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use futures_lite::future::block_on;
+use gltf_kun::{
+    extensions::ExtensionExport,
+    graph::{Graph, gltf::GltfDocument},
+    io::format::{
+        glb::{GlbExport, GlbImport},
+        gltf::{GltfFormat, GltfImport},
+    },
+};
+use gltf_kun_vrm::{vrm0::Vrm, vrm1::vrmc_vrm};
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VrmVersion {
+    V0,
+    V1,
+}
+
+/// An avatar imported into a graph, along with its original JSON.
+pub struct Avatar {
+    pub graph: Graph,
+    pub doc: GltfDocument,
+    /// The glTF JSON as read from the file, without resources.
+    pub format: GltfFormat,
+}
+
+impl Avatar {
+    pub fn version(&self) -> Option<VrmVersion> {
+        let others = &self.format.json.extensions.as_ref()?.others;
+
+        if others.contains_key(vrmc_vrm::EXTENSION_NAME) {
+            Some(VrmVersion::V1)
+        } else if others.contains_key(gltf_kun_vrm::vrm0::EXTENSION_NAME) {
+            Some(VrmVersion::V0)
+        } else {
+            None
+        }
+    }
+
+    /// The JSON of a root extension.
+    pub fn extension(&self, name: &str) -> Option<&serde_json::Value> {
+        self.format.json.extensions.as_ref()?.others.get(name)
+    }
+
+    pub fn node_name(&self, index: u32) -> String {
+        let name = self
+            .format
+            .json
+            .nodes
+            .get(index as usize)
+            .and_then(|node| node.name.as_deref());
+        describe(index, name)
+    }
+
+    pub fn mesh_name(&self, index: u32) -> String {
+        let name = self
+            .format
+            .json
+            .meshes
+            .get(index as usize)
+            .and_then(|mesh| mesh.name.as_deref());
+        describe(index, name)
+    }
+}
+
+/// The serialized name of an enum variant, such as `leftUpperArm`.
+pub fn json_name(value: &impl Serialize) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn describe(index: u32, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{index} \"{name}\""),
+        None => index.to_string(),
+    }
+}
+
+/// Loads a `.vrm`, `.glb`, or `.gltf` file.
+/// VRM 0.x extensions are imported into the graph.
+pub fn load(path: &Path) -> Result<Avatar> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    if bytes.starts_with(b"glTF") {
+        return load_glb(&bytes);
+    }
+
+    let mut graph = Graph::default();
+    let doc = block_on(GltfImport::<Vrm>::import_file(&mut graph, path))?;
+
+    avatar(graph, doc, &bytes)
+}
+
+/// Loads a `.vrm` or `.glb` file from memory.
+pub fn load_glb(bytes: &[u8]) -> Result<Avatar> {
+    let json = glb_json(bytes)?;
+
+    let mut graph = Graph::default();
+    let doc = block_on(GlbImport::<Vrm>::import_slice(&mut graph, bytes))?;
+
+    avatar(graph, doc, json)
+}
+
+fn avatar(graph: Graph, doc: GltfDocument, json: &[u8]) -> Result<Avatar> {
+    let format = GltfFormat {
+        json: serde_json::from_slice(json).context("failed to parse glTF JSON")?,
+        ..Default::default()
+    };
+
+    Ok(Avatar { graph, doc, format })
+}
+
+/// Reads the JSON chunk of a GLB.
+fn glb_json(bytes: &[u8]) -> Result<&[u8]> {
+    let u32_at = |i: usize| {
+        bytes
+            .get(i..i + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+
+    let (Some(len), Some(b"JSON")) = (u32_at(12), bytes.get(16..20)) else {
+        bail!("invalid GLB header");
+    };
+
+    bytes.get(20..20 + len).context("truncated GLB JSON chunk")
+}
+
+/// Writes the document as a GLB, exporting extensions with `E`.
+pub fn save<E>(graph: &mut Graph, doc: GltfDocument, path: &Path) -> Result<()>
+where
+    E: ExtensionExport<GltfDocument, GltfFormat>,
+{
+    let glb = GlbExport::<E>::export(graph, &doc)?;
+    std::fs::write(path, glb.0).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Packs a glTF document and its binary buffer into a GLB.
+    pub fn glb(json: &serde_json::Value, bin: &[u8]) -> Vec<u8> {
+        let mut json = serde_json::to_vec(json).unwrap();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+
+        let mut bin = bin.to_vec();
+        while !bin.len().is_multiple_of(4) {
+            bin.push(0);
+        }
+
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+
+        let mut bytes = Vec::with_capacity(length);
+        bytes.extend_from_slice(b"glTF");
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&(length as u32).to_le_bytes());
+        bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"JSON");
+        bytes.extend_from_slice(&json);
+        bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"BIN\0");
+        bytes.extend_from_slice(&bin);
+        bytes
+    }
+
+    /// A VRM 0.x triangle with three morph targets, on mesh 0 and node 0.
+    pub fn triangle(vrm: serde_json::Value) -> Avatar {
+        let positions = [[0.0f32, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        let bin = positions
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let json = serde_json::json!({
+            "asset": { "version": "2.0" },
+            "extensionsUsed": ["VRM"],
+            "buffers": [{ "byteLength": bin.len() }],
+            "bufferViews": [{ "buffer": 0, "byteLength": bin.len() }],
+            "accessors": [{
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": 3,
+                "type": "VEC3",
+                "min": [0.0, 0.0, 0.0],
+                "max": [1.0, 1.0, 0.0],
+            }],
+            "materials": [{ "name": "Face" }],
+            "meshes": [{
+                "primitives": [{
+                    "attributes": { "POSITION": 0 },
+                    "material": 0,
+                    "targets": [{ "POSITION": 0 }, { "POSITION": 0 }, { "POSITION": 0 }],
+                }],
+                "weights": [0.1, 0.2, 0.3],
+                "extras": { "targetNames": ["a", "b", "c"] },
+            }],
+            "nodes": [{ "name": "Body", "mesh": 0 }],
+            "scenes": [{ "nodes": [0] }],
+            "scene": 0,
+            "extensions": { "VRM": vrm },
+        });
+
+        load_glb(&glb(&json, &bin)).unwrap()
+    }
+
+    #[test]
+    fn glb_version() {
+        let avatar = triangle(serde_json::json!({}));
+        assert_eq!(avatar.version(), Some(VrmVersion::V0));
+        assert_eq!(avatar.node_name(0), "0 \"Body\"");
+        assert_eq!(avatar.mesh_name(0), "0");
+    }
+}
@@ -0,0 +1,41 @@
+//! Command-line tool for [VRM](https://vrm.dev/en/) avatars.
+
+use clap::{Parser, Subcommand};
+
+mod convert;
+mod inspect;
+mod io;
+mod strip;
+mod thumbnail;
+mod validate;
+
+#[derive(Parser)]
+#[command(name = "vrm", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the meta, bones, expressions, spring bones, and materials of an avatar.
+    Inspect(inspect::Args),
+    /// Check an avatar for errors, exiting with a failure if any are found.
+    Validate(validate::Args),
+    /// Convert a VRM 0.x avatar to VRM 1.0.
+    Convert(convert::Args),
+    /// Write the thumbnail of an avatar to a file.
+    ExtractThumbnail(thumbnail::Args),
+    /// Remove blend shapes and textures the avatar does not use.
+    Strip(strip::Args),
+}
+
+fn main() -> anyhow::Result<()> {
+    match Cli::parse().command {
+        Command::Inspect(args) => inspect::run(args),
+        Command::Validate(args) => validate::run(args),
+        Command::Convert(args) => convert::run(args),
+        Command::ExtractThumbnail(args) => thumbnail::run(args),
+        Command::Strip(args) => strip::run(args),
+    }
+}
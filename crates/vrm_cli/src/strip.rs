@@ -0,0 +1,336 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result, ensure};
+use gltf_kun::graph::{
+    ByteNode, Extensions, Graph, GraphNodeWeight,
+    gltf::{GltfDocument, Mesh, animation::TargetPath},
+};
+use gltf_kun_vrm::vrm0::Vrm;
+use serde_json::value::RawValue;
+
+use crate::io::{self, VrmVersion};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the avatar.
+    input: PathBuf,
+    /// Where to write the stripped avatar.
+    output: PathBuf,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let mut avatar = io::load(&args.input)?;
+
+    ensure!(
+        avatar.version() == Some(VrmVersion::V0),
+        "strip only supports VRM 0.x avatars"
+    );
+
+    let graph = &mut avatar.graph;
+    let doc = avatar.doc;
+
+    let vrm = doc
+        .get_extension::<Vrm>(graph)
+        .context("failed to import the VRM extension, run `vrm validate` for details")?;
+
+    let morph_targets = strip_morph_targets(graph, doc, vrm);
+    let (textures, images) = strip_textures(graph, doc, vrm);
+    let accessors = strip_accessors(graph, doc);
+
+    io::save::<Vrm>(graph, doc, &args.output)?;
+
+    println!(
+        "removed {morph_targets} morph targets, {textures} textures, {images} images, and {accessors} accessors"
+    );
+    Ok(())
+}
+
+/// Removes morph targets that no blend shape group binds.
+/// Meshes with animated morph weights are left alone.
+fn strip_morph_targets(graph: &mut Graph, doc: GltfDocument, vrm: Vrm) -> usize {
+    let animated = doc
+        .animations(graph)
+        .iter()
+        .flat_map(|animation| animation.channels(graph))
+        .filter(|channel| channel.get(graph).path == TargetPath::MorphTargetWeights)
+        .filter_map(|channel| channel.target(graph)?.mesh(graph))
+        .collect::<HashSet<_>>();
+
+    let binds = vrm
+        .blend_shape_groups(graph)
+        .iter()
+        .flat_map(|group| group.binds(graph))
+        .collect::<Vec<_>>();
+
+    let mut used = HashMap::<Mesh, BTreeSet<usize>>::new();
+    for bind in &binds {
+        if let (Some(mesh), Some(index)) = (bind.mesh(graph), bind.read(graph).index) {
+            used.entry(mesh).or_default().insert(index as usize);
+        }
+    }
+
+    let mut removed = 0;
+
+    for mut mesh in doc.meshes(graph) {
+        if animated.contains(&mesh) {
+            continue;
+        }
+
+        let primitives = mesh.primitives(graph);
+        let count = primitives
+            .iter()
+            .map(|p| p.morph_targets(graph).len())
+            .max()
+            .unwrap_or_default();
+
+        let keep = used
+            .get(&mesh)
+            .map(|set| {
+                set.iter()
+                    .copied()
+                    .filter(|i| *i < count)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        if keep.len() == count {
+            continue;
+        }
+
+        for primitive in primitives {
+            let targets = primitive.morph_targets(graph);
+
+            for target in &targets {
+                primitive.remove_morph_target(graph, target);
+            }
+
+            for (new, old) in keep.iter().enumerate() {
+                if let Some(target) = targets.get(*old) {
+                    primitive.add_morph_target(graph, target, new);
+                }
+            }
+        }
+
+        let filter = |weights: &[f32]| {
+            keep.iter()
+                .filter_map(|i| weights.get(*i).copied())
+                .collect::<Vec<_>>()
+        };
+
+        let weight = mesh.get_mut(graph);
+        weight.weights = filter(&weight.weights);
+        strip_target_names(&mut weight.extras, &keep);
+
+        for mut node in mesh.nodes(graph) {
+            let weight = node.get_mut(graph);
+            weight.weights = filter(&weight.weights);
+        }
+
+        for bind in &binds {
+            if bind.mesh(graph) != Some(mesh) {
+                continue;
+            }
+
+            let mut weight = bind.read(graph);
+            weight.index = weight
+                .index
+                .and_then(|i| keep.iter().position(|k| *k == i as usize))
+                .map(|i| i as u32);
+            bind.write(graph, &weight);
+        }
+
+        removed += count - keep.len();
+    }
+
+    removed
+}
+
+/// Filters the `targetNames` extra many exporters add to meshes.
+fn strip_target_names(extras: &mut Option<Box<RawValue>>, keep: &[usize]) {
+    let Some(raw) = extras else {
+        return;
+    };
+
+    let Ok(mut value) = serde_json::from_str::<serde_json::Value>(raw.get()) else {
+        return;
+    };
+
+    let Some(names) = value.get_mut("targetNames").and_then(|n| n.as_array_mut()) else {
+        return;
+    };
+
+    *names = keep.iter().filter_map(|i| names.get(*i).cloned()).collect();
+
+    if let Ok(new) = serde_json::value::to_raw_value(&value) {
+        *raw = new;
+    }
+}
+
+/// Removes textures no material or thumbnail references, and then unused images.
+fn strip_textures(graph: &mut Graph, doc: GltfDocument, vrm: Vrm) -> (usize, usize) {
+    let mut used = HashSet::new();
+
+    for material in doc.materials(graph) {
+        used.extend(
+            [
+                material.base_color_texture(graph),
+                material.emissive_texture(graph),
+                material.metallic_roughness_texture(graph),
+                material.normal_texture(graph),
+                material.occlusion_texture(graph),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+    }
+
+    for property in vrm.material_properties(graph) {
+        used.extend(
+            [
+                property.main_texture(graph),
+                property.shade_texture(graph),
+                property.bump_map(graph),
+                property.sphere_add(graph),
+                property.emission_map(graph),
+                property.rim_texture(graph),
+                property.outline_width_texture(graph),
+                property.uv_anim_mask_texture(graph),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+    }
+
+    used.extend(vrm.thumbnail(graph));
+
+    let mut textures = 0;
+    for texture in doc.textures(graph) {
+        if !used.contains(&texture) {
+            doc.remove_texture(graph, texture);
+            textures += 1;
+        }
+    }
+
+    let used_images = doc
+        .textures(graph)
+        .iter()
+        .filter_map(|texture| texture.image(graph))
+        .collect::<HashSet<_>>();
+
+    let mut images = 0;
+    for image in doc.images(graph) {
+        if !used_images.contains(&image) {
+            doc.remove_image(graph, image);
+            images += 1;
+        }
+    }
+
+    (textures, images)
+}
+
+/// Removes accessors left unreferenced, such as those of removed morph targets.
+fn strip_accessors(graph: &mut Graph, doc: GltfDocument) -> usize {
+    let mut used = HashSet::new();
+
+    for mesh in doc.meshes(graph) {
+        for primitive in mesh.primitives(graph) {
+            used.extend(primitive.attributes(graph).into_iter().map(|(_, a)| a));
+            used.extend(primitive.indices(graph));
+
+            for target in primitive.morph_targets(graph) {
+                used.extend(target.attributes(graph).into_iter().map(|(_, a)| a));
+            }
+        }
+    }
+
+    for skin in doc.skins(graph) {
+        used.extend(skin.inverse_bind_matrices(graph));
+    }
+
+    for animation in doc.animations(graph) {
+        for channel in animation.channels(graph) {
+            if let Some(sampler) = channel.sampler(graph) {
+                used.extend(sampler.input(graph));
+                used.extend(sampler.output(graph));
+            }
+        }
+    }
+
+    let mut removed = 0;
+    for accessor in doc.accessors(graph) {
+        if !used.contains(&accessor) {
+            doc.remove_accessor(graph, accessor);
+            removed += 1;
+        }
+    }
+
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::io::tests::triangle;
+
+    #[test]
+    fn strip_unbound_morph_targets() {
+        let mut avatar = triangle(json!({
+            "blendShapeMaster": {
+                "blendShapeGroups": [
+                    { "name": "A", "binds": [{ "mesh": 0, "index": 0, "weight": 100 }] },
+                    { "name": "C", "binds": [{ "mesh": 0, "index": 2, "weight": 100 }] },
+                ],
+            },
+        }));
+
+        let graph = &mut avatar.graph;
+        let doc = avatar.doc;
+        let vrm = doc.get_extension::<Vrm>(graph).unwrap();
+
+        assert_eq!(strip_morph_targets(graph, doc, vrm), 1);
+
+        let mesh = doc.meshes(graph)[0];
+        assert_eq!(mesh.primitives(graph)[0].morph_targets(graph).len(), 2);
+
+        let weight = mesh.get(graph);
+        assert_eq!(weight.weights, vec![0.1, 0.3]);
+
+        let extras = weight.extras.as_ref().unwrap().get();
+        let extras = serde_json::from_str::<serde_json::Value>(extras).unwrap();
+        assert_eq!(extras["targetNames"], json!(["a", "c"]));
+
+        let indices = vrm
+            .blend_shape_groups(graph)
+            .iter()
+            .flat_map(|group| group.binds(graph))
+            .map(|bind| bind.read(graph).index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec![Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn keep_bound_morph_targets() {
+        let mut avatar = triangle(json!({
+            "blendShapeMaster": {
+                "blendShapeGroups": [{
+                    "binds": [
+                        { "mesh": 0, "index": 0 },
+                        { "mesh": 0, "index": 1 },
+                        { "mesh": 0, "index": 2 },
+                    ],
+                }],
+            },
+        }));
+
+        let graph = &mut avatar.graph;
+        let doc = avatar.doc;
+        let vrm = doc.get_extension::<Vrm>(graph).unwrap();
+
+        assert_eq!(strip_morph_targets(graph, doc, vrm), 0);
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use gltf_kun::graph::{Extensions, GraphNodeWeight, gltf::Image};
+use gltf_kun_vrm::vrm0::Vrm;
+use serde_vrm::vrm1::vrmc_vrm::VrmcVrm;
+
+use crate::io::{self, VrmVersion};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the avatar.
+    input: PathBuf,
+    /// Where to write the image. Defaults to the input path, with the image's extension.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let avatar = io::load(&args.input)?;
+    let graph = &avatar.graph;
+
+    let image: Option<Image> = match avatar.version() {
+        Some(VrmVersion::V0) => avatar
+            .doc
+            .get_extension::<Vrm>(graph)
+            .and_then(|vrm| vrm.thumbnail(graph))
+            .and_then(|texture| texture.image(graph)),
+        Some(VrmVersion::V1) => {
+            let ext = avatar
+                .extension(gltf_kun_vrm::vrm1::vrmc_vrm::EXTENSION_NAME)
+                .cloned()
+                .unwrap_or_default();
+            let ext: VrmcVrm = serde_json::from_value(ext)?;
+            ext.meta
+                .thumbnail_image
+                .and_then(|idx| avatar.doc.images(graph).get(idx as usize).copied())
+        }
+        None => bail!("{} is not a VRM", args.input.display()),
+    };
+
+    let image = image.context("avatar has no thumbnail")?.get(graph);
+
+    if image.data.is_empty() {
+        bail!("thumbnail has no data");
+    }
+
+    let extension = match image.mime_type.as_deref() {
+        Some("image/jpeg") => "jpg",
+        Some("image/png") => "png",
+        _ if image.data.starts_with(&[0xFF, 0xD8]) => "jpg",
+        _ => "png",
+    };
+
+    let output = args
+        .output
+        .unwrap_or_else(|| args.input.with_extension(extension));
+
+    std::fs::write(&output, &image.data)
+        .with_context(|| format!("failed to write {}", output.display()))?;
+
+    println!("wrote {}", output.display());
+    Ok(())
+}
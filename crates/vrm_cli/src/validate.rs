@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use gltf_kun_vrm::vrm0::validate::{Severity, ValidationOptions, validate, validate_format};
+
+use crate::io::{self, VrmVersion};
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Path to the avatar.
+    input: PathBuf,
+    /// Largest allowed texture width or height, in pixels. 0 for no limit.
+    #[arg(long, default_value_t = 4096)]
+    max_texture_size: u32,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let avatar = io::load(&args.input)?;
+
+    if avatar.version() == Some(VrmVersion::V1) {
+        bail!("validation only supports VRM 0.x avatars");
+    }
+
+    let options = ValidationOptions {
+        max_texture_size: (args.max_texture_size > 0).then_some(args.max_texture_size),
+    };
+
+    let mut report = validate_format(&avatar.format);
+
+    // Out of range indices fail the import, leaving nothing more to check.
+    if report.diagnostics.is_empty() {
+        report.append(validate(&avatar.graph, avatar.doc, &options));
+    }
+
+    for diagnostic in &report.diagnostics {
+        let severity = match diagnostic.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        println!("{severity}: {diagnostic}");
+    }
+
+    let errors = report.errors().count();
+    let warnings = report.warnings().count();
+
+    if errors > 0 {
+        bail!("{errors} errors, {warnings} warnings");
+    }
+
+    println!("valid, {warnings} warnings");
+    Ok(())
+}
@@ -2,14 +2,12 @@
 
 use std::f32::consts::PI;
 
-use bevy::{asset::AssetMetaCheck, prelude::*, render::view::RenderLayers};
+use bevy::{asset::AssetMetaCheck, prelude::*};
 use bevy_egui::EguiPlugin;
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 use bevy_vrm::{
     VrmBundle, VrmInstance, VrmPlugins, VrmScene,
-    first_person::{FirstPersonFlag, RENDER_LAYERS, SetupFirstPerson},
-    loader::Vrm,
-    mtoon::MtoonSun,
+    first_person::{AutoFirstPerson, FirstPersonFlag, FirstPersonLayers},
 };
 use ui::RenderLayer;
 
@@ -49,7 +47,6 @@ impl Plugin for VrmViewerPlugin {
                     move_leg::move_leg,
                     read_dropped_files,
                     set_render_layers,
-                    setup_first_person,
                     ui::update_ui,
                 ),
             );
@@ -81,7 +78,6 @@ fn setup(mut commands: Commands, mut settings: ResMut<Settings>) {
             ..default()
         },
         Transform::from_rotation(Quat::from_rotation_x(-PI / 3.0)),
-        MtoonSun,
     ));
 
     let mut transform = Transform::default();
@@ -105,28 +101,12 @@ fn set_render_layers(
         if flag != *prev {
             *prev = flag;
 
-            let layers = RenderLayers::layer(0).union(&RENDER_LAYERS[&flag]);
+            let layers = FirstPersonLayers::default().camera_layers(flag);
             commands.entity(entity).insert(layers);
         }
     }
 }
 
-fn setup_first_person(
-    mut events: EventReader<AssetEvent<Vrm>>,
-    mut writer: EventWriter<SetupFirstPerson>,
-    vrms: Query<(Entity, &VrmInstance)>,
-) {
-    for event in events.read() {
-        if let AssetEvent::LoadedWithDependencies { id } = event {
-            let (ent, _) = vrms
-                .iter()
-                .find(|(_, handle)| handle.0.id() == *id)
-                .unwrap();
-            writer.write(SetupFirstPerson(ent));
-        }
-    }
-}
-
 fn load_model(
     asset_server: Res<AssetServer>,
     mut commands: Commands,
@@ -147,6 +127,7 @@ fn load_model(
 
     commands.spawn((
         transform,
+        AutoFirstPerson,
         VrmBundle {
             scene: VrmScene::default(),
             vrm: VrmInstance(asset_server.load(settings.model.clone())),